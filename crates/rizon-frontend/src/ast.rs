@@ -1,2 +1,2 @@
 pub mod expr;
-pub mod stmt;
\ No newline at end of file
+pub mod stmt;pub mod rewrite;
@@ -3,7 +3,9 @@ use ecow::EcoString;
 
 use rizon_tools::results::{Loc, RizonReport, RizonResult};
 
-use crate::lexer::Token;
+use crate::lexer::{Token, TokenKind};
+
+use super::stmt::VarTypeDecl;
 
 
 #[derive(Debug, PartialEq, Clone)]
@@ -13,60 +15,146 @@ pub enum Expr {
     IntLiteral(IntLiteralExpr),
     FloatLiteral(FloatLiteralExpr),
     StrLiteral(StrLiteralExpr),
+    CharLiteral(CharLiteralExpr),
     Identifier(IdentifierExpr),
     Unary(UnaryExpr),
     Assign(AssignExpr),
+    // Sugar, lowered by the normalize pass
+    CompoundAssign(CompoundAssignExpr),
     Logical(LogicalExpr),
+    ChainedCompare(ChainedCompareExpr),
     Call(CallExpr),
     Get(GetExpr),
     Set(SetExpr),
     Selff(SelfExpr),
     Is(IsExpr),
+    Cast(CastExpr),
+    If(IfExpr),
+    ArrayLiteral(ArrayLiteralExpr),
+    MapLiteral(MapLiteralExpr),
+    StructLiteral(StructLiteralExpr),
+    Index(IndexExpr),
+    IndexSet(IndexSetExpr),
+    Range(RangeExpr),
 }
 
+// Source code like representation, used by 'dbg'
 impl Display for Expr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Expr::Binary(e) => write!(f, "{} {} {}", e.left, e.operator, e.right),
-            Expr::Grouping(e) => write!(f, "{}", e.expr),
+            Expr::Grouping(e) => write!(f, "({})", e.expr),
             Expr::IntLiteral(e) => write!(f, "{}", e.value),
-            Expr::FloatLiteral(e) => write!(f, "{}", e.value),
-            Expr::StrLiteral(e) => write!(f, "{}", e.value),
+            Expr::FloatLiteral(e) => write!(f, "{:?}", e.value),
+            Expr::StrLiteral(e) => write!(f, "\"{}\"", e.value),
+            Expr::CharLiteral(e) => write!(f, "{:?}", e.value),
             Expr::Identifier(e) => write!(f, "{}", e.name),
-            Expr::Unary(e) => write!(f, "{} {}", e.operator, e.right),
-            Expr::Assign(e) => write!(f, "{} {}", e.name, e.value),
+            Expr::Unary(e) => write!(f, "{}{}", e.operator, e.right),
+            Expr::Assign(e) => write!(f, "{} = {}", e.name, e.value),
+            Expr::CompoundAssign(e) => match e.operator.kind {
+                TokenKind::PlusPlus | TokenKind::MinusMinus => {
+                    write!(f, "{}{}", e.target, e.operator.value)
+                }
+                _ => write!(f, "{} {} {}", e.target, e.operator.value, e.value),
+            },
             Expr::Logical(e) => write!(f, "{} {} {}", e.left, e.operator, e.right),
-            Expr::Call(e) => write!(f, "{}: {:?}", e.callee, e.args),
-            Expr::Get(e) => write!(f, "{}: {}", e.object, e.name),
-            Expr::Set(e) => write!(f, "{}: {} {}", e.object, e.name, e.value),
+            Expr::ChainedCompare(e) => {
+                write!(f, "{}", e.operands[0])?;
+
+                for (operator, operand) in e.operators.iter().zip(&e.operands[1..]) {
+                    write!(f, " {} {}", operator, operand)?;
+                }
+
+                Ok(())
+            }
+            Expr::Call(e) => {
+                let args = e
+                    .args
+                    .iter()
+                    .zip(&e.arg_names)
+                    .map(|(a, n)| match n {
+                        Some(n) => format!("{}: {}", n.value, a),
+                        None => a.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                write!(f, "{}({})", e.callee, args)
+            }
+            Expr::Get(e) => write!(f, "{}.{}", e.object, e.name),
+            Expr::Set(e) => write!(f, "{}.{} = {}", e.object, e.name, e.value),
             Expr::Selff(_) => write!(f, "self"),
-            Expr::Is(e) => write!(f, "{} is {}", e.left, e.typ.value),
+            Expr::Is(e) => write!(f, "{} is {}", e.left, e.typ),
+            Expr::Cast(e) => write!(f, "{} as {}", e.expr, e.typ.value),
+            Expr::If(e) => {
+                write!(f, "if {} {{ {} }}", e.condition, e.then_expr)?;
+
+                match e.else_expr.as_deref() {
+                    Some(els @ Expr::If(_)) => write!(f, " else {}", els),
+                    Some(els) => write!(f, " else {{ {} }}", els),
+                    None => Ok(()),
+                }
+            }
+            Expr::ArrayLiteral(e) => write!(f, "[{}]", join(&e.values)),
+            Expr::MapLiteral(e) => {
+                let entries: Vec<String> = e.entries.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Expr::StructLiteral(e) if e.fields.is_empty() => write!(f, "{} {{}}", e.strukt),
+            Expr::StructLiteral(e) => {
+                let fields: Vec<String> = e.fields.iter().map(|(n, v)| format!("{}: {}", n.value, v)).collect();
+
+                write!(f, "{} {{ {} }}", e.strukt, fields.join(", "))
+            }
+            Expr::Index(e) => write!(f, "{}[{}]", e.object, e.index),
+            Expr::IndexSet(e) => write!(f, "{}[{}] = {}", e.object, e.index, e.value),
+            Expr::Range(e) => {
+                let op = if e.inclusive { "..=" } else { ".." };
+
+                write!(f, "{}{}{}", e.start, op, e.end)?;
+
+                match &e.step {
+                    Some(s) => write!(f, ":{}", s),
+                    None => Ok(()),
+                }
+            }
         }
     }
 }
 
+fn join(exprs: &[Expr]) -> String {
+    exprs.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", ")
+}
+
 impl Expr {
     pub fn get_loc(&self) -> Loc {
         match self {
             Self::Binary(b) => b.get_loc(),
-            Self::Grouping(g) => {
-                let mut loc = g.expr.get_loc();
-                loc.start -= 1;
-                loc.end += 1;
-                loc
-            },
+            Self::Grouping(g) => g.loc.clone(),
             Self::IntLiteral(i) => i.loc.clone(),
             Self::FloatLiteral(r) => r.loc.clone(),
             Self::StrLiteral(s) => s.loc.clone(),
+            Self::CharLiteral(c) => c.loc.clone(),
             Self::Identifier(i) => i.loc.clone(),
-            Self::Unary(u) => u.right.get_loc(),
+            Self::Unary(u) => Loc::span(&u.operator.loc, &u.right.get_loc()),
             Self::Assign(a) => a.loc.clone(),
+            Self::CompoundAssign(a) => a.loc.clone(),
             Self::Logical(l) => l.loc.clone(),
+            Self::ChainedCompare(c) => c.loc.clone(),
             Self::Call(c) => c.loc.clone(),
-            Self::Get(g) => g.object.get_loc(),
+            Self::Get(g) => Loc::span(&g.object.get_loc(), &g.loc),
             Self::Set(s) => s.loc.clone(),
             Self::Selff(s) => s.loc.clone(),
             Self::Is(i) => i.loc.clone(),
+            Self::Cast(c) => c.loc.clone(),
+            Self::If(i) => i.loc.clone(),
+            Self::ArrayLiteral(a) => a.loc.clone(),
+            Self::MapLiteral(m) => m.loc.clone(),
+            Self::StructLiteral(s) => s.loc.clone(),
+            Self::Index(i) => i.loc.clone(),
+            Self::IndexSet(i) => i.loc.clone(),
+            Self::Range(r) => r.loc.clone(),
         }
     }
 }
@@ -83,13 +171,15 @@ impl BinaryExpr {
         let l = self.left.get_loc();
         let r = self.right.get_loc();
 
-        Loc::new(l.start, r.end)
+        Loc::span(&l, &r)
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct GroupingExpr {
     pub expr: Box<Expr>,
+    // Parentheses included
+    pub loc: Loc,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -110,6 +200,12 @@ pub struct StrLiteralExpr {
     pub loc: Loc,
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct CharLiteralExpr {
+    pub value: char,
+    pub loc: Loc,
+}
+
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct IdentifierExpr {
@@ -132,6 +228,15 @@ pub struct AssignExpr {
     pub loc: Loc,
 }
 
+// x += 1 or x++, target is either an identifier, a field or an index
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompoundAssignExpr {
+    pub target: Box<Expr>,
+    pub operator: Token,
+    pub value: Box<Expr>,
+    pub loc: Loc,
+}
+
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct LogicalExpr {
@@ -141,10 +246,21 @@ pub struct LogicalExpr {
     pub loc: Loc,
 }
 
+// Comparisons going in one direction: a < b <= c. Each operand is
+// evaluated once, from left to right, until a comparison is false
+#[derive(Debug, PartialEq, Clone)]
+pub struct ChainedCompareExpr {
+    pub operands: Vec<Expr>,
+    pub operators: Vec<Token>,
+    pub loc: Loc,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct CallExpr {
     pub callee: Box<Expr>,
     pub args: Vec<Expr>,
+    // Name of each argument, if given as 'name: value'
+    pub arg_names: Vec<Option<Token>>,
     pub loc: Loc,
 }
 
@@ -160,6 +276,9 @@ pub struct SetExpr {
     pub object: Box<Expr>,
     pub name: Token,
     pub value: Box<Expr>,
+    // Lowered from 'a.b += v', the value is 'a.b + v' and its left
+    // operand is read from the object already evaluated
+    pub compound: bool,
     pub loc: Loc,
 }
 
@@ -172,10 +291,74 @@ pub struct SelfExpr {
 #[derive(Debug, PartialEq, Clone)]
 pub struct IsExpr {
     pub left: Box<Expr>,
+    pub typ: VarTypeDecl,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CastExpr {
+    pub expr: Box<Expr>,
     pub typ: Token,
     pub loc: Loc,
 }
 
+// Branches are single expressions: if a { 1 } else if b { 2 } else { 3 }
+#[derive(Debug, PartialEq, Clone)]
+pub struct IfExpr {
+    pub condition: Box<Expr>,
+    pub then_expr: Box<Expr>,
+    pub else_expr: Option<Box<Expr>>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ArrayLiteralExpr {
+    pub values: Vec<Expr>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MapLiteralExpr {
+    pub entries: Vec<(Expr, Expr)>,
+    pub loc: Loc,
+}
+
+// Foo { a: 1, b: 2 }
+#[derive(Debug, PartialEq, Clone)]
+pub struct StructLiteralExpr {
+    pub strukt: Box<Expr>,
+    pub fields: Vec<(Token, Expr)>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IndexExpr {
+    pub object: Box<Expr>,
+    pub index: Box<Expr>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct IndexSetExpr {
+    pub object: Box<Expr>,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+    // Lowered from 'a[i] += v', the object and index are evaluated once
+    pub compound: bool,
+    pub loc: Loc,
+}
+
+// End is excluded unless inclusive: 0..3 or 0..=2. The step is 1 by
+// default, a negative one counts down: 10..0:-2
+#[derive(Debug, PartialEq, Clone)]
+pub struct RangeExpr {
+    pub start: Box<Expr>,
+    pub end: Box<Expr>,
+    pub step: Option<Box<Expr>>,
+    pub inclusive: bool,
+    pub loc: Loc,
+}
+
 
 impl Expr {
     pub fn accept<T, U: RizonReport>(
@@ -188,15 +371,28 @@ impl Expr {
             Expr::IntLiteral(e) => visitor.visit_int_literal_expr(e),
             Expr::FloatLiteral(e) => visitor.visit_float_literal_expr(e),
             Expr::StrLiteral(e) => visitor.visit_str_literal_expr(e),
+            Expr::CharLiteral(e) => visitor.visit_char_literal_expr(e),
             Expr::Identifier(e) => visitor.visit_identifier_expr(e),
             Expr::Unary(e) => visitor.visit_unary_expr(e),
             Expr::Assign(e) => visitor.visit_assign_expr(e),
+            Expr::CompoundAssign(_) => {
+                unreachable!("compound assignments are lowered by the normalize pass")
+            }
             Expr::Logical(e) => visitor.visit_logical_expr(e),
+            Expr::ChainedCompare(e) => visitor.visit_chained_compare_expr(e),
             Expr::Call(e) => visitor.visit_call_expr(e),
             Expr::Get(e) => visitor.visit_get_expr(e),
             Expr::Set(e) => visitor.visit_set_expr(e),
             Expr::Selff(e) => visitor.visit_self_expr(e),
             Expr::Is(e) => visitor.visit_is_expr(e),
+            Expr::Cast(e) => visitor.visit_cast_expr(e),
+            Expr::If(e) => visitor.visit_if_expr(e),
+            Expr::ArrayLiteral(e) => visitor.visit_array_literal_expr(e),
+            Expr::MapLiteral(e) => visitor.visit_map_literal_expr(e),
+            Expr::StructLiteral(e) => visitor.visit_struct_literal_expr(e),
+            Expr::Index(e) => visitor.visit_index_expr(e),
+            Expr::IndexSet(e) => visitor.visit_index_set_expr(e),
+            Expr::Range(e) => visitor.visit_range_expr(e),
         }
     }
 }
@@ -207,15 +403,25 @@ pub trait VisitExpr<T, U: RizonReport> {
     fn visit_int_literal_expr(&mut self, expr: &IntLiteralExpr) -> Result<T, RizonResult<U>>;
     fn visit_float_literal_expr(&mut self, expr: &FloatLiteralExpr) -> Result<T, RizonResult<U>>;
     fn visit_str_literal_expr(&mut self, expr: &StrLiteralExpr) -> Result<T, RizonResult<U>>;
+    fn visit_char_literal_expr(&mut self, expr: &CharLiteralExpr) -> Result<T, RizonResult<U>>;
     fn visit_identifier_expr(&mut self, expr: &IdentifierExpr) -> Result<T, RizonResult<U>>;
     fn visit_unary_expr(&mut self, expr: &UnaryExpr) -> Result<T, RizonResult<U>>;
     fn visit_assign_expr(&mut self, expr: &AssignExpr) -> Result<T, RizonResult<U>>;
     fn visit_logical_expr(&mut self, expr: &LogicalExpr) -> Result<T, RizonResult<U>>;
+    fn visit_chained_compare_expr(&mut self, expr: &ChainedCompareExpr) -> Result<T, RizonResult<U>>;
     fn visit_call_expr(&mut self, expr: &CallExpr) -> Result<T, RizonResult<U>>;
     fn visit_get_expr(&mut self, expr: &GetExpr) -> Result<T, RizonResult<U>>;
     fn visit_set_expr(&mut self, expr: &SetExpr) -> Result<T, RizonResult<U>>;
     fn visit_self_expr(&mut self, expr: &SelfExpr) -> Result<T, RizonResult<U>>;
     fn visit_is_expr(&mut self, expr: &IsExpr) -> Result<T, RizonResult<U>>;
+    fn visit_cast_expr(&mut self, expr: &CastExpr) -> Result<T, RizonResult<U>>;
+    fn visit_if_expr(&mut self, expr: &IfExpr) -> Result<T, RizonResult<U>>;
+    fn visit_array_literal_expr(&mut self, expr: &ArrayLiteralExpr) -> Result<T, RizonResult<U>>;
+    fn visit_map_literal_expr(&mut self, expr: &MapLiteralExpr) -> Result<T, RizonResult<U>>;
+    fn visit_struct_literal_expr(&mut self, expr: &StructLiteralExpr) -> Result<T, RizonResult<U>>;
+    fn visit_index_expr(&mut self, expr: &IndexExpr) -> Result<T, RizonResult<U>>;
+    fn visit_index_set_expr(&mut self, expr: &IndexSetExpr) -> Result<T, RizonResult<U>>;
+    fn visit_range_expr(&mut self, expr: &RangeExpr) -> Result<T, RizonResult<U>>;
 }
 
 // Into
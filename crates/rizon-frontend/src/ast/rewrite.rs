@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use super::{
+    expr::Expr,
+    stmt::{BlockStmt, FnDeclStmt, MatchPattern, Stmt, VarDeclStmt},
+};
+
+
+// AST pass rewriting expressions in place
+pub trait Rewriter {
+    // Expressions left untouched, with all their children
+    fn skip(&self, _expr: &Expr) -> bool {
+        false
+    }
+
+    // Children are already rewritten when their parent is
+    fn rewrite(&mut self, expr: &mut Expr);
+
+    // Called before the statement's children are visited
+    fn rewrite_stmt(&mut self, _stmt: &mut Stmt) {}
+}
+
+pub fn rewrite_exprs(stmts: &mut [Stmt], f: &mut impl Rewriter) {
+    stmts.iter_mut().for_each(|s| rewrite_stmt(s, f));
+}
+
+fn rewrite_stmt(stmt: &mut Stmt, f: &mut impl Rewriter) {
+    f.rewrite_stmt(stmt);
+
+    match stmt {
+        Stmt::Expr(s) => rewrite_expr(&mut s.expr, f),
+        Stmt::Print(s) => s.exprs.iter_mut().for_each(|e| rewrite_expr(e, f)),
+        Stmt::VarDecl(s) => rewrite_var_decl(s, f),
+        Stmt::Block(s) => rewrite_block(s, f),
+        Stmt::If(s) => {
+            rewrite_expr(&mut s.condition, f);
+            s.then_branch.iter_mut().for_each(|b| rewrite_block(b, f));
+            s.else_branch.iter_mut().for_each(|b| rewrite_block(b, f));
+        }
+        Stmt::While(s) => {
+            rewrite_expr(&mut s.condition, f);
+            rewrite_stmt(&mut s.body, f);
+        }
+        Stmt::DoWhile(s) => {
+            rewrite_stmt(&mut s.body, f);
+            rewrite_expr(&mut s.condition, f);
+        }
+        Stmt::For(s) => {
+            rewrite_var_decl(&mut s.placeholder, f);
+            rewrite_expr(&mut s.iterable, f);
+            rewrite_stmt(&mut s.body, f);
+        }
+        Stmt::FnDecl(s) => rewrite_fn_decl(s, f),
+        Stmt::Return(s) => s.value.iter_mut().for_each(|e| rewrite_expr(e, f)),
+        Stmt::Struct(s) => {
+            s.fields.iter_mut().for_each(|d| rewrite_var_decl(d, f));
+            s.methods.iter_mut().for_each(|d| rewrite_fn_decl(d, f));
+        }
+        Stmt::Match(s) => {
+            rewrite_expr(&mut s.expr, f);
+
+            for arm in s.arms.iter_mut() {
+                if let MatchPattern::Value(e) = &mut arm.pattern {
+                    rewrite_expr(e, f);
+                }
+
+                rewrite_stmt(&mut arm.body, f);
+            }
+        }
+        Stmt::EnumDecl(s) => s.variants.iter_mut().filter_map(|v| v.value.as_mut()).for_each(|e| rewrite_expr(e, f)),
+        Stmt::Break(_) | Stmt::Continue(_) | Stmt::TypeAlias(_) => {}
+    }
+}
+
+fn rewrite_block(block: &mut BlockStmt, f: &mut impl Rewriter) {
+    block.stmts.iter_mut().for_each(|s| rewrite_stmt(s, f));
+}
+
+fn rewrite_var_decl(decl: &mut VarDeclStmt, f: &mut impl Rewriter) {
+    decl.value.iter_mut().for_each(|e| rewrite_expr(e, f));
+}
+
+fn rewrite_fn_decl(decl: &mut FnDeclStmt, f: &mut impl Rewriter) {
+    // The body is only shared once the function is declared at runtime
+    let body = Arc::get_mut(&mut decl.body).expect("function body shared before rewriting");
+
+    rewrite_block(body, f);
+}
+
+fn rewrite_expr(expr: &mut Expr, f: &mut impl Rewriter) {
+    if f.skip(expr) {
+        return;
+    }
+
+    match expr {
+        Expr::Binary(e) => {
+            rewrite_expr(&mut e.left, f);
+            rewrite_expr(&mut e.right, f);
+        }
+        Expr::Grouping(e) => rewrite_expr(&mut e.expr, f),
+        Expr::Unary(e) => rewrite_expr(&mut e.right, f),
+        Expr::Assign(e) => rewrite_expr(&mut e.value, f),
+        Expr::CompoundAssign(e) => {
+            rewrite_expr(&mut e.target, f);
+            rewrite_expr(&mut e.value, f);
+        }
+        Expr::Logical(e) => {
+            rewrite_expr(&mut e.left, f);
+            rewrite_expr(&mut e.right, f);
+        }
+        Expr::ChainedCompare(e) => e.operands.iter_mut().for_each(|o| rewrite_expr(o, f)),
+        Expr::Call(e) => {
+            rewrite_expr(&mut e.callee, f);
+            e.args.iter_mut().for_each(|a| rewrite_expr(a, f));
+        }
+        Expr::Get(e) => rewrite_expr(&mut e.object, f),
+        Expr::Set(e) => {
+            rewrite_expr(&mut e.object, f);
+            rewrite_expr(&mut e.value, f);
+        }
+        Expr::Is(e) => rewrite_expr(&mut e.left, f),
+        Expr::Cast(e) => rewrite_expr(&mut e.expr, f),
+        Expr::If(e) => {
+            rewrite_expr(&mut e.condition, f);
+            rewrite_expr(&mut e.then_expr, f);
+            e.else_expr.iter_mut().for_each(|e| rewrite_expr(e, f));
+        }
+        Expr::ArrayLiteral(e) => e.values.iter_mut().for_each(|v| rewrite_expr(v, f)),
+        Expr::MapLiteral(e) => {
+            for (key, value) in e.entries.iter_mut() {
+                rewrite_expr(key, f);
+                rewrite_expr(value, f);
+            }
+        }
+        Expr::StructLiteral(e) => {
+            rewrite_expr(&mut e.strukt, f);
+            e.fields.iter_mut().for_each(|(_, v)| rewrite_expr(v, f));
+        }
+        Expr::Index(e) => {
+            rewrite_expr(&mut e.object, f);
+            rewrite_expr(&mut e.index, f);
+        }
+        Expr::IndexSet(e) => {
+            rewrite_expr(&mut e.object, f);
+            rewrite_expr(&mut e.index, f);
+            rewrite_expr(&mut e.value, f);
+        }
+        Expr::Range(e) => {
+            rewrite_expr(&mut e.start, f);
+            rewrite_expr(&mut e.end, f);
+            e.step.iter_mut().for_each(|s| rewrite_expr(s, f));
+        }
+        Expr::IntLiteral(_)
+        | Expr::FloatLiteral(_)
+        | Expr::StrLiteral(_)
+        | Expr::CharLiteral(_)
+        | Expr::Identifier(_)
+        | Expr::Selff(_) => {}
+    }
+
+    f.rewrite(expr);
+}
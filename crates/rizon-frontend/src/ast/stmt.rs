@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{fmt::Display, sync::Arc};
 
 use crate::lexer::Token;
 
@@ -11,9 +11,22 @@ pub enum VarTypeDecl {
     Identifier(Token),
     Fn {
         fn_tk: Token,  // For error reporting
-        param_types: Vec<Token>,
+        param_types: Vec<VarTypeDecl>,
         return_type: Option<Box<VarTypeDecl>>
-    }
+    },
+    Array {
+        typ: Box<VarTypeDecl>,
+        loc: Loc,
+    },
+    Map {
+        key: Box<VarTypeDecl>,
+        value: Box<VarTypeDecl>,
+        loc: Loc,
+    },
+    Optional {
+        typ: Box<VarTypeDecl>,
+        loc: Loc,
+    },
 }
 
 impl VarTypeDecl {
@@ -23,13 +36,35 @@ impl VarTypeDecl {
             VarTypeDecl::Fn { fn_tk, param_types, return_type } => {
                 let end = return_type
                     .as_ref()
-                    .map(|r| r.get_loc().end)
+                    .map(|r| r.get_loc())
                     .unwrap_or_else(|| param_types
                         .last()
-                        .map_or(fn_tk.loc.end, |p| p.loc.end));
+                        .map_or(fn_tk.loc.clone(), |p| p.get_loc()));
 
-                Loc::new(fn_tk.loc.start, end)
+                Loc::span(&fn_tk.loc, &end)
             }
+            VarTypeDecl::Array { loc, .. }
+            | VarTypeDecl::Map { loc, .. }
+            | VarTypeDecl::Optional { loc, .. } => loc.clone(),
+        }
+    }
+}
+
+impl Display for VarTypeDecl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VarTypeDecl::Identifier(tk) => write!(f, "{}", tk.value),
+            VarTypeDecl::Fn { param_types, return_type, .. } => {
+                let params: Vec<String> = param_types.iter().map(|p| p.to_string()).collect();
+
+                match return_type {
+                    Some(r) => write!(f, "fn({}) -> {}", params.join(", "), r),
+                    None => write!(f, "fn({})", params.join(", ")),
+                }
+            }
+            VarTypeDecl::Array { typ, .. } => write!(f, "[{}]", typ),
+            VarTypeDecl::Map { key, value, .. } => write!(f, "{{{}: {}}}", key, value),
+            VarTypeDecl::Optional { typ, .. } => write!(f, "{}?", typ),
         }
     }
 }
@@ -43,10 +78,16 @@ pub enum Stmt {
     Block(BlockStmt),
     If(IfStmt),
     While(WhileStmt),
+    DoWhile(DoWhileStmt),
     For(ForStmt),
+    Break(BreakStmt),
+    Continue(ContinueStmt),
     FnDecl(FnDeclStmt),
     Return(ReturnStmt),
     Struct(StructStmt),
+    EnumDecl(EnumDeclStmt),
+    Match(MatchStmt),
+    TypeAlias(TypeAliasStmt),
 }
 
 impl Stmt {
@@ -55,27 +96,22 @@ impl Stmt {
             Self::Expr(s) => s.loc.clone(),
             Self::Print(s) => s.loc.clone(),
             Self::VarDecl(s) => s.loc.clone(),
-            Self::Block(s) => {
-                let mut loc = if !s.stmts.is_empty() {
-                    s.stmts[0].get_loc()
-                } else {
-                    Loc::new(0, 0)
-                };
-
-                if s.stmts.len() >= 2 {
-                    let end = s.stmts.last().unwrap().get_loc();
-
-                    loc = Loc::new(end.start - loc.start, end.end - loc.end);
-                }
-
-                loc
+            Self::Block(s) => match (s.stmts.first(), s.stmts.last()) {
+                (Some(first), Some(last)) => Loc::span(&first.get_loc(), &last.get_loc()),
+                _ => Loc::default(),
             },
             Self::If(s) => s.loc.clone(),
             Self::While(s) => s.loc.clone(),
+            Self::DoWhile(s) => s.loc.clone(),
             Self::For(s) => s.body.get_loc(),
+            Self::Break(s) => s.loc.clone(),
+            Self::Continue(s) => s.loc.clone(),
             Self::FnDecl(s) => s.loc.clone(),
             Self::Return(s) => s.loc.clone(),
             Self::Struct(s) => s.loc.clone(),
+            Self::EnumDecl(s) => s.loc.clone(),
+            Self::Match(s) => s.loc.clone(),
+            Self::TypeAlias(s) => s.loc.clone(),
         }
     }
 }
@@ -88,7 +124,7 @@ pub struct ExprStmt {
 
 #[derive(Debug, PartialEq)]
 pub struct PrintStmt {
-    pub expr: Expr,
+    pub exprs: Vec<Expr>,
     pub loc: Loc,
 }
 
@@ -120,18 +156,31 @@ pub struct WhileStmt {
     pub loc: Loc,
 }
 
+// Body runs once before the condition is checked
+#[derive(Debug, PartialEq)]
+pub struct DoWhileStmt {
+    pub body: Box<Stmt>,
+    pub condition: Expr,
+    pub loc: Loc,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ForStmt {
     pub placeholder: VarDeclStmt,
-    pub range: ForRange,
+    // Range, array or map
+    pub iterable: Expr,
     pub body: Box<Stmt>,
     pub loc: Loc,
 }
 
 #[derive(Debug, PartialEq)]
-pub struct ForRange {
-    pub start: i64,
-    pub end: Option<i64>,
+pub struct BreakStmt {
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ContinueStmt {
+    pub loc: Loc,
 }
 
 #[derive(Debug, PartialEq)]
@@ -140,6 +189,15 @@ pub struct FnDeclStmt {
     pub params: Arc<Vec<FnParam>>,
     pub body: Arc<BlockStmt>,
     pub return_type: Option<VarTypeDecl>,
+    pub annotations: Vec<Annotation>,
+    pub loc: Loc,
+}
+
+// Declaration annotation: @name or @name(arg, ...)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Annotation {
+    pub name: Token,
+    pub args: Vec<Token>,
     pub loc: Loc,
 }
 
@@ -161,9 +219,51 @@ pub struct StructStmt {
     pub name: Token,
     pub fields: Vec<VarDeclStmt>,
     pub methods: Vec<FnDeclStmt>,
+    pub annotations: Vec<Annotation>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct EnumDeclStmt {
+    pub name: Token,
+    pub variants: Vec<EnumVariantDecl>,
     pub loc: Loc,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct EnumVariantDecl {
+    pub name: Token,
+    // Explicit discriminant: A = 1
+    pub value: Option<Expr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TypeAliasStmt {
+    pub name: Token,
+    pub typ: VarTypeDecl,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MatchStmt {
+    pub expr: Expr,
+    pub arms: Vec<MatchArm>,
+    pub loc: Loc,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MatchArm {
+    pub pattern: MatchPattern,
+    pub body: Box<Stmt>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MatchPattern {
+    Wildcard(Loc),
+    // Literal or enum variant
+    Value(Expr),
+}
+
 
 impl Stmt {
     pub fn accept<T, U: RizonReport>(
@@ -177,10 +277,16 @@ impl Stmt {
             Stmt::Block(stmt) => stmt.accept(visitor),
             Stmt::If(stmt) => visitor.visit_if_stmt(stmt),
             Stmt::While(stmt) => visitor.visit_while_stmt(stmt),
+            Stmt::DoWhile(stmt) => visitor.visit_do_while_stmt(stmt),
             Stmt::For(stmt) => visitor.visit_for_stmt(stmt),
+            Stmt::Break(stmt) => visitor.visit_break_stmt(stmt),
+            Stmt::Continue(stmt) => visitor.visit_continue_stmt(stmt),
             Stmt::FnDecl(stmt) => visitor.visit_fn_decl_stmt(stmt),
             Stmt::Return(stmt) => visitor.visit_return_stmt(stmt),
             Stmt::Struct(stmt) => visitor.visit_struct_stmt(stmt),
+            Stmt::EnumDecl(stmt) => visitor.visit_enum_decl_stmt(stmt),
+            Stmt::Match(stmt) => visitor.visit_match_stmt(stmt),
+            Stmt::TypeAlias(stmt) => visitor.visit_type_alias_stmt(stmt),
         }
     }
 }
@@ -201,8 +307,14 @@ pub trait VisitStmt<T, U: RizonReport> {
     fn visit_block_stmt(&mut self, stmt: &BlockStmt) -> Result<T, RizonResult<U>>;
     fn visit_if_stmt(&mut self, stmt: &IfStmt) -> Result<T, RizonResult<U>>;
     fn visit_while_stmt(&mut self, stmt: &WhileStmt) -> Result<T, RizonResult<U>>;
+    fn visit_do_while_stmt(&mut self, stmt: &DoWhileStmt) -> Result<T, RizonResult<U>>;
     fn visit_for_stmt(&mut self, stmt: &ForStmt) -> Result<T, RizonResult<U>>;
+    fn visit_break_stmt(&mut self, stmt: &BreakStmt) -> Result<T, RizonResult<U>>;
+    fn visit_continue_stmt(&mut self, stmt: &ContinueStmt) -> Result<T, RizonResult<U>>;
     fn visit_fn_decl_stmt(&mut self, stmt: &FnDeclStmt) -> Result<T, RizonResult<U>>;
     fn visit_return_stmt(&mut self, stmt: &ReturnStmt) -> Result<T, RizonResult<U>>;
     fn visit_struct_stmt(&mut self, stmt: &StructStmt) -> Result<T, RizonResult<U>>;
+    fn visit_enum_decl_stmt(&mut self, stmt: &EnumDeclStmt) -> Result<T, RizonResult<U>>;
+    fn visit_match_stmt(&mut self, stmt: &MatchStmt) -> Result<T, RizonResult<U>>;
+    fn visit_type_alias_stmt(&mut self, stmt: &TypeAliasStmt) -> Result<T, RizonResult<U>>;
 }
\ No newline at end of file
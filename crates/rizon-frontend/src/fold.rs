@@ -0,0 +1,218 @@
+use crate::ast::{
+    expr::{BinaryExpr, Expr, FloatLiteralExpr, IntLiteralExpr, StrLiteralExpr, UnaryExpr},
+    rewrite::{rewrite_exprs, Rewriter},
+    stmt::Stmt,
+};
+use crate::lexer::TokenKind;
+use rizon_tools::results::Loc;
+
+
+// Replaces operations on literals by their result. Runs after static
+// analysis, so the operands types are valid. Operations that would fail
+// at runtime (division by zero, overflow) are kept to be reported there
+pub fn fold_constants(mut stmts: Vec<Stmt>) -> Vec<Stmt> {
+    rewrite_exprs(&mut stmts, &mut ConstantFolder);
+
+    stmts
+}
+
+// Longest string, in bytes, produced by folding a repetition. The folding
+// also runs on dead code, a bigger one is better built if ever executed
+const MAX_FOLDED_STR_LEN: usize = 4096;
+
+struct ConstantFolder;
+
+impl Rewriter for ConstantFolder {
+    // 'dbg' prints the source of its argument
+    fn skip(&self, expr: &Expr) -> bool {
+        matches!(expr, Expr::Call(c) if matches!(&*c.callee, Expr::Identifier(i) if i.name == "dbg"))
+    }
+
+    fn rewrite(&mut self, expr: &mut Expr) {
+        let folded = match expr {
+            Expr::Binary(e) => fold_binary(e),
+            Expr::Unary(e) => fold_unary(e),
+            Expr::Grouping(e) if is_literal(&e.expr) => Some((*e.expr).clone()),
+            _ => None,
+        };
+
+        if let Some(f) = folded {
+            *expr = f;
+        }
+    }
+}
+
+fn is_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::IntLiteral(_) | Expr::FloatLiteral(_) | Expr::StrLiteral(_))
+}
+
+fn fold_unary(expr: &UnaryExpr) -> Option<Expr> {
+    if expr.operator.kind != TokenKind::Minus {
+        return None;
+    }
+
+    let loc = Loc::span(&expr.operator.loc, &expr.right.get_loc());
+
+    match &*expr.right {
+        Expr::IntLiteral(i) => Some(Expr::IntLiteral(IntLiteralExpr {
+            value: i.value.checked_neg()?,
+            loc,
+        })),
+        Expr::FloatLiteral(f) => Some(Expr::FloatLiteral(FloatLiteralExpr { value: -f.value, loc })),
+        _ => None,
+    }
+}
+
+// Same rules as the runtime: int with int gives an int, any float
+// operand gives a float
+fn fold_binary(expr: &BinaryExpr) -> Option<Expr> {
+    let loc = Loc::span(&expr.left.get_loc(), &expr.right.get_loc());
+
+    match (&*expr.left, &*expr.right) {
+        (Expr::IntLiteral(l), Expr::IntLiteral(r)) => {
+            let value = match expr.operator.kind {
+                TokenKind::Plus => l.value.checked_add(r.value),
+                TokenKind::Minus => l.value.checked_sub(r.value),
+                TokenKind::Star => l.value.checked_mul(r.value),
+                TokenKind::Slash | TokenKind::TildeSlash => l.value.checked_div(r.value),
+                _ => None,
+            }?;
+
+            Some(Expr::IntLiteral(IntLiteralExpr { value, loc }))
+        }
+        (Expr::StrLiteral(l), Expr::StrLiteral(r)) if expr.operator.kind == TokenKind::Plus => {
+            Some(Expr::StrLiteral(StrLiteralExpr {
+                value: format!("{}{}", l.value, r.value).into(),
+                loc,
+            }))
+        }
+        // Repetition, a negative count or a too long result is left to
+        // the runtime
+        (Expr::StrLiteral(s), Expr::IntLiteral(n)) | (Expr::IntLiteral(n), Expr::StrLiteral(s))
+            if expr.operator.kind == TokenKind::Star =>
+        {
+            let count = usize::try_from(n.value).ok()?;
+
+            if s.value.len().checked_mul(count)? > MAX_FOLDED_STR_LEN {
+                return None;
+            }
+
+            Some(Expr::StrLiteral(StrLiteralExpr {
+                value: s.value.repeat(count),
+                loc,
+            }))
+        }
+        (lhs, rhs) => {
+            let (l, r) = (as_float(lhs)?, as_float(rhs)?);
+
+            let value = match expr.operator.kind {
+                TokenKind::Plus => l + r,
+                TokenKind::Minus => l - r,
+                TokenKind::Star => l * r,
+                TokenKind::Slash if r != 0. => l / r,
+                _ => return None,
+            };
+
+            Some(Expr::FloatLiteral(FloatLiteralExpr { value, loc }))
+        }
+    }
+}
+
+// Numeric literal as a float, at least one of the operands is a float
+// when called
+fn as_float(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::IntLiteral(i) => Some(i.value as f64),
+        Expr::FloatLiteral(f) => Some(f.value),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{expr::Expr, stmt::Stmt},
+        lexer::Lexer,
+        parser::Parser,
+    };
+
+    use super::fold_constants;
+
+    fn parse(code: &str) -> Vec<Stmt> {
+        let tokens = Lexer::new().tokenize(code).unwrap();
+        Parser::default().parse(tokens).unwrap()
+    }
+
+    // Folded expression as source, compared to the unfolded one
+    fn folded(code: &str) -> String {
+        let unfolded = parse(code);
+        let mut stmts = fold_constants(parse(code));
+
+        match (stmts.pop(), unfolded.last()) {
+            (Some(Stmt::Expr(f)), Some(Stmt::Expr(u))) => {
+                assert_eq!(u.expr.to_string(), code);
+                f.expr.to_string()
+            }
+            s => panic!("expected an expression statement, found {:?}", s),
+        }
+    }
+
+    #[test]
+    fn fold_arithmetic() {
+        assert_eq!(folded("2 + 3 * 4"), "14");
+        assert_eq!(folded("(2 + 3) * 4"), "20");
+        assert_eq!(folded("7 / 2"), "3");
+        assert_eq!(folded("-2 * 3"), "-6");
+        assert_eq!(folded("1 + 2.5"), "3.5");
+        assert_eq!(folded("1.5 * 2"), "3.0");
+        assert_eq!(folded("\"a\" + \"b\""), "\"ab\"");
+    }
+
+    #[test]
+    fn fold_strings() {
+        assert_eq!(folded("\"a\" + \"b\" + \"c\""), "\"abc\"");
+        assert_eq!(folded("\"ab\" * 2"), "\"abab\"");
+        assert_eq!(folded("3 * \"x\""), "\"xxx\"");
+        assert_eq!(folded("\"a\" + \"b\" * 2"), "\"abb\"");
+        assert_eq!(folded("s + \"a\" + \"b\""), "s + \"a\" + \"b\"");
+        assert_eq!(folded("\"ab\" * -1"), "\"ab\" * -1");
+        assert_eq!(folded("\"ab\" * 2048"), format!("\"{}\"", "ab".repeat(2048)));
+        assert_eq!(folded("\"ab\" * 2049"), "\"ab\" * 2049");
+        assert_eq!(folded("\"ab\" * 100000000000"), "\"ab\" * 100000000000");
+        assert_eq!(
+            folded("\"ab\" * 4611686018427387904"),
+            "\"ab\" * 4611686018427387904"
+        );
+
+        let stmts = fold_constants(parse("\"a\" + \"b\" + \"c\""));
+        assert!(matches!(
+            &stmts[..],
+            [Stmt::Expr(s)] if matches!(&s.expr, Expr::StrLiteral(l) if l.value == "abc")
+        ));
+    }
+
+    #[test]
+    fn fold_literal_subexpressions_only() {
+        assert_eq!(folded("x + 2 * 3"), "x + 6");
+        assert_eq!(folded("f(1 + 1, x * 2)"), "f(2, x * 2)");
+        assert_eq!(folded("2 < 3"), "2 < 3");
+        assert_eq!(folded("7 % 3"), "7 % 3");
+        assert_eq!(folded("dbg(1 + 2)"), "dbg(1 + 2)");
+    }
+
+    #[test]
+    fn keep_runtime_errors() {
+        assert_eq!(folded("1 / 0"), "1 / 0");
+        assert_eq!(folded("1.5 / (1 - 1)"), "1.5 / 0");
+        assert_eq!(folded("9223372036854775807 + 1"), "9223372036854775807 + 1");
+    }
+
+    #[test]
+    fn fold_in_nested_statements() {
+        let stmts = fold_constants(parse("fn f() -> int { if true { return 1 + 1 } return 0 }"));
+
+        assert!(format!("{:?}", stmts).contains("IntLiteral(IntLiteralExpr { value: 2"));
+        assert!(!format!("{:?}", stmts).contains("Binary"));
+    }
+}
@@ -1,7 +1,11 @@
 use colored::*;
 use ecow::EcoString;
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 use thiserror::Error;
+use unicode_ident::{is_xid_continue, is_xid_start};
 
 use rizon_tools::results::{Loc, RizonReport, RizonResult};
 
@@ -24,6 +28,38 @@ pub enum LexerErr {
     // Strings
     #[error("string literal never closed with '\"'")]
     StringNeverClosed,
+
+    #[error("multi-line string literal never closed with '\"\"\"'")]
+    MultilineStringNeverClosed,
+
+    // Characters
+    #[error("character literal never closed with '''")]
+    CharNeverClosed,
+
+    #[error("character literal must contain exactly one character")]
+    InvalidCharLiteral,
+
+    #[error("unknown escape sequence '\\{0}'")]
+    UnknownEscape(char),
+
+    // Preprocessor
+    #[error("unknown directive '#{0}', expected '#if' or '#endif'")]
+    UnknownDirective(String),
+
+    #[error("missing feature name after '#if'")]
+    MissingFeatureName,
+
+    #[error("missing '{{' after '#if' feature name")]
+    MissingIfDirectiveOpenBrace,
+
+    #[error("'#if' block never closed with '}}' and '#endif'")]
+    IfDirectiveNeverClosed,
+
+    #[error("missing '#endif' after '#if' block")]
+    MissingEndifDirective,
+
+    #[error("'#endif' without matching '#if'")]
+    UnmatchedEndifDirective,
 }
 
 impl RizonReport for LexerErr {
@@ -34,6 +70,12 @@ impl RizonReport for LexerErr {
 
 type RizonResLex = RizonResult<LexerErr>;
 
+// Lexes the code without parsing it. Each token has its kind, its
+// classification with 'Token::class' and its span in the code
+pub fn token_stream(code: &str) -> Result<Vec<Token>, Vec<RizonResLex>> {
+    Lexer::new().lex(code)
+}
+
 // --------
 //  Lexing
 // --------
@@ -44,9 +86,14 @@ pub enum TokenKind {
     CloseParen,
     OpenBrace,
     CloseBrace,
+    OpenBracket,
+    CloseBracket,
     Comma,
     Dot,
     Colon,
+    Underscore,
+    Question,
+    At,
     Minus,
     Plus,
     Slash,
@@ -63,11 +110,24 @@ pub enum TokenKind {
     Less,
     LessEqual,
     DotDot,
+    DotDotEqual,
     SmallArrow,
+    FatArrow,
+    QuestionQuestion,
+    StarStar,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    TildeSlash,
+    ModuloEqual,
+    PlusPlus,
+    MinusMinus,
 
     // Literals
     Identifier,
     StringLit,
+    CharLit,
     IntLit,
     FloatLit,
 
@@ -76,11 +136,13 @@ pub enum TokenKind {
     FloatType,
     StringType,
     BoolType,
+    CharType,
     AnyType,
     VoidType,
 
     // Keywords
     Struct,
+    Enum,
     Fn,
     SelfKw,
     Var,
@@ -94,10 +156,21 @@ pub enum TokenKind {
     Print,
     For,
     While,
+    Do,
+    Loop,
+    Break,
+    Continue,
     In,
     True,
     False,
     Is,
+    As,
+    Match,
+    Type,
+
+    // Preprocessor
+    IfDirective,
+    EndifDirective,
 
     // Temporary
     Range(Box<(Token, Token, Option<Token>)>),
@@ -106,6 +179,109 @@ pub enum TokenKind {
     Eof,
 }
 
+// Coarse classification of tokens, used by external tools like
+// syntax highlighters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenClass {
+    Keyword,
+    Type,
+    Identifier,
+    Literal,
+    Operator,
+    Punctuation,
+    NewLine,
+    Eof,
+}
+
+impl TokenKind {
+    pub fn class(&self) -> TokenClass {
+        match self {
+            TokenKind::OpenParen
+            | TokenKind::CloseParen
+            | TokenKind::OpenBrace
+            | TokenKind::CloseBrace
+            | TokenKind::OpenBracket
+            | TokenKind::CloseBracket
+            | TokenKind::Comma
+            | TokenKind::Dot
+            | TokenKind::Colon
+            | TokenKind::Underscore
+            | TokenKind::At
+            | TokenKind::SmallArrow
+            | TokenKind::FatArrow => TokenClass::Punctuation,
+            TokenKind::Question
+            | TokenKind::Minus
+            | TokenKind::Plus
+            | TokenKind::Slash
+            | TokenKind::Star
+            | TokenKind::Modulo
+            | TokenKind::Bang
+            | TokenKind::BangEqual
+            | TokenKind::Equal
+            | TokenKind::EqualEqual
+            | TokenKind::Greater
+            | TokenKind::GreaterEqual
+            | TokenKind::Less
+            | TokenKind::LessEqual
+            | TokenKind::DotDot
+            | TokenKind::DotDotEqual
+            | TokenKind::QuestionQuestion
+            | TokenKind::StarStar
+            | TokenKind::PlusEqual
+            | TokenKind::MinusEqual
+            | TokenKind::StarEqual
+            | TokenKind::SlashEqual
+            | TokenKind::TildeSlash
+            | TokenKind::ModuloEqual
+            | TokenKind::PlusPlus
+            | TokenKind::MinusMinus => TokenClass::Operator,
+            TokenKind::Identifier => TokenClass::Identifier,
+            TokenKind::StringLit
+            | TokenKind::CharLit
+            | TokenKind::IntLit
+            | TokenKind::FloatLit
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::Null
+            | TokenKind::Range(_) => TokenClass::Literal,
+            TokenKind::IntType
+            | TokenKind::FloatType
+            | TokenKind::StringType
+            | TokenKind::BoolType
+            | TokenKind::CharType
+            | TokenKind::AnyType
+            | TokenKind::VoidType => TokenClass::Type,
+            TokenKind::Struct
+            | TokenKind::Enum
+            | TokenKind::Fn
+            | TokenKind::SelfKw
+            | TokenKind::Var
+            | TokenKind::Const
+            | TokenKind::Return
+            | TokenKind::If
+            | TokenKind::Else
+            | TokenKind::And
+            | TokenKind::Or
+            | TokenKind::Print
+            | TokenKind::For
+            | TokenKind::While
+            | TokenKind::Do
+            | TokenKind::Loop
+            | TokenKind::Break
+            | TokenKind::Continue
+            | TokenKind::In
+            | TokenKind::Is
+            | TokenKind::As
+            | TokenKind::Match
+            | TokenKind::Type
+            | TokenKind::IfDirective
+            | TokenKind::EndifDirective => TokenClass::Keyword,
+            TokenKind::NewLine => TokenClass::NewLine,
+            TokenKind::Eof => TokenClass::Eof,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Token {
     pub kind: TokenKind,
@@ -113,6 +289,12 @@ pub struct Token {
     pub loc: Loc,
 }
 
+impl Token {
+    pub fn class(&self) -> TokenClass {
+        self.kind.class()
+    }
+}
+
 impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.value)
@@ -122,9 +304,22 @@ impl Display for Token {
 #[derive(Default)]
 pub struct Lexer {
     code: Vec<char>,
+    // Byte offset of each character, and of the end of the code
+    byte_offsets: Vec<u32>,
     keywords: HashMap<String, TokenKind>,
     start: usize,
     current: usize,
+    // Features activated for '#if' blocks
+    features: HashSet<EcoString>,
+}
+
+// '#if' block being preprocessed
+struct IfDirective {
+    active: bool,
+    // Braces depth inside the block
+    depth: usize,
+    closed: bool,
+    loc: Loc,
 }
 
 impl Lexer {
@@ -143,11 +338,13 @@ impl Lexer {
         map.insert("true".into(), TokenKind::True);
         map.insert("false".into(), TokenKind::False);
         map.insert("struct".into(), TokenKind::Struct);
+        map.insert("enum".into(), TokenKind::Enum);
         map.insert("self".into(), TokenKind::SelfKw);
         map.insert("int".into(), TokenKind::IntType);
         map.insert("float".into(), TokenKind::FloatType);
         map.insert("str".into(), TokenKind::StringType);
         map.insert("bool".into(), TokenKind::BoolType);
+        map.insert("char".into(), TokenKind::CharType);
         map.insert("null".into(), TokenKind::Null);
         map.insert("any".into(), TokenKind::AnyType);
         map.insert("void".into(), TokenKind::VoidType);
@@ -159,15 +356,38 @@ impl Lexer {
         map.insert("or".into(), TokenKind::Or);
         map.insert("for".into(), TokenKind::For);
         map.insert("while".into(), TokenKind::While);
+        map.insert("do".into(), TokenKind::Do);
+        map.insert("loop".into(), TokenKind::Loop);
+        map.insert("break".into(), TokenKind::Break);
+        map.insert("continue".into(), TokenKind::Continue);
         map.insert("in".into(), TokenKind::In);
         map.insert("print".into(), TokenKind::Print);
         map.insert("is".into(), TokenKind::Is);
+        map.insert("as".into(), TokenKind::As);
+        map.insert("match".into(), TokenKind::Match);
+        map.insert("type".into(), TokenKind::Type);
 
         self.keywords = map;
     }
 
+    pub fn set_features(&mut self, features: HashSet<EcoString>) {
+        self.features = features;
+    }
+
     pub fn tokenize(&mut self, code: &str) -> Result<Vec<Token>, Vec<RizonResLex>> {
+        let tokens = self.lex(code)?;
+
+        self.preprocess(tokens)
+    }
+
+    // Lexes the code, keeping preprocessor directives
+    fn lex(&mut self, code: &str) -> Result<Vec<Token>, Vec<RizonResLex>> {
         self.code = code.chars().collect();
+        self.byte_offsets = code
+            .char_indices()
+            .map(|(i, _)| i as u32)
+            .chain(std::iter::once(code.len() as u32))
+            .collect();
 
         let mut errors: Vec<RizonResLex> = vec![];
         let mut tokens: Vec<Token> = vec![];
@@ -194,29 +414,84 @@ impl Lexer {
                 ')' => self.add_token(TokenKind::CloseParen),
                 '{' => self.add_token(TokenKind::OpenBrace),
                 '}' => self.add_token(TokenKind::CloseBrace),
+                '[' => self.add_token(TokenKind::OpenBracket),
+                ']' => self.add_token(TokenKind::CloseBracket),
                 ',' => self.add_token(TokenKind::Comma),
                 '.' => {
                     if self.at().is_numeric() {
                         self.lex_number(true)
                     } else if self.is_at_and_advance('.') {
-                        self.add_token(TokenKind::DotDot)
+                        if self.is_at_and_advance('=') {
+                            self.add_token(TokenKind::DotDotEqual)
+                        } else {
+                            self.add_token(TokenKind::DotDot)
+                        }
                     } else {
                         self.add_token(TokenKind::Dot)
                     }
                 }
                 ':' => self.add_token(TokenKind::Colon),
+                '@' => self.add_token(TokenKind::At),
+                '?' => {
+                    let tk = if self.is_at_and_advance('?') {
+                        TokenKind::QuestionQuestion
+                    } else {
+                        TokenKind::Question
+                    };
+
+                    self.add_token(tk)
+                }
+                '_' => {
+                    if is_xid_continue(self.at()) {
+                        self.lex_identifier()
+                    } else {
+                        self.add_token(TokenKind::Underscore)
+                    }
+                }
                 '-' => {
                     let tk = if self.is_at_and_advance('>') {
                         TokenKind::SmallArrow
+                    } else if self.is_at_and_advance('=') {
+                        TokenKind::MinusEqual
+                    } else if self.is_at_and_advance('-') {
+                        TokenKind::MinusMinus
                     } else {
                         TokenKind::Minus
                     };
 
                     self.add_token(tk)
                 },
-                '+' => self.add_token(TokenKind::Plus),
-                '*' => self.add_token(TokenKind::Star),
-                '%' => self.add_token(TokenKind::Modulo),
+                '+' => {
+                    let tk = if self.is_at_and_advance('=') {
+                        TokenKind::PlusEqual
+                    } else if self.is_at_and_advance('+') {
+                        TokenKind::PlusPlus
+                    } else {
+                        TokenKind::Plus
+                    };
+
+                    self.add_token(tk)
+                }
+                '*' => {
+                    let tk = if self.is_at_and_advance('*') {
+                        TokenKind::StarStar
+                    } else if self.is_at_and_advance('=') {
+                        TokenKind::StarEqual
+                    } else {
+                        TokenKind::Star
+                    };
+
+                    self.add_token(tk)
+                }
+                '%' => {
+                    let tk = if self.is_at_and_advance('=') {
+                        TokenKind::ModuloEqual
+                    } else {
+                        TokenKind::Modulo
+                    };
+
+                    self.add_token(tk)
+                }
 
                 // One or two char tokens
                 '!' => {
@@ -231,6 +506,8 @@ impl Lexer {
                 '=' => {
                     let tk = if self.is_at_and_advance('=') {
                         TokenKind::EqualEqual
+                    } else if self.is_at_and_advance('>') {
+                        TokenKind::FatArrow
                     } else {
                         TokenKind::Equal
                     };
@@ -256,13 +533,26 @@ impl Lexer {
                     self.add_token(tk)
                 }
                 // Longer tokens
-                '/' => self.add_token(TokenKind::Slash),
+                '/' => {
+                    let tk = if self.is_at_and_advance('=') {
+                        TokenKind::SlashEqual
+                    } else {
+                        TokenKind::Slash
+                    };
+
+                    self.add_token(tk)
+                }
+                // Integer division, '//' can't be used as it starts a comment
+                '~' if self.is_at_and_advance('/') => self.add_token(TokenKind::TildeSlash),
+                '\"' if self.is_at_triple_quote() => self.lex_multiline_string(),
                 '\"' => self.lex_string(),
+                '\'' => self.lex_char(),
+                '#' => self.lex_directive(),
 
                 _ => {
                     if c.is_numeric() {
                         self.lex_number(false)
-                    } else if c.is_alphabetic() {
+                    } else if is_xid_start(c) {
                         self.lex_identifier()
                     } else {
                         Err(self.trigger_error(LexerErr::UnexpectedToken(c)))
@@ -296,6 +586,8 @@ impl Lexer {
             loc: Loc {
                 start: self.code.len(),
                 end: self.code.len() + 1,
+                byte_start: code.len() as u32,
+                byte_end: code.len() as u32 + 1,
             },
         });
 
@@ -305,6 +597,117 @@ impl Lexer {
         }
     }
 
+    // Removes the '#if' blocks of inactive features and the directives
+    // of active ones: #if FEATURE { ... } #endif
+    fn preprocess(&self, tokens: Vec<Token>) -> Result<Vec<Token>, Vec<RizonResLex>> {
+        let mut errors: Vec<RizonResLex> = vec![];
+        let mut res: Vec<Token> = vec![];
+        let mut directives: Vec<IfDirective> = vec![];
+
+        let mut tokens = tokens.into_iter().peekable();
+
+        while let Some(tk) = tokens.next() {
+            let active = directives.iter().all(|d| d.active);
+
+            // Only new lines are allowed between the closing brace and '#endif'
+            if let Some(d) = directives.last() {
+                if d.closed && !matches!(tk.kind, TokenKind::NewLine | TokenKind::EndifDirective) {
+                    errors.push(RizonResult::new(LexerErr::MissingEndifDirective, Some(d.loc.clone())));
+                    directives.pop();
+                }
+            }
+
+            match tk.kind {
+                TokenKind::IfDirective => {
+                    let feature = match tokens.next_if(|t| t.kind == TokenKind::Identifier) {
+                        Some(f) => f,
+                        None => {
+                            errors.push(RizonResult::new(LexerErr::MissingFeatureName, Some(tk.loc)));
+                            continue;
+                        }
+                    };
+
+                    if tokens.next_if(|t| t.kind == TokenKind::OpenBrace).is_none() {
+                        errors.push(RizonResult::new(
+                            LexerErr::MissingIfDirectiveOpenBrace,
+                            Some(feature.loc),
+                        ));
+                        continue;
+                    }
+
+                    directives.push(IfDirective {
+                        active: self.features.contains(&feature.value),
+                        depth: 0,
+                        closed: false,
+                        loc: tk.loc,
+                    });
+                }
+                TokenKind::EndifDirective => match directives.last() {
+                    Some(d) if d.closed => {
+                        directives.pop();
+                    }
+                    _ => errors.push(RizonResult::new(LexerErr::UnmatchedEndifDirective, Some(tk.loc))),
+                },
+                TokenKind::OpenBrace => {
+                    if let Some(d) = directives.last_mut() {
+                        d.depth += 1;
+                    }
+
+                    if active {
+                        res.push(tk);
+                    }
+                }
+                TokenKind::CloseBrace => match directives.last_mut() {
+                    Some(d) if d.depth == 0 => d.closed = true,
+                    Some(d) => {
+                        d.depth -= 1;
+
+                        if active {
+                            res.push(tk);
+                        }
+                    }
+                    None => res.push(tk),
+                },
+                TokenKind::Eof => {
+                    for d in directives.drain(..) {
+                        let err = match d.closed {
+                            true => LexerErr::MissingEndifDirective,
+                            false => LexerErr::IfDirectiveNeverClosed,
+                        };
+
+                        errors.push(RizonResult::new(err, Some(d.loc)));
+                    }
+
+                    res.push(tk);
+                }
+                _ => {
+                    if active {
+                        res.push(tk);
+                    }
+                }
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(res),
+            false => Err(errors),
+        }
+    }
+
+    fn lex_directive(&mut self) -> Result<Token, RizonResLex> {
+        while self.at().is_alphabetic() {
+            self.eat();
+        }
+
+        let directive: String = self.code[self.start + 1..self.current].iter().collect();
+
+        match directive.as_str() {
+            "if" => self.add_token(TokenKind::IfDirective),
+            "endif" => self.add_token(TokenKind::EndifDirective),
+            _ => Err(self.trigger_error(LexerErr::UnknownDirective(directive))),
+        }
+    }
+
     fn lex_comment(&mut self) {
         while !self.eof() && self.at() != '\n' {
             self.eat();
@@ -321,7 +724,7 @@ impl Lexer {
         if self.eof() {
             return Err(self.trigger_error_with_loc(
                 LexerErr::StringNeverClosed,
-                Loc::new(open_quote, open_quote),
+                self.loc(open_quote, open_quote),
             ));
         }
 
@@ -339,6 +742,85 @@ impl Lexer {
         self.add_value_token(TokenKind::StringLit, value.into())
     }
 
+    // The first quote is already eaten
+    fn is_at_triple_quote(&self) -> bool {
+        self.code[self.current..].starts_with(&['"', '"'])
+    }
+
+    // Content is kept verbatim, new lines included
+    fn lex_multiline_string(&mut self) -> Result<Token, RizonResLex> {
+        let open_quote = self.current - 1;
+
+        self.eat();
+        self.eat();
+
+        while !self.eof() && !self.code[self.current..].starts_with(&['"', '"', '"']) {
+            self.eat();
+        }
+
+        if self.eof() {
+            return Err(self.trigger_error_with_loc(
+                LexerErr::MultilineStringNeverClosed,
+                self.loc(open_quote, open_quote + 3),
+            ));
+        }
+
+        let value: String = self.code[self.start + 3..self.current].iter().collect();
+
+        self.eat();
+        self.eat();
+        self.eat();
+
+        self.add_value_token(TokenKind::StringLit, value.into())
+    }
+
+    // The token value is the character itself, with its escape resolved
+    fn lex_char(&mut self) -> Result<Token, RizonResLex> {
+        let mut chars: Vec<char> = vec![];
+        let mut unknown_escape: Option<char> = None;
+
+        while !self.eof() && !matches!(self.at(), '\'' | '\n') {
+            let c = match self.eat() {
+                '\\' if self.eof() => break,
+                '\\' => match self.eat() {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    '0' => '\0',
+                    c @ ('\\' | '\'' | '"') => c,
+                    c => {
+                        unknown_escape.get_or_insert(c);
+                        c
+                    }
+                },
+                c => c,
+            };
+
+            chars.push(c);
+        }
+
+        if self.at() != '\'' {
+            let open_quote = self.start;
+
+            return Err(self.trigger_error_with_loc(
+                LexerErr::CharNeverClosed,
+                self.loc(open_quote, open_quote),
+            ));
+        }
+
+        // We eat the ', no need to synchronize past this point
+        self.eat();
+
+        if let Some(c) = unknown_escape {
+            return Err(RizonResult::new(LexerErr::UnknownEscape(c), Some(self.get_loc())));
+        }
+
+        match chars.as_slice() {
+            [c] => self.add_value_token(TokenKind::CharLit, c.to_string().into()),
+            _ => Err(RizonResult::new(LexerErr::InvalidCharLiteral, Some(self.get_loc()))),
+        }
+    }
+
     // point_float is when we are in the case ".456" and we have already parsed
     // the '.'
     fn lex_number(&mut self, point_float: bool) -> Result<Token, RizonResLex> {
@@ -401,7 +883,11 @@ impl Lexer {
         self.start = self.current;
         self.eat();
         self.eat();
-        let dotdot = self.add_token(TokenKind::DotDot)?;
+        let dotdot = if self.is_at_and_advance('=') {
+            self.add_token(TokenKind::DotDotEqual)?
+        } else {
+            self.add_token(TokenKind::DotDot)?
+        };
 
         self.start = self.current;
         while self.at().is_numeric() {
@@ -416,12 +902,13 @@ impl Lexer {
         Ok(Token {
             kind: TokenKind::Range(Box::new((start, dotdot, end))),
             value: "".into(),
-            loc: Loc { start: 0, end: 0 },
+            loc: Loc::default(),
         })
     }
 
+    // Unicode identifiers: café, λ. Keywords are ASCII and matched exactly
     fn lex_identifier(&mut self) -> Result<Token, RizonResLex> {
-        while self.at().is_alphanumeric() || self.at() == '_' {
+        while is_xid_continue(self.at()) {
             self.eat();
         }
 
@@ -534,7 +1021,16 @@ impl Lexer {
     }
 
     fn get_loc(&self) -> Loc {
-        Loc::new(self.start, self.current - 1)
+        self.loc(self.start, self.current - 1)
+    }
+
+    fn loc(&self, start: usize, end: usize) -> Loc {
+        Loc {
+            start,
+            end,
+            byte_start: self.byte_offsets[start],
+            byte_end: self.byte_offsets[end],
+        }
     }
 }
 
@@ -542,13 +1038,13 @@ impl Lexer {
 mod tests {
     use ecow::EcoString;
 
-    use crate::lexer::{LexerErr, Loc, TokenKind};
+    use crate::lexer::{LexerErr, Loc, Token, TokenClass, TokenKind};
 
-    use super::Lexer;
+    use super::{token_stream, Lexer};
 
     #[test]
     fn tokenize_single_char() {
-        let code: String = "(){},.-+%/*=!<>\n".into();
+        let code: String = "(){}[],.-+%/* =!<>\n".into();
         let mut lexer = Lexer::new();
         let tokens = lexer.tokenize(&code).unwrap();
 
@@ -561,6 +1057,8 @@ mod tests {
                 TokenKind::CloseParen,
                 TokenKind::OpenBrace,
                 TokenKind::CloseBrace,
+                TokenKind::OpenBracket,
+                TokenKind::CloseBracket,
                 TokenKind::Comma,
                 TokenKind::Dot,
                 TokenKind::Minus,
@@ -580,7 +1078,7 @@ mod tests {
 
     #[test]
     fn tokenize_double_char() {
-        let code: String = "!= <= >= == ..".into();
+        let code: String = "!= <= >= == .. -> => ?? ** += -= *= /= ~/ %= ++ --".into();
         let mut lexer = Lexer::new();
         let tokens = lexer.tokenize(&code).unwrap();
 
@@ -594,6 +1092,18 @@ mod tests {
                 TokenKind::GreaterEqual,
                 TokenKind::EqualEqual,
                 TokenKind::DotDot,
+                TokenKind::SmallArrow,
+                TokenKind::FatArrow,
+                TokenKind::QuestionQuestion,
+                TokenKind::StarStar,
+                TokenKind::PlusEqual,
+                TokenKind::MinusEqual,
+                TokenKind::StarEqual,
+                TokenKind::SlashEqual,
+                TokenKind::TildeSlash,
+                TokenKind::ModuloEqual,
+                TokenKind::PlusPlus,
+                TokenKind::MinusMinus,
                 TokenKind::Eof,
             ]
         );
@@ -659,6 +1169,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tokenize_inclusive_range() {
+        let tk_type = |code: &str| {
+            let tokens: Vec<Token> = Lexer::new().tokenize(code).unwrap();
+            tokens.iter().map(|tk| tk.kind.clone()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            tk_type("2..=5"),
+            vec![TokenKind::IntLit, TokenKind::DotDotEqual, TokenKind::IntLit, TokenKind::Eof]
+        );
+        assert_eq!(
+            tk_type("a..=b"),
+            vec![
+                TokenKind::Identifier,
+                TokenKind::DotDotEqual,
+                TokenKind::Identifier,
+                TokenKind::Eof
+            ]
+        );
+    }
+
     #[test]
     fn string_errors() {
         let code: String = "\"foo".into();
@@ -671,6 +1203,85 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn tokenize_char() {
+        let tokens = Lexer::new().tokenize("'a' '\\n' '\\'' 'é'").unwrap();
+
+        let values: Vec<&str> = tokens[..4].iter().map(|tk| tk.value.as_str()).collect();
+
+        assert!(tokens[..4].iter().all(|tk| tk.kind == TokenKind::CharLit));
+        assert_eq!(values, vec!["a", "\n", "'", "é"]);
+    }
+
+    #[test]
+    fn char_errors() {
+        let errors = Lexer::new().tokenize("'ab'").err().unwrap();
+        assert!(matches!(errors[0].err, LexerErr::InvalidCharLiteral));
+
+        let errors = Lexer::new().tokenize("''").err().unwrap();
+        assert!(matches!(errors[0].err, LexerErr::InvalidCharLiteral));
+
+        let errors = Lexer::new().tokenize("'\\q'").err().unwrap();
+        assert!(matches!(errors[0].err, LexerErr::UnknownEscape('q')));
+
+        let errors = Lexer::new().tokenize("'a").err().unwrap();
+        assert!(matches!(errors[0].err, LexerErr::CharNeverClosed));
+    }
+
+    #[test]
+    fn multiline_string() {
+        let tokens = Lexer::new().tokenize("\"\"\"first \"line\"\nsecond\"\"\"").unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::StringLit);
+        assert_eq!(tokens[0].value, "first \"line\"\nsecond");
+
+        let errors = Lexer::new().tokenize("\"\"\"foo\n\"").err().unwrap();
+
+        assert!(matches!(errors[0].err, LexerErr::MultilineStringNeverClosed));
+    }
+
+    #[test]
+    fn unicode_identifiers() {
+        let tokens = Lexer::new().tokenize("var café = λ _été\nvàr").unwrap();
+
+        let tk: Vec<(TokenKind, &str, Loc)> = tokens
+            .iter()
+            .map(|tk| (tk.kind.clone(), tk.value.as_str(), tk.loc.clone()))
+            .collect();
+
+        // Keywords are matched exactly, locations are in characters
+        assert_eq!(
+            tk[..7],
+            [
+                (TokenKind::Var, "var", Loc::new(0, 2)),
+                (TokenKind::Identifier, "café", Loc::new(4, 7)),
+                (TokenKind::Equal, "=", Loc::new(9, 9)),
+                (TokenKind::Identifier, "λ", Loc::new(11, 11)),
+                (TokenKind::Identifier, "_été", Loc::new(13, 16)),
+                (TokenKind::NewLine, "\n", Loc::new(17, 17)),
+                (TokenKind::Identifier, "vàr", Loc::new(18, 20)),
+            ]
+        );
+
+        assert!(Lexer::new().tokenize("a€").is_err());
+    }
+
+    #[test]
+    fn byte_ranges() {
+        let code = "var café = \"λ€\"\nvàr";
+        let tokens = Lexer::new().tokenize(code).unwrap();
+
+        let lexemes: Vec<&str> = tokens[..6]
+            .iter()
+            .map(|tk| &code[tk.loc.byte_range(code)])
+            .collect();
+
+        assert_eq!(lexemes, vec!["var", "café", "=", "\"λ€\"", "\n", "vàr"]);
+        assert_eq!(tokens[1].loc.byte_range(code), 4..9);
+        // Recorded while lexing, the end is where the last character starts
+        assert_eq!((tokens[3].loc.byte_start, tokens[3].loc.byte_end), (12, 18));
+    }
+
     #[test]
     fn location() {
         let code: String = "
@@ -705,4 +1316,83 @@ break 45+7"
             ]
         );
     }
+
+    #[test]
+    fn token_stream_classes() {
+        let tokens = token_stream("var a: int = 4\nprint a + 1.5").unwrap();
+
+        let tk_class: Vec<(TokenKind, TokenClass, Loc)> = tokens
+            .iter()
+            .map(|tk| (tk.kind.clone(), tk.class(), tk.loc.clone()))
+            .collect();
+
+        assert_eq!(
+            tk_class,
+            vec![
+                (TokenKind::Var, TokenClass::Keyword, Loc::new(0, 2)),
+                (TokenKind::Identifier, TokenClass::Identifier, Loc::new(4, 4)),
+                (TokenKind::Colon, TokenClass::Punctuation, Loc::new(5, 5)),
+                (TokenKind::IntType, TokenClass::Type, Loc::new(7, 9)),
+                (TokenKind::Equal, TokenClass::Operator, Loc::new(11, 11)),
+                (TokenKind::IntLit, TokenClass::Literal, Loc::new(13, 13)),
+                (TokenKind::NewLine, TokenClass::NewLine, Loc::new(14, 14)),
+                (TokenKind::Print, TokenClass::Keyword, Loc::new(15, 19)),
+                (TokenKind::Identifier, TokenClass::Identifier, Loc::new(21, 21)),
+                (TokenKind::Plus, TokenClass::Operator, Loc::new(23, 23)),
+                (TokenKind::FloatLit, TokenClass::Literal, Loc::new(25, 27)),
+                (TokenKind::Eof, TokenClass::Eof, Loc::new(28, 29)),
+            ]
+        );
+    }
+
+    #[test]
+    fn if_directive() {
+        let code: String = "
+#if FOO {
+    var a = 1
+} #endif
+#if BAR {
+    var b: int = \"wrong\"
+    #if FOO {
+        print b
+    }
+    #endif
+} #endif".into();
+
+        let mut lexer = Lexer::new();
+        lexer.set_features(["FOO".into()].into());
+        let tokens = lexer.tokenize(&code).unwrap();
+
+        let tk_kind: Vec<TokenKind> = tokens
+            .iter()
+            .map(|tk| tk.kind.clone())
+            .filter(|k| *k != TokenKind::NewLine)
+            .collect();
+
+        assert_eq!(
+            tk_kind,
+            vec![
+                TokenKind::Var,
+                TokenKind::Identifier,
+                TokenKind::Equal,
+                TokenKind::IntLit,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn if_directive_errors() {
+        let errs = Lexer::new().tokenize("#if FOO { var a = 1").err().unwrap();
+        assert!(matches!(errs[0].err, LexerErr::IfDirectiveNeverClosed));
+
+        let errs = Lexer::new().tokenize("#if FOO { var a = 1 }\nvar b = 2").err().unwrap();
+        assert!(matches!(errs[0].err, LexerErr::MissingEndifDirective));
+
+        let errs = Lexer::new().tokenize("#endif").err().unwrap();
+        assert!(matches!(errs[0].err, LexerErr::UnmatchedEndifDirective));
+
+        let errs = Lexer::new().tokenize("#ifdef").err().unwrap();
+        assert!(matches!(errs[0].err, LexerErr::UnknownDirective(_)));
+    }
 }
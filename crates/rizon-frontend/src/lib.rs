@@ -1,5 +1,7 @@
 pub mod lexer;
 pub mod parser;
+pub mod normalize;
+pub mod fold;
 pub mod ast;
 
 extern crate rizon_tools;
@@ -0,0 +1,303 @@
+use std::sync::Arc;
+
+use crate::ast::{
+    expr::{
+        AssignExpr, BinaryExpr, CallExpr, CompoundAssignExpr, Expr, IdentifierExpr, IndexSetExpr,
+        SelfExpr, SetExpr,
+    },
+    rewrite::{rewrite_exprs, Rewriter},
+    stmt::{BlockStmt, FnDeclStmt, ReturnStmt, Stmt, StructStmt, VarTypeDecl},
+};
+use crate::lexer::{Token, TokenKind};
+
+
+// Lowers syntactic sugar into core nodes. Static analysis and
+// interpretation run on the output and never see sugar nodes
+pub fn normalize(mut stmts: Vec<Stmt>) -> Vec<Stmt> {
+    rewrite_exprs(&mut stmts, &mut Normalizer);
+
+    stmts
+}
+
+struct Normalizer;
+
+impl Rewriter for Normalizer {
+    fn rewrite(&mut self, expr: &mut Expr) {
+        if let Expr::CompoundAssign(e) = expr {
+            *expr = lower_compound_assign(e);
+        }
+    }
+
+    fn rewrite_stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Struct(s) => {
+                derive_methods(s);
+                s.methods.iter_mut().for_each(lower_implicit_return);
+            }
+            Stmt::FnDecl(s) => lower_implicit_return(s),
+            _ => {}
+        }
+    }
+}
+
+// @derive(to_string)  ->  fn to_string() -> str { return to_str(self) }
+// The runtime displays instances of such structures with their fields
+fn derive_methods(stmt: &mut StructStmt) {
+    let to_string = stmt
+        .annotations
+        .iter()
+        .filter(|a| a.name.value == "derive")
+        .find_map(|a| a.args.iter().find(|arg| arg.value == "to_string").map(|arg| (a, arg)));
+
+    let Some((annotation, arg)) = to_string else {
+        return;
+    };
+
+    // Each node has its own location, identifiers are resolved by it
+    let call = Expr::Call(CallExpr {
+        callee: Box::new(Expr::Identifier(IdentifierExpr {
+            name: "to_str".into(),
+            loc: annotation.name.loc.clone(),
+        })),
+        args: vec![Expr::Selff(SelfExpr {
+            name: "self".into(),
+            loc: arg.loc.clone(),
+        })],
+        arg_names: vec![None],
+        loc: annotation.loc.clone(),
+    });
+
+    let method = FnDeclStmt {
+        name: Token {
+            kind: TokenKind::Identifier,
+            value: "to_string".into(),
+            loc: arg.loc.clone(),
+        },
+        params: Arc::new(vec![]),
+        body: Arc::new(BlockStmt {
+            stmts: vec![Stmt::Return(ReturnStmt {
+                value: Some(call),
+                loc: annotation.loc.clone(),
+            })],
+        }),
+        return_type: Some(VarTypeDecl::Identifier(Token {
+            kind: TokenKind::StringType,
+            value: "str".into(),
+            loc: arg.loc.clone(),
+        })),
+        annotations: vec![],
+        loc: annotation.loc.clone(),
+    };
+
+    stmt.methods.push(method);
+}
+
+// fn f() -> int { x * 2 }  ->  fn f() -> int { return x * 2 }
+// Only for functions returning a value, the static analyzer checks its type
+fn lower_implicit_return(decl: &mut FnDeclStmt) {
+    match &decl.return_type {
+        None => return,
+        Some(VarTypeDecl::Identifier(t)) if t.kind == TokenKind::VoidType => return,
+        _ => {}
+    }
+
+    let body = Arc::get_mut(&mut decl.body).expect("function body shared before rewriting");
+
+    if let Some(last) = body.stmts.last_mut() {
+        if let Stmt::Expr(e) = last {
+            *last = Stmt::Return(ReturnStmt {
+                loc: e.expr.get_loc(),
+                value: Some(e.expr.clone()),
+            });
+        }
+    }
+}
+
+// x += v  ->  x = x + v, and x++  ->  x = x + 1
+// For fields and indexes, the assignment is marked as compound: the
+// interpreter evaluates the object and index once for both sides
+fn lower_compound_assign(expr: &CompoundAssignExpr) -> Expr {
+    let (kind, value) = match expr.operator.kind {
+        TokenKind::PlusEqual | TokenKind::PlusPlus => (TokenKind::Plus, "+"),
+        TokenKind::MinusEqual | TokenKind::MinusMinus => (TokenKind::Minus, "-"),
+        TokenKind::StarEqual => (TokenKind::Star, "*"),
+        TokenKind::SlashEqual => (TokenKind::Slash, "/"),
+        TokenKind::ModuloEqual => (TokenKind::Modulo, "%"),
+        _ => unreachable!("not a compound assignment operator"),
+    };
+
+    let operation = Box::new(Expr::Binary(BinaryExpr {
+        left: expr.target.clone(),
+        operator: Token {
+            kind,
+            value: value.into(),
+            loc: expr.operator.loc.clone(),
+        },
+        right: expr.value.clone(),
+    }));
+
+    match &*expr.target {
+        Expr::Identifier(e) => Expr::Assign(AssignExpr {
+            name: e.name.clone(),
+            value: operation,
+            loc: expr.loc.clone(),
+        }),
+        Expr::Get(e) => Expr::Set(SetExpr {
+            object: e.object.clone(),
+            name: e.name.clone(),
+            value: operation,
+            compound: true,
+            loc: expr.loc.clone(),
+        }),
+        Expr::Index(e) => Expr::IndexSet(IndexSetExpr {
+            object: e.object.clone(),
+            index: e.index.clone(),
+            value: operation,
+            compound: true,
+            loc: expr.loc.clone(),
+        }),
+        _ => unreachable!("invalid compound assignment target, rejected by the parser"),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{expr::Expr, stmt::Stmt},
+        lexer::{Lexer, TokenKind},
+        parser::Parser,
+    };
+
+    use super::normalize;
+
+    fn normalized_expr(code: &str) -> Expr {
+        let tokens = Lexer::new().tokenize(code).unwrap();
+        let mut stmts = normalize(Parser::default().parse(tokens).unwrap());
+
+        match stmts.pop() {
+            Some(Stmt::Expr(s)) => s.expr,
+            s => panic!("expected an expression statement, found {:?}", s),
+        }
+    }
+
+    #[test]
+    fn compound_assign_to_variable() {
+        let Expr::Assign(assign) = normalized_expr("x += 1") else {
+            panic!("expected an assignment");
+        };
+
+        assert_eq!(assign.name, "x");
+
+        let Expr::Binary(bin) = &*assign.value else {
+            panic!("expected a binary expression");
+        };
+
+        assert!(matches!(&*bin.left, Expr::Identifier(i) if i.name == "x"));
+        assert_eq!(bin.operator.kind, TokenKind::Plus);
+        assert_eq!(bin.operator.value, "+");
+        assert!(matches!(&*bin.right, Expr::IntLiteral(i) if i.value == 1));
+    }
+
+    #[test]
+    fn compound_assign_operators() {
+        let ops = [
+            ("-=", TokenKind::Minus),
+            ("*=", TokenKind::Star),
+            ("/=", TokenKind::Slash),
+            ("%=", TokenKind::Modulo),
+        ];
+
+        for (op, kind) in ops {
+            let Expr::Assign(assign) = normalized_expr(&format!("x {} 2", op)) else {
+                panic!("expected an assignment");
+            };
+
+            assert!(matches!(&*assign.value, Expr::Binary(b) if b.operator.kind == kind));
+        }
+    }
+
+    #[test]
+    fn increment_and_decrement() {
+        let ops = [("++", TokenKind::Plus), ("--", TokenKind::Minus)];
+
+        for (op, kind) in ops {
+            let Expr::Assign(assign) = normalized_expr(&format!("x{}", op)) else {
+                panic!("expected an assignment");
+            };
+
+            assert_eq!(assign.name, "x");
+            assert!(matches!(
+                &*assign.value,
+                Expr::Binary(b) if b.operator.kind == kind
+                    && matches!(&*b.right, Expr::IntLiteral(i) if i.value == 1)
+            ));
+        }
+    }
+
+    #[test]
+    fn compound_assign_to_field_and_index() {
+        let Expr::Set(set) = normalized_expr("a.b *= 2") else {
+            panic!("expected a field assignment");
+        };
+
+        assert_eq!(set.name.value, "b");
+        assert!(set.compound);
+        assert!(matches!(&*set.value, Expr::Binary(b) if matches!(&*b.left, Expr::Get(_))));
+
+        let Expr::IndexSet(set) = normalized_expr("a[0] -= 2") else {
+            panic!("expected an index assignment");
+        };
+
+        assert!(set.compound);
+        assert!(matches!(&*set.value, Expr::Binary(b) if matches!(&*b.left, Expr::Index(_))));
+    }
+
+    #[test]
+    fn nested_sugar_is_lowered() {
+        let tokens = Lexer::new().tokenize("fn f() { while true { x += 1 } }").unwrap();
+        let stmts = normalize(Parser::default().parse(tokens).unwrap());
+
+        // No sugar node left anywhere
+        assert!(!format!("{:?}", stmts).contains("CompoundAssign"));
+    }
+
+    #[test]
+    fn trailing_expr_is_returned() {
+        let code = "fn f(x: int) -> int { x * 2 }\nfn g() { h() }\nstruct S { fn m() -> int { 1 } }";
+        let tokens = Lexer::new().tokenize(code).unwrap();
+        let stmts = normalize(Parser::default().parse(tokens).unwrap());
+
+        let [Stmt::FnDecl(f), Stmt::FnDecl(g), Stmt::Struct(s)] = stmts.as_slice() else {
+            panic!("expected two functions and a structure, found {:?}", stmts);
+        };
+
+        assert!(matches!(
+            &f.body.stmts[..],
+            [Stmt::Return(r)] if r.value.as_ref().is_some_and(|v| v.to_string() == "x * 2")
+        ));
+        // Functions without return type keep their expression statements
+        assert!(matches!(&g.body.stmts[..], [Stmt::Expr(_)]));
+        assert!(matches!(&s.methods[0].body.stmts[..], [Stmt::Return(_)]));
+    }
+
+    #[test]
+    fn derive_to_string_adds_method() {
+        let tokens = Lexer::new().tokenize("@derive(eq, to_string) struct S { fn f() {} }").unwrap();
+        let stmts = normalize(Parser::default().parse(tokens).unwrap());
+
+        let Some(Stmt::Struct(s)) = stmts.first() else {
+            panic!("expected a structure");
+        };
+
+        let names: Vec<&str> = s.methods.iter().map(|m| m.name.value.as_str()).collect();
+        assert_eq!(names, ["f", "to_string"]);
+
+        let method = &s.methods[1];
+        assert!(method.params.is_empty());
+        assert!(matches!(
+            &method.body.stmts[..],
+            [Stmt::Return(r)] if r.value.as_ref().is_some_and(|v| v.to_string() == "to_str(self)")
+        ));
+    }
+}
@@ -4,13 +4,14 @@ use colored::*;
 use thiserror::Error;
 
 use crate::ast::expr::{
-    AssignExpr, BinaryExpr, CallExpr, Expr, FloatLiteralExpr, GetExpr, GroupingExpr,
-    IdentifierExpr, IntLiteralExpr, IsExpr, LogicalExpr, SelfExpr, SetExpr, StrLiteralExpr,
-    UnaryExpr,
+    ArrayLiteralExpr, AssignExpr, BinaryExpr, CallExpr, CastExpr, ChainedCompareExpr, CharLiteralExpr, CompoundAssignExpr, Expr, FloatLiteralExpr, GetExpr,
+    GroupingExpr, IdentifierExpr, IfExpr, IndexExpr, IndexSetExpr, IntLiteralExpr, IsExpr, LogicalExpr,
+    MapLiteralExpr, RangeExpr, SelfExpr, SetExpr, StrLiteralExpr, StructLiteralExpr, UnaryExpr,
 };
 use crate::ast::stmt::{
-    BlockStmt, ExprStmt, FnDeclStmt, FnParam, ForRange, ForStmt, IfStmt, PrintStmt, ReturnStmt,
-    Stmt, StructStmt, VarDeclStmt, VarTypeDecl, WhileStmt,
+    Annotation, BlockStmt, BreakStmt, ContinueStmt, DoWhileStmt, EnumDeclStmt, EnumVariantDecl, ExprStmt, FnDeclStmt, FnParam, ForStmt, IfStmt, MatchArm,
+    MatchPattern, MatchStmt, PrintStmt, ReturnStmt, Stmt, StructStmt, TypeAliasStmt, VarDeclStmt,
+    VarTypeDecl, WhileStmt,
 };
 use crate::lexer::{Token, TokenKind};
 use rizon_tools::results::{Loc, RizonReport, RizonResult};
@@ -70,6 +71,9 @@ pub enum ParserErr {
     #[error("'if' statement with no condition")]
     IfWithNoCond,
 
+    #[error("missing '}}' after 'if' expression branch")]
+    MissingIfExprCloseBrace,
+
     #[error("missing right expression in 'or' statement")]
     OrWithNoCond,
 
@@ -79,6 +83,10 @@ pub enum ParserErr {
     #[error("'else' branch can't have a condition")]
     ElseWithCond,
 
+    // Comparison
+    #[error("can't mix '<' and '>' comparisons in a chain")]
+    MixedComparisonChain,
+
     // While
     #[error("'while' statement with no condition")]
     WhileWithNoCond,
@@ -86,6 +94,17 @@ pub enum ParserErr {
     #[error("missing block start '{{' after 'while' condition")]
     MissingWhileOpenBrace,
 
+    // Do while
+    #[error("missing block start '{{' after 'do' keyword")]
+    MissingDoOpenBrace,
+
+    #[error("missing 'while' condition after 'do' block")]
+    MissingDoWhile,
+
+    // Loop
+    #[error("missing block start '{{' after 'loop' keyword")]
+    MissingLoopOpenBrace,
+
     // For
     #[error("missing variable name in 'for' loop")]
     MissingVarNameFor,
@@ -96,9 +115,6 @@ pub enum ParserErr {
     #[error("missing range in 'for' loop")]
     MissingForRange,
 
-    #[error("only 'ints' are supported as 'for' ranges")]
-    NonIntForRange,
-
     #[error("range can only be positive")]
     NegativeForRange,
 
@@ -111,9 +127,38 @@ pub enum ParserErr {
     #[error("missing end of range after '..'")]
     MissingEndForRange,
 
+    #[error("missing step of range after ':'")]
+    MissingStepForRange,
+
     #[error("missing block start '{{' after 'for' condition")]
     MissingForOpenBrace,
 
+    // Type alias
+    #[error("missing alias name after 'type' keyword")]
+    MissingTypeAliasName,
+
+    #[error("missing '=' after type alias name")]
+    MissingTypeAliasEqual,
+
+    #[error("type aliases can only be declared at top level")]
+    NonTopLevelTypeAlias,
+
+    // Match
+    #[error("'match' statement with no value")]
+    MatchWithNoValue,
+
+    #[error("missing block start '{{' after 'match' value")]
+    MissingMatchOpenBrace,
+
+    #[error("missing '}}' after 'match' arms")]
+    MissingMatchCloseBrace,
+
+    #[error("missing '=>' after match pattern")]
+    MissingMatchArrow,
+
+    #[error("match patterns must be literals, enum variants or '_'")]
+    InvalidMatchPattern,
+
     // Call
     #[error("missing close parenthesis after arguments list")]
     MissingCallCloseParen,
@@ -124,6 +169,9 @@ pub enum ParserErr {
     #[error("missing comma to seperate arguments")]
     MissingArgsComma,
 
+    #[error("positional arguments can't follow named arguments")]
+    PositionalAfterNamedArg,
+
     // Function declaration
     #[error("missing function name after 'fn' keyword")]
     MissingFnName,
@@ -162,10 +210,71 @@ pub enum ParserErr {
     #[error("missing structure field's type")]
     StructFieldNoType,
 
+    // Annotations
+    #[error("missing annotation name after '@'")]
+    MissingAnnotationName,
+
+    #[error("annotation arguments must be identifiers")]
+    NonIdentAnnotationArg,
+
+    #[error("annotations must be followed by a function or structure declaration")]
+    AnnotationWithoutDecl,
+
+    // Enum declaration
+    #[error("missing enum name after 'enum' keyword")]
+    MissingEnumName,
+
+    #[error("missing '{{' before enum body")]
+    MissingEnumOpenBrace,
+
+    #[error("missing '}}' after enum body")]
+    MissingEnumCloseBrace,
+
+    #[error("enum variants must be identifiers")]
+    NonIdentEnumVariant,
+
+    #[error("missing comma to seperate enum variants")]
+    MissingEnumVariantComma,
+
+    #[error("missing enum variant value after '='")]
+    MissingEnumVariantValue,
+
     // Property
     #[error("missing property name after '.'")]
     MissingPropName,
 
+    // Arrays and maps
+    #[error("missing ']' to close array")]
+    MissingArrayCloseBracket,
+
+    #[error("missing comma to seperate array values")]
+    MissingArrayComma,
+
+    #[error("missing '}}' to close map")]
+    MissingMapCloseBrace,
+
+    #[error("missing ':' between map key and value")]
+    MissingMapColon,
+
+    #[error("missing comma to seperate map entries")]
+    MissingMapComma,
+
+    // Structure literal
+    #[error("missing '}}' to close structure literal")]
+    MissingStructLitCloseBrace,
+
+    #[error("expected a field name in structure literal")]
+    MissingStructLitFieldName,
+
+    #[error("missing ':' between field name and value")]
+    MissingStructLitColon,
+
+    #[error("missing comma to seperate structure literal fields")]
+    MissingStructLitComma,
+
+    #[error("missing ']' after index")]
+    MissingIndexCloseBracket,
+
     // Types
     #[error("type name expected after '{0}'")]
     ExpectedTypeName(String),
@@ -173,9 +282,12 @@ pub enum ParserErr {
     #[error("missing '{0}' to declare type")]
     MissingTokenTypeDecl(String),
 
-    #[error("right hand side of 'is' must be a type identifier")]
+    #[error("right hand side of 'is' must be a type")]
     NonIdentTypeInIs,
 
+    #[error("right hand side of 'as' must be a type identifier")]
+    NonIdentTypeInAs,
+
     #[error("missing opening parenthesis in lambda type declaration")]
     MissingOpenParenLambda,
     
@@ -191,6 +303,15 @@ pub enum ParserErr {
     #[error("missing '->' before return type in lambda type declaration")]
     MissingSmallArrowLambda,
 
+    #[error("missing ']' in array type declaration")]
+    MissingCloseBracketArrayType,
+
+    #[error("missing ':' between key and value types in map type declaration")]
+    MissingColonMapType,
+
+    #[error("missing '}}' in map type declaration")]
+    MissingCloseBraceMapType,
+
     // Others
     #[error("unexpected end of file")]
     UnexpectedEof,
@@ -219,7 +340,11 @@ enum CodeBlock {
     FnDeclBody,
     FnCallArgs,
     Struct,
+    Enum,
     Block,
+    // Body of a match arm written on the same line as the pattern, where
+    // a comma separates it from the next arm
+    MatchArm,
 }
 
 // ---------
@@ -228,9 +353,12 @@ enum CodeBlock {
 #[derive(Default)]
 pub struct Parser {
     tokens: Vec<Token>,
-    start_loc: usize,
+    start_loc: Loc,
     current: usize,
     code_blocks: Vec<CodeBlock>,
+    // In 'if', 'while', 'for' and 'match' headers, 'Foo {}' is a condition
+    // followed by an empty block, not a structure literal
+    in_condition: bool,
 }
 
 impl Parser {
@@ -305,6 +433,8 @@ impl Parser {
                 }
             }
             TokenKind::NewLine | TokenKind::Eof | TokenKind::CloseBrace => {}
+            // Structure fields can be on the same line: x: int, y: int
+            TokenKind::Comma if self.code_blocks.last() == Some(&CodeBlock::Struct) => {}
             _ => return Err(self.trigger_error(ParserErr::WrongRhsVarDecl)),
         }
 
@@ -322,10 +452,18 @@ impl Parser {
             TokenKind::OpenBrace => self.parse_block_stmt(),
             TokenKind::If => self.parse_if_stmt(),
             TokenKind::While => self.parse_while_stmt(),
+            TokenKind::Do => self.parse_do_while_stmt(),
+            TokenKind::Loop => self.parse_loop_stmt(),
+            TokenKind::Break => self.parse_break_stmt(),
+            TokenKind::Continue => self.parse_continue_stmt(),
             TokenKind::For => self.parse_for_stmt(),
             TokenKind::Fn => self.parse_fn_decl_stmt(),
             TokenKind::Return => self.parse_return_stmt(),
             TokenKind::Struct => self.parse_struct_stmt(),
+            TokenKind::Enum => self.parse_enum_stmt(),
+            TokenKind::Match => self.parse_match_stmt(),
+            TokenKind::Type => self.parse_type_alias_stmt(),
+            TokenKind::At => self.parse_annotated_stmt(),
             _ => self.parse_expr_stmt(),
         };
 
@@ -337,10 +475,23 @@ impl Parser {
     fn parse_print_stmt(&mut self) -> ParserStmtRes {
         self.expect(TokenKind::Print)?;
 
-        let expr = self.parse_expr()?;
+        // A bare 'print' prints an empty line
+        let mut exprs = vec![];
+        if !self.is_at(TokenKind::NewLine)
+            && !self.is_at(TokenKind::CloseBrace)
+            && !self.eof()
+        {
+            exprs.push(self.parse_expr()?);
+
+            // In a match arm, the comma is the one separating the arms
+            while self.is_at(TokenKind::Comma) && self.code_blocks.last() != Some(&CodeBlock::MatchArm) {
+                self.eat()?;
+                exprs.push(self.parse_expr()?);
+            }
+        }
 
         Ok(Stmt::Print(PrintStmt {
-            expr,
+            exprs,
             loc: self.get_loc(),
         }))
     }
@@ -372,7 +523,7 @@ impl Parser {
         self.eat()?;
         self.is_at_brace_or_end_of(ParserErr::IfWithNoCond)?;
 
-        let condition = self.parse_expr()?;
+        let condition = self.parse_condition()?;
 
         let tk = self.skip_expect_and_skip(TokenKind::OpenBrace)
             .map_err(|_| self.trigger_error(ParserErr::MissingIfOpenBrace))?;
@@ -417,7 +568,7 @@ impl Parser {
         self.eat()?;
         self.is_at_brace_or_end_of(ParserErr::WhileWithNoCond)?;
 
-        let condition = self.parse_expr()?;
+        let condition = self.parse_condition()?;
         self.skip_new_lines();
 
         if !self.is_at(TokenKind::OpenBrace) {
@@ -433,6 +584,65 @@ impl Parser {
         }))
     }
 
+    fn parse_do_while_stmt(&mut self) -> ParserStmtRes {
+        self.eat()?;
+        self.skip_new_lines();
+
+        if !self.is_at(TokenKind::OpenBrace) {
+            return Err(self.trigger_error(ParserErr::MissingDoOpenBrace));
+        }
+
+        let body = Box::new(self.parse_block_stmt()?);
+
+        if !self.is_at(TokenKind::While) {
+            return Err(self.trigger_error(ParserErr::MissingDoWhile));
+        }
+
+        self.eat()?;
+        self.is_at_brace_or_end_of(ParserErr::WhileWithNoCond)?;
+
+        let condition = self.parse_expr()?;
+
+        Ok(Stmt::DoWhile(DoWhileStmt {
+            body,
+            condition,
+            loc: self.get_loc(),
+        }))
+    }
+
+    // Sugar for 'while true', only exited with 'break' or 'return'
+    fn parse_loop_stmt(&mut self) -> ParserStmtRes {
+        let loop_tk = self.eat()?.clone();
+        self.skip_new_lines();
+
+        if !self.is_at(TokenKind::OpenBrace) {
+            return Err(self.trigger_error(ParserErr::MissingLoopOpenBrace));
+        }
+
+        let body = Box::new(self.parse_stmt()?);
+
+        Ok(Stmt::While(WhileStmt {
+            condition: Expr::Identifier(IdentifierExpr {
+                name: "true".into(),
+                loc: loop_tk.loc,
+            }),
+            body,
+            loc: self.get_loc(),
+        }))
+    }
+
+    fn parse_break_stmt(&mut self) -> ParserStmtRes {
+        let loc = self.eat()?.loc.clone();
+
+        Ok(Stmt::Break(BreakStmt { loc }))
+    }
+
+    fn parse_continue_stmt(&mut self) -> ParserStmtRes {
+        let loc = self.eat()?.loc.clone();
+
+        Ok(Stmt::Continue(ContinueStmt { loc }))
+    }
+
     fn parse_for_stmt(&mut self) -> ParserStmtRes {
         self.eat()?;
 
@@ -458,31 +668,27 @@ impl Parser {
             return Err(self.trigger_error(ParserErr::NegativeForRange));
         }
 
-        let start = self
-            .expect(TokenKind::IntLit)
-            .map_err(|_| self.trigger_error(ParserErr::NonIntForRange))?
-            .value
-            .parse::<i64>()
-            .map_err(|_| self.trigger_error(ParserErr::ParsingInt))?;
-
-        let mut end = None;
-        if self.is_at(TokenKind::DotDot) {
-            self.eat()?;
-
-            self.is_at_brace_or_end_of(ParserErr::MissingEndForRange)?;
-
-            end = Some(
-                self.expect(TokenKind::IntLit)
-                    .map_err(|_| self.trigger_error(ParserErr::NonIntForRange))?
-                    .value
-                    .parse::<i64>()
-                    .map_err(|_| self.trigger_error(ParserErr::ParsingInt))?,
-            );
-
-            if Some(start) > end {
-                return Err(self.trigger_error(ParserErr::LesserEndForRange));
-            }
-        }
+        let iterable = match self.parse_condition()? {
+            // A count is a range from 0: for i in 3 {}
+            Expr::IntLiteral(end) => Expr::Range(RangeExpr {
+                start: Box::new(Expr::IntLiteral(IntLiteralExpr {
+                    value: 0,
+                    loc: end.loc.clone(),
+                })),
+                loc: end.loc.clone(),
+                end: Box::new(Expr::IntLiteral(end)),
+                step: None,
+                inclusive: false,
+            }),
+            // With a step, the range can count down: 10..0:-1
+            Expr::Range(r) => match (&*r.start, &*r.end) {
+                (Expr::IntLiteral(s), Expr::IntLiteral(e)) if s.value > e.value && r.step.is_none() => {
+                    return Err(self.trigger_error(ParserErr::LesserEndForRange));
+                }
+                _ => Expr::Range(r),
+            },
+            e => e,
+        };
 
         self.skip_new_lines();
         if !self.is_at(TokenKind::OpenBrace) {
@@ -493,7 +699,7 @@ impl Parser {
 
         Ok(Stmt::For(ForStmt {
             placeholder,
-            range: ForRange { start, end },
+            iterable,
             body,
             loc: self.get_loc(),
         }))
@@ -502,10 +708,77 @@ impl Parser {
     fn parse_fn_decl_stmt(&mut self) -> ParserStmtRes {
         self.eat()?;
 
-        Ok(Stmt::FnDecl(self.parse_fn_decl()?))
+        Ok(Stmt::FnDecl(self.parse_fn_decl(vec![])?))
+    }
+
+    // Annotations are attached to the declaration following them
+    fn parse_annotated_stmt(&mut self) -> ParserStmtRes {
+        let annotations = self.parse_annotations()?;
+
+        match self.at().kind {
+            TokenKind::Fn => {
+                self.eat()?;
+
+                Ok(Stmt::FnDecl(self.parse_fn_decl(annotations)?))
+            }
+            TokenKind::Struct => self.parse_struct_decl(annotations),
+            _ => Err(self.trigger_error(ParserErr::AnnotationWithoutDecl)),
+        }
+    }
+
+    // @name or @name(arg, ...), each one can be on its own line
+    fn parse_annotations(&mut self) -> Result<Vec<Annotation>, RizonResParser> {
+        let mut annotations: Vec<Annotation> = vec![];
+
+        while self.is_at(TokenKind::At) {
+            let start = self.eat()?.loc.clone();
+
+            let name = self
+                .expect_no_eat(TokenKind::Identifier)
+                .map_err(|_| self.trigger_error(ParserErr::MissingAnnotationName))?;
+
+            let mut args: Vec<Token> = vec![];
+            if self.is_at(TokenKind::OpenParen) {
+                let open_paren = self.eat()?.clone();
+
+                while !self.is_at(TokenKind::CloseParen) {
+                    if self.is_at(TokenKind::NewLine) || self.eof() {
+                        return Err(
+                            self.trigger_error_with_loc(ParserErr::ParenNeverClosed, open_paren.loc)
+                        );
+                    }
+
+                    let arg = self
+                        .expect_no_eat(TokenKind::Identifier)
+                        .map_err(|_| self.trigger_error(ParserErr::NonIdentAnnotationArg))?;
+
+                    args.push(arg);
+
+                    if self.is_at(TokenKind::Comma) {
+                        self.eat()?;
+                    } else if !self.is_at(TokenKind::CloseParen) {
+                        return Err(
+                            self.trigger_error_with_loc(ParserErr::ParenNeverClosed, open_paren.loc)
+                        );
+                    }
+                }
+
+                self.eat()?;
+            }
+
+            annotations.push(Annotation {
+                name,
+                args,
+                loc: self.get_loc_from(&start),
+            });
+
+            self.skip_new_lines();
+        }
+
+        Ok(annotations)
     }
 
-    fn parse_fn_decl(&mut self) -> Result<FnDeclStmt, RizonResParser> {
+    fn parse_fn_decl(&mut self, annotations: Vec<Annotation>) -> Result<FnDeclStmt, RizonResParser> {
         self.enter_code_block(CodeBlock::FnDecl);
 
         let name = self
@@ -595,6 +868,7 @@ impl Parser {
             params: Arc::new(params),
             body,
             return_type,
+            annotations,
             loc: self.get_loc(),
         })
     }
@@ -614,6 +888,10 @@ impl Parser {
     }
 
     fn parse_struct_stmt(&mut self) -> ParserStmtRes {
+        self.parse_struct_decl(vec![])
+    }
+
+    fn parse_struct_decl(&mut self, annotations: Vec<Annotation>) -> ParserStmtRes {
         self.eat()?;
 
         self.enter_code_block(CodeBlock::Struct);
@@ -644,13 +922,23 @@ impl Parser {
 
             fields.push(field);
 
+            if self.is_at(TokenKind::Comma) {
+                self.eat()?;
+            }
+
             self.skip_new_lines();
         }
 
         let mut methods: Vec<FnDeclStmt> = vec![];
-        while !self.is_at(TokenKind::CloseBrace) && !self.eof() && self.is_at(TokenKind::Fn) {
-            self.eat()?;
-            methods.push(self.parse_fn_decl()?);
+        while !self.is_at(TokenKind::CloseBrace)
+            && !self.eof()
+            && (self.is_at(TokenKind::Fn) || self.is_at(TokenKind::At))
+        {
+            let annotations = self.parse_annotations()?;
+
+            self.expect(TokenKind::Fn)
+                .map_err(|_| self.trigger_error(ParserErr::AnnotationWithoutDecl))?;
+            methods.push(self.parse_fn_decl(annotations)?);
 
             self.skip_new_lines();
         }
@@ -668,10 +956,167 @@ impl Parser {
             name,
             fields,
             methods,
+            annotations,
+            loc: self.get_loc(),
+        }))
+    }
+
+    fn parse_enum_stmt(&mut self) -> ParserStmtRes {
+        self.eat()?;
+
+        self.enter_code_block(CodeBlock::Enum);
+
+        let name = self
+            .expect(TokenKind::Identifier)
+            .map_err(|_| self.trigger_error(ParserErr::MissingEnumName))?;
+
+        self.expect(TokenKind::OpenBrace)
+            .map_err(|_| self.trigger_error(ParserErr::MissingEnumOpenBrace))?;
+
+        self.skip_new_lines();
+
+        let mut variants: Vec<EnumVariantDecl> = vec![];
+        while !self.is_at(TokenKind::CloseBrace) && !self.eof() {
+            let name = self
+                .expect(TokenKind::Identifier)
+                .map_err(|_| self.trigger_error(ParserErr::NonIdentEnumVariant))?;
+
+            let value = if self.is_at(TokenKind::Equal) {
+                self.eat()?;
+
+                if self.is_at(TokenKind::Comma)
+                    || self.is_at(TokenKind::CloseBrace)
+                    || self.is_at(TokenKind::NewLine)
+                    || self.eof()
+                {
+                    return Err(self.trigger_error(ParserErr::MissingEnumVariantValue));
+                }
+
+                Some(self.parse_expr()?)
+            } else {
+                None
+            };
+
+            variants.push(EnumVariantDecl { name, value });
+
+            self.skip_new_lines();
+
+            if self.is_at(TokenKind::Comma) {
+                self.eat()?;
+                self.skip_new_lines();
+            } else if !self.is_at(TokenKind::CloseBrace) {
+                return Err(self.trigger_error(ParserErr::MissingEnumVariantComma));
+            }
+        }
+
+        self.expect(TokenKind::CloseBrace)
+            .map_err(|_| self.trigger_error(ParserErr::MissingEnumCloseBrace))?;
+
+        self.exit_code_block();
+
+        Ok(Stmt::EnumDecl(EnumDeclStmt {
+            name,
+            variants,
+            loc: self.get_loc(),
+        }))
+    }
+
+    fn parse_type_alias_stmt(&mut self) -> ParserStmtRes {
+        if self.code_blocks.last() != Some(&CodeBlock::Global) {
+            return Err(self.trigger_error(ParserErr::NonTopLevelTypeAlias));
+        }
+
+        self.eat()?;
+
+        let name = self
+            .expect_no_eat(TokenKind::Identifier)
+            .map_err(|_| self.trigger_error_before_cur_len_one(ParserErr::MissingTypeAliasName))?;
+
+        let typ = match self.parse_type(TokenKind::Equal, "=")? {
+            Some(t) => t,
+            None => return Err(self.trigger_error(ParserErr::MissingTypeAliasEqual)),
+        };
+
+        Ok(Stmt::TypeAlias(TypeAliasStmt {
+            name,
+            typ,
+            loc: self.get_loc(),
+        }))
+    }
+
+    fn parse_match_stmt(&mut self) -> ParserStmtRes {
+        self.eat()?;
+        self.is_at_brace_or_end_of(ParserErr::MatchWithNoValue)?;
+
+        let expr = self.parse_condition()?;
+
+        let open_brace = self
+            .skip_expect_and_skip(TokenKind::OpenBrace)
+            .map_err(|_| self.trigger_error(ParserErr::MissingMatchOpenBrace))?;
+
+        self.enter_code_block(CodeBlock::Block);
+
+        let mut arms: Vec<MatchArm> = vec![];
+        while !self.is_at(TokenKind::CloseBrace) && !self.eof() {
+            let pattern = if self.is_at(TokenKind::Underscore) {
+                MatchPattern::Wildcard(self.eat()?.loc.clone())
+            } else {
+                MatchPattern::Value(self.parse_match_pattern()?)
+            };
+
+            self.expect(TokenKind::FatArrow)
+                .map_err(|_| self.trigger_error(ParserErr::MissingMatchArrow))?;
+            self.skip_new_lines();
+
+            self.enter_code_block(CodeBlock::MatchArm);
+            let body = Box::new(self.parse_stmt()?);
+            self.exit_code_block();
+
+            arms.push(MatchArm { pattern, body });
+
+            if self.is_at(TokenKind::Comma) {
+                self.eat()?;
+                self.skip_new_lines();
+            }
+        }
+
+        self.expect(TokenKind::CloseBrace).map_err(|_| {
+            self.trigger_error_with_loc(ParserErr::MissingMatchCloseBrace, open_brace.loc)
+        })?;
+
+        self.exit_code_block();
+
+        Ok(Stmt::Match(MatchStmt {
+            expr,
+            arms,
             loc: self.get_loc(),
         }))
     }
 
+    fn parse_match_pattern(&mut self) -> ParserExprRes {
+        let pattern = self.parse_unary()?;
+
+        let is_valid = match &pattern {
+            Expr::IntLiteral(_)
+            | Expr::FloatLiteral(_)
+            | Expr::StrLiteral(_)
+            | Expr::CharLiteral(_) => true,
+            Expr::Identifier(i) => matches!(i.name.as_str(), "true" | "false" | "null"),
+            Expr::Unary(u) => {
+                u.operator.kind == TokenKind::Minus
+                    && matches!(*u.right, Expr::IntLiteral(_) | Expr::FloatLiteral(_))
+            }
+            Expr::Get(g) => matches!(*g.object, Expr::Identifier(_)),
+            _ => false,
+        };
+
+        if !is_valid {
+            return Err(self.trigger_error_with_loc(ParserErr::InvalidMatchPattern, pattern.get_loc()));
+        }
+
+        Ok(pattern)
+    }
+
     fn parse_type(
         &mut self,
         start_token: TokenKind,
@@ -687,36 +1132,87 @@ impl Parser {
         if self.is_at(start_token) {
             self.eat()?;
 
-            if !self.is_at_type() {
-                let err_loc = Loc::new_len_one_from_start(self.prev().loc.clone());
+            return Ok(Some(self.parse_type_decl(tk_str)?));
+        }
 
-                return Err(self
-                    .trigger_error_with_loc(ParserErr::ExpectedTypeName(tk_str.into()), err_loc));
-            }
+        Ok(None)
+    }
 
-            let tmp_type = self.eat()?.clone();
+    fn parse_type_decl(&mut self, prev_tk_str: &str) -> Result<VarTypeDecl, RizonResParser> {
+        if !self.is_at_type_start() {
+            let err_loc = Loc::new_len_one_from_start(self.prev().loc.clone());
 
-            return Ok(Some(match tmp_type.kind {
-                TokenKind::Fn => self.parse_fn_type(tmp_type)?,
-                _ => VarTypeDecl::Identifier(tmp_type)
-            }))
+            return Err(self
+                .trigger_error_with_loc(ParserErr::ExpectedTypeName(prev_tk_str.into()), err_loc));
         }
 
-        Ok(None)
+        let tmp_type = self.eat()?.clone();
+
+        let typ = match tmp_type.kind {
+            TokenKind::Fn => self.parse_fn_type(tmp_type)?,
+            TokenKind::OpenBracket => self.parse_array_type(tmp_type)?,
+            TokenKind::OpenBrace => self.parse_map_type(tmp_type)?,
+            _ => VarTypeDecl::Identifier(tmp_type),
+        };
+
+        // Nullable type: int?
+        if self.is_at(TokenKind::Question) {
+            let question = self.eat()?.clone();
+            let loc = Loc::span(&typ.get_loc(), &question.loc);
+
+            return Ok(VarTypeDecl::Optional {
+                typ: Box::new(typ),
+                loc,
+            });
+        }
+
+        Ok(typ)
+    }
+
+    fn parse_array_type(&mut self, open_bracket: Token) -> Result<VarTypeDecl, RizonResParser> {
+        let typ = self.parse_type_decl("[")?;
+
+        let close_bracket = self
+            .expect_no_eat(TokenKind::CloseBracket)
+            .map_err(|_| self.trigger_error(ParserErr::MissingCloseBracketArrayType))?;
+
+        Ok(VarTypeDecl::Array {
+            typ: Box::new(typ),
+            loc: Loc::span(&open_bracket.loc, &close_bracket.loc),
+        })
+    }
+
+    fn parse_map_type(&mut self, open_brace: Token) -> Result<VarTypeDecl, RizonResParser> {
+        let key = self.parse_type_decl("{")?;
+
+        self.expect_no_eat(TokenKind::Colon)
+            .map_err(|_| self.trigger_error(ParserErr::MissingColonMapType))?;
+
+        let value = self.parse_type_decl(":")?;
+
+        let close_brace = self
+            .expect_no_eat(TokenKind::CloseBrace)
+            .map_err(|_| self.trigger_error(ParserErr::MissingCloseBraceMapType))?;
+
+        Ok(VarTypeDecl::Map {
+            key: Box::new(key),
+            value: Box::new(value),
+            loc: Loc::span(&open_brace.loc, &close_brace.loc),
+        })
     }
 
     fn parse_fn_type(&mut self, fn_tk: Token) -> Result<VarTypeDecl, RizonResParser> {
         self.expect_no_eat_and_skip(TokenKind::OpenParen)
             .map_err(|_| self.trigger_error_before_cur_len_one(ParserErr::MissingOpenParenLambda))?;
 
-        if !self.is_at_type() && !self.is_at(TokenKind::CloseParen) {
+        if !self.is_at_type_start() && !self.is_at(TokenKind::CloseParen) {
             return Err(self
                 .trigger_error_with_loc(ParserErr::NonTypeArgsLambdaDecl, self.at().loc.clone()));
         }
 
-        let mut param_types: Vec<Token> = vec![];
-        while self.is_at_type() && !self.eof() {
-            param_types.push(self.eat()?.clone());
+        let mut param_types: Vec<VarTypeDecl> = vec![];
+        while self.is_at_type_start() && !self.eof() {
+            param_types.push(self.parse_type_decl("(")?);
 
             if self.at().kind == TokenKind::Comma {
                 self.eat()?;
@@ -730,17 +1226,13 @@ impl Parser {
         if self.is_at(TokenKind::SmallArrow) {
             self.eat()?;
 
-            if !self.is_at_type() {
+            if !self.is_at_type_start() {
                 return Err(self.trigger_error_before_cur_len_one(ParserErr::MissingTypeArrowLambda))
             }
 
-            if self.is_at(TokenKind::Fn) {
-                let tk = self.eat()?.clone();
+            let return_type = self.parse_type_decl("->")?;
 
-                Ok(VarTypeDecl::Fn { fn_tk: tk.clone(), param_types, return_type: Some(Box::new(self.parse_fn_type(tk)?)) })
-            } else {
-                Ok(VarTypeDecl::Fn { fn_tk, param_types: param_types, return_type: Some(Box::new(VarTypeDecl::Identifier(self.eat()?.clone()))) })
-            }
+            Ok(VarTypeDecl::Fn { fn_tk, param_types, return_type: Some(Box::new(return_type)) })
         } else {
             if self.is_at_type() {
                 return Err(self.trigger_error_before_cur_len_one(ParserErr::MissingSmallArrowLambda))
@@ -751,7 +1243,12 @@ impl Parser {
     }
 
     fn parse_expr_stmt(&mut self) -> ParserStmtRes {
-        let expr = self.parse_expr()?;
+        let mut expr = self.parse_expr()?;
+
+        // x++ and x-- don't yield a value, they are only allowed as statements
+        if self.is_at(TokenKind::PlusPlus) || self.is_at(TokenKind::MinusMinus) {
+            expr = self.parse_increment(expr)?;
+        }
 
         Ok(Stmt::Expr(ExprStmt {
             expr,
@@ -759,12 +1256,35 @@ impl Parser {
         }))
     }
 
+    // x++  ->  x += 1, lowered like any compound assignment
+    fn parse_increment(&mut self, target: Expr) -> ParserExprRes {
+        let operator = self.eat()?.clone();
+
+        match target {
+            Expr::Identifier(_) | Expr::Get(_) | Expr::Index(_) => {
+                let start = target.get_loc();
+
+                Ok(Expr::CompoundAssign(CompoundAssignExpr {
+                    target: Box::new(target),
+                    value: Box::new(Expr::IntLiteral(IntLiteralExpr {
+                        value: 1,
+                        loc: operator.loc.clone(),
+                    })),
+                    operator,
+                    loc: self.get_loc_from(&start),
+                }))
+            }
+            _ => Err(self.trigger_error_with_loc(ParserErr::InvalidAssignTarget, target.get_loc())),
+        }
+    }
+
     fn parse_expr(&mut self) -> ParserExprRes {
         self.parse_assign()
     }
 
     fn parse_assign(&mut self) -> ParserExprRes {
-        let assigne = self.parse_or()?;
+        let assigne = self.parse_range()?;
+        let start = assigne.get_loc();
 
         match self.at().kind {
             TokenKind::Equal => {
@@ -774,22 +1294,103 @@ impl Parser {
                     Expr::Identifier(e) => Ok(Expr::Assign(AssignExpr {
                         name: e.name.clone(),
                         value: Box::new(self.parse_assign()?),
-                        loc: self.get_loc_from_prev(),
+                        loc: self.get_loc_from(&start),
                     })),
                     Expr::Get(e) => Ok(Expr::Set(SetExpr {
                         object: e.object,
                         name: e.name,
                         value: Box::new(self.parse_assign()?),
-                        loc: self.get_loc_from_prev(),
+                        compound: false,
+                        loc: self.get_loc_from(&start),
+                    })),
+                    Expr::Index(e) => Ok(Expr::IndexSet(IndexSetExpr {
+                        object: e.object,
+                        index: e.index,
+                        value: Box::new(self.parse_assign()?),
+                        compound: false,
+                        loc: self.get_loc_from(&start),
                     })),
                     _ => Err(self
                         .trigger_error_with_loc(ParserErr::InvalidAssignTarget, assigne.get_loc())),
                 }
             }
+            TokenKind::PlusEqual
+            | TokenKind::MinusEqual
+            | TokenKind::StarEqual
+            | TokenKind::SlashEqual
+            | TokenKind::ModuloEqual => {
+                let operator = self.eat()?.clone();
+
+                match assigne {
+                    Expr::Identifier(_) | Expr::Get(_) | Expr::Index(_) => {
+                        Ok(Expr::CompoundAssign(CompoundAssignExpr {
+                            target: Box::new(assigne),
+                            operator,
+                            value: Box::new(self.parse_assign()?),
+                            loc: self.get_loc_from(&start),
+                        }))
+                    }
+                    _ => Err(self
+                        .trigger_error_with_loc(ParserErr::InvalidAssignTarget, assigne.get_loc())),
+                }
+            }
             _ => Ok(assigne),
         }
     }
 
+    // Not associative: 0..n, 0..=n or 0..n:step
+    fn parse_range(&mut self) -> ParserExprRes {
+        let start = self.parse_null_coalescing()?;
+
+        if !self.is_at(TokenKind::DotDot) && !self.is_at(TokenKind::DotDotEqual) {
+            return Ok(start);
+        }
+
+        let inclusive = self.eat()?.kind == TokenKind::DotDotEqual;
+
+        self.is_at_brace_or_end_of(ParserErr::MissingEndForRange)?;
+
+        let end = self.parse_null_coalescing()?;
+
+        let mut step = None;
+        if self.is_at(TokenKind::Colon) {
+            self.eat()?;
+            self.is_at_brace_or_end_of(ParserErr::MissingStepForRange)?;
+
+            step = Some(Box::new(self.parse_null_coalescing()?));
+        }
+
+        let last = step.as_deref().unwrap_or(&end);
+
+        Ok(Expr::Range(RangeExpr {
+            loc: Loc::span(&start.get_loc(), &last.get_loc()),
+            start: Box::new(start),
+            end: Box::new(end),
+            step,
+            inclusive,
+        }))
+    }
+
+    fn parse_null_coalescing(&mut self) -> ParserExprRes {
+        let mut left = self.parse_or()?;
+
+        while self.is_at(TokenKind::QuestionQuestion) {
+            let op = self.eat()?.clone();
+            let right = self.parse_or()?;
+
+            let loc = Loc::span(&left.get_loc(), &right.get_loc());
+
+            left = Expr::Logical(LogicalExpr {
+                left: Box::new(left),
+                operator: op,
+                right: Box::new(right),
+                loc,
+            });
+        }
+
+        Ok(left)
+    }
+
     fn parse_or(&mut self) -> ParserExprRes {
         let mut left = self.parse_and()?;
 
@@ -805,11 +1406,13 @@ impl Parser {
 
             let right = self.parse_and()?;
 
+            let loc = Loc::span(&left.get_loc(), &right.get_loc());
+
             left = Expr::Logical(LogicalExpr {
                 left: Box::new(left),
                 operator: op,
                 right: Box::new(right),
-                loc: self.get_loc(),
+                loc,
             });
         }
 
@@ -831,11 +1434,13 @@ impl Parser {
 
             let right = self.parse_equality()?;
 
+            let loc = Loc::span(&left.get_loc(), &right.get_loc());
+
             left = Expr::Logical(LogicalExpr {
                 left: Box::new(left),
                 operator: op,
                 right: Box::new(right),
-                loc: self.get_loc(),
+                loc,
             });
         }
 
@@ -859,8 +1464,11 @@ impl Parser {
         Ok(expr)
     }
 
+    // Chains go in one direction, mixing '<' and '>' would be ambiguous
     fn parse_comparison(&mut self) -> ParserExprRes {
-        let mut expr = self.parse_is()?;
+        let mut operands = vec![self.parse_is()?];
+        let mut operators: Vec<Token> = vec![];
+        let is_ascending = |op: &Token| matches!(op.kind, TokenKind::Less | TokenKind::LessEqual);
 
         while self.is_at(TokenKind::Less)
             || self.is_at(TokenKind::LessEqual)
@@ -868,16 +1476,32 @@ impl Parser {
             || self.is_at(TokenKind::GreaterEqual)
         {
             let operator = self.eat()?.clone();
-            let right = self.parse_term()?;
 
-            expr = Expr::Binary(BinaryExpr {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            });
+            if operators.first().is_some_and(|first| is_ascending(first) != is_ascending(&operator)) {
+                return Err(self.trigger_error_with_loc(ParserErr::MixedComparisonChain, operator.loc));
+            }
+
+            operators.push(operator);
+            operands.push(self.parse_term()?);
         }
 
-        Ok(expr)
+        match operators.len() {
+            0 => Ok(operands.pop().unwrap()),
+            1 => {
+                let right = operands.pop().unwrap();
+
+                Ok(Expr::Binary(BinaryExpr {
+                    left: Box::new(operands.pop().unwrap()),
+                    operator: operators.pop().unwrap(),
+                    right: Box::new(right),
+                }))
+            }
+            _ => Ok(Expr::ChainedCompare(ChainedCompareExpr {
+                loc: Loc::span(&operands[0].get_loc(), &operands.last().unwrap().get_loc()),
+                operands,
+                operators,
+            })),
+        }
     }
 
     fn parse_is(&mut self) -> ParserExprRes {
@@ -890,12 +1514,13 @@ impl Parser {
                 return Err(self.trigger_error(ParserErr::NonIdentTypeInIs));
             }
 
-            let typ = self.eat()?.clone();
+            // Function types can be checked: f is fn(int) -> int
+            let typ = self.parse_type_decl("is")?;
 
             expr = Expr::Is(IsExpr {
+                loc: Loc::span(&expr.get_loc(), &typ.get_loc()),
                 left: Box::new(expr),
                 typ,
-                loc: self.get_loc(),
             });
         }
 
@@ -920,14 +1545,15 @@ impl Parser {
     }
 
     fn parse_factor(&mut self) -> ParserExprRes {
-        let mut expr = self.parse_unary()?;
+        let mut expr = self.parse_cast()?;
 
         while self.is_at(TokenKind::Star)
             || self.is_at(TokenKind::Slash)
+            || self.is_at(TokenKind::TildeSlash)
             || self.is_at(TokenKind::Modulo)
         {
             let operator = self.eat()?.clone();
-            let right = self.parse_unary()?;
+            let right = self.parse_cast()?;
 
             expr = Expr::Binary(BinaryExpr {
                 left: Box::new(expr),
@@ -939,6 +1565,28 @@ impl Parser {
         Ok(expr)
     }
 
+    fn parse_cast(&mut self) -> ParserExprRes {
+        let mut expr = self.parse_unary()?;
+
+        while self.is_at(TokenKind::As) {
+            self.eat()?;
+
+            if !self.is_at_type() {
+                return Err(self.trigger_error(ParserErr::NonIdentTypeInAs));
+            }
+
+            let typ = self.eat()?.clone();
+
+            expr = Expr::Cast(CastExpr {
+                loc: Loc::span(&expr.get_loc(), &typ.loc),
+                expr: Box::new(expr),
+                typ,
+            });
+        }
+
+        Ok(expr)
+    }
+
     fn parse_unary(&mut self) -> ParserExprRes {
         if self.is_at(TokenKind::Bang) || self.is_at(TokenKind::Minus) {
             let operator = self.eat()?.clone();
@@ -950,7 +1598,25 @@ impl Parser {
             }));
         }
 
-        self.parse_call()
+        self.parse_power()
+    }
+
+    // Right associative: 2 ** 3 ** 2 == 2 ** (3 ** 2)
+    fn parse_power(&mut self) -> ParserExprRes {
+        let expr = self.parse_call()?;
+
+        if self.is_at(TokenKind::StarStar) {
+            let operator = self.eat()?.clone();
+            let right = self.parse_unary()?;
+
+            return Ok(Expr::Binary(BinaryExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            }));
+        }
+
+        Ok(expr)
     }
 
     fn parse_call(&mut self) -> ParserExprRes {
@@ -975,6 +1641,23 @@ impl Parser {
                         loc: self.prev().loc.clone(),
                     })
                 }
+                TokenKind::OpenBracket => {
+                    self.eat()?;
+                    self.skip_new_lines();
+
+                    let index = self.parse_expr()?;
+                    self.skip_new_lines();
+
+                    let close_bracket = self
+                        .expect(TokenKind::CloseBracket)
+                        .map_err(|_| self.trigger_error(ParserErr::MissingIndexCloseBracket))?;
+
+                    expr = Expr::Index(IndexExpr {
+                        loc: Loc::span(&expr.get_loc(), &close_bracket.loc),
+                        object: Box::new(expr),
+                        index: Box::new(index),
+                    })
+                }
                 _ => break,
             }
         }
@@ -986,6 +1669,7 @@ impl Parser {
         self.enter_code_block(CodeBlock::FnCallArgs);
 
         let mut args: Vec<Expr> = vec![];
+        let mut arg_names: Vec<Option<Token>> = vec![];
 
         if !self.is_at(TokenKind::CloseParen) {
             loop {
@@ -994,7 +1678,20 @@ impl Parser {
                 }
 
                 self.skip_new_lines();
+
+                // Named argument: name: value
+                let name = if self.is_at(TokenKind::Identifier) && self.next_is(TokenKind::Colon) {
+                    let name = self.eat()?.clone();
+                    let _ = self.eat();
+                    Some(name)
+                } else if matches!(arg_names.last(), Some(Some(_))) {
+                    return Err(self.trigger_error(ParserErr::PositionalAfterNamedArg));
+                } else {
+                    None
+                };
+
                 args.push(self.parse_expr()?);
+                arg_names.push(name);
 
                 if self.is_at(TokenKind::Comma) {
                     let _ = self.eat();
@@ -1011,22 +1708,26 @@ impl Parser {
             }
         }
 
-        // Before eating close paren
-        let loc = self.get_loc_from_prev();
-
         self.expect(TokenKind::CloseParen)
             .map_err(|_| self.trigger_error(ParserErr::MissingCallCloseParen))?;
 
+        let loc = self.get_loc_from(&callee.get_loc());
+
         self.exit_code_block();
 
         Ok(Expr::Call(CallExpr {
             callee: Box::new(callee),
             args,
+            arg_names,
             loc,
         }))
     }
 
     fn parse_primary(&mut self) -> ParserExprRes {
+        if self.is_at_struct_literal() {
+            return self.parse_struct_literal();
+        }
+
         match &self.at().kind {
             TokenKind::Identifier
             | TokenKind::True
@@ -1035,6 +1736,7 @@ impl Parser {
             | TokenKind::FloatType
             | TokenKind::StringType
             | TokenKind::BoolType
+            | TokenKind::CharType
             | TokenKind::Null => Ok(Expr::Identifier(IdentifierExpr {
                 name: self.eat()?.value.clone(),
                 loc: self.prev().loc.clone(),
@@ -1042,14 +1744,18 @@ impl Parser {
             TokenKind::IntLit => self.parse_int_literal(),
             TokenKind::FloatLit => self.parse_float_literal(),
             TokenKind::StringLit => self.parse_str_literal(),
+            TokenKind::CharLit => self.parse_char_literal(),
             TokenKind::OpenParen => self.parse_grouping(),
+            TokenKind::OpenBracket => self.parse_array_literal(),
+            TokenKind::OpenBrace => self.parse_map_literal(),
+            TokenKind::If => self.parse_if_expr(),
             TokenKind::SelfKw => Ok(Expr::Selff(SelfExpr {
                 name: self.eat()?.value.clone(),
                 loc: self.get_loc_from_prev(),
             })),
             TokenKind::NewLine => Err(self.trigger_error(ParserErr::UnexpectedEol)),
             tk => match tk {
-                TokenKind::Star | TokenKind::Plus | TokenKind::Slash | TokenKind::Modulo => {
+                TokenKind::Star | TokenKind::Plus | TokenKind::Slash | TokenKind::TildeSlash | TokenKind::Modulo => {
                     Err(self.trigger_error(ParserErr::MissingLhsInBinop))
                 }
                 _ => Err(self.trigger_error(ParserErr::UnexpectedToken(self.prev().to_string()))),
@@ -1057,6 +1763,58 @@ impl Parser {
         }
     }
 
+    fn parse_if_expr(&mut self) -> ParserExprRes {
+        let if_tk = self.eat()?.clone();
+        self.is_at_brace_or_end_of(ParserErr::IfWithNoCond)?;
+
+        let condition = self.parse_condition()?;
+
+        self.expect_and_skip(TokenKind::OpenBrace)
+            .map_err(|_| self.trigger_error(ParserErr::MissingIfOpenBrace))?;
+
+        let then_expr = self.parse_if_expr_branch()?;
+
+        // The 'else' can be on the next lines, if there is none we
+        // leave the new lines for the enclosing statement
+        let (current, start_loc) = (self.current, self.start_loc.clone());
+        self.skip_new_lines();
+
+        let else_expr = if self.is_at(TokenKind::Else) {
+            self.eat()?;
+            self.skip_new_lines();
+
+            if self.is_at(TokenKind::If) {
+                Some(Box::new(self.parse_if_expr()?))
+            } else {
+                self.expect_and_skip(TokenKind::OpenBrace)
+                    .map_err(|_| self.trigger_error(ParserErr::MissingElseOpenBrace))?;
+
+                Some(Box::new(self.parse_if_expr_branch()?))
+            }
+        } else {
+            self.current = current;
+            self.start_loc = start_loc;
+            None
+        };
+
+        Ok(Expr::If(IfExpr {
+            condition: Box::new(condition),
+            then_expr: Box::new(then_expr),
+            else_expr,
+            loc: Loc::span(&if_tk.loc, &self.prev().loc),
+        }))
+    }
+
+    fn parse_if_expr_branch(&mut self) -> ParserExprRes {
+        let expr = self.parse_expr()?;
+
+        self.skip_new_lines();
+        self.expect(TokenKind::CloseBrace)
+            .map_err(|_| self.trigger_error(ParserErr::MissingIfExprCloseBrace))?;
+
+        Ok(expr)
+    }
+
     fn parse_int_literal(&mut self) -> ParserExprRes {
         let tk = self.eat()?.clone();
         let value = tk
@@ -1086,8 +1844,18 @@ impl Parser {
         }))
     }
 
+    // The lexer ensures the token holds exactly one character
+    fn parse_char_literal(&mut self) -> ParserExprRes {
+        let tk = self.eat()?;
+
+        Ok(Expr::CharLiteral(CharLiteralExpr {
+            value: tk.value.chars().next().unwrap(),
+            loc: tk.loc.clone(),
+        }))
+    }
+
     fn parse_grouping(&mut self) -> ParserExprRes {
-        self.eat()?;
+        let open_paren = self.eat()?.clone();
         let expr = match self.parse_expr() {
             Ok(expr) => expr,
             Err(e) => match e.err {
@@ -1101,16 +1869,134 @@ impl Parser {
             },
         };
 
-        self.expect(TokenKind::CloseParen).map_err(|_| {
+        let close_paren = self.expect(TokenKind::CloseParen).map_err(|_| {
             let err_loc = Loc::new_len_one_from_start(expr.get_loc());
             self.trigger_error_with_loc(ParserErr::ParenNeverClosed, err_loc)
         })?;
 
         Ok(Expr::Grouping(GroupingExpr {
             expr: Box::new(expr),
+            loc: Loc::span(&open_paren.loc, &close_paren.loc),
+        }))
+    }
+
+    fn parse_array_literal(&mut self) -> ParserExprRes {
+        let open_bracket = self.eat()?.clone();
+        self.skip_new_lines();
+
+        let mut values: Vec<Expr> = vec![];
+        while !self.is_at(TokenKind::CloseBracket) && !self.eof() {
+            values.push(self.parse_expr()?);
+
+            self.skip_new_lines();
+
+            if self.is_at(TokenKind::Comma) {
+                self.eat()?;
+                self.skip_new_lines();
+            } else if !self.is_at(TokenKind::CloseBracket) {
+                return Err(self.trigger_error(ParserErr::MissingArrayComma));
+            }
+        }
+
+        let close_bracket = self
+            .expect(TokenKind::CloseBracket)
+            .map_err(|_| self.trigger_error_with_loc(ParserErr::MissingArrayCloseBracket, open_bracket.loc.clone()))?;
+
+        Ok(Expr::ArrayLiteral(ArrayLiteralExpr {
+            values,
+            loc: Loc::span(&open_bracket.loc, &close_bracket.loc),
+        }))
+    }
+
+    fn parse_map_literal(&mut self) -> ParserExprRes {
+        let open_brace = self.eat()?.clone();
+        self.skip_new_lines();
+
+        let mut entries: Vec<(Expr, Expr)> = vec![];
+        while !self.is_at(TokenKind::CloseBrace) && !self.eof() {
+            let key = self.parse_expr()?;
+
+            self.expect(TokenKind::Colon)
+                .map_err(|_| self.trigger_error(ParserErr::MissingMapColon))?;
+            self.skip_new_lines();
+
+            let value = self.parse_expr()?;
+
+            entries.push((key, value));
+
+            self.skip_new_lines();
+
+            if self.is_at(TokenKind::Comma) {
+                self.eat()?;
+                self.skip_new_lines();
+            } else if !self.is_at(TokenKind::CloseBrace) {
+                return Err(self.trigger_error(ParserErr::MissingMapComma));
+            }
+        }
+
+        let close_brace = self
+            .expect(TokenKind::CloseBrace)
+            .map_err(|_| self.trigger_error_with_loc(ParserErr::MissingMapCloseBrace, open_brace.loc.clone()))?;
+
+        Ok(Expr::MapLiteral(MapLiteralExpr {
+            entries,
+            loc: Loc::span(&open_brace.loc, &close_brace.loc),
+        }))
+    }
+
+    fn parse_struct_literal(&mut self) -> ParserExprRes {
+        let name = self.eat()?.clone();
+        let open_brace = self.eat()?.clone();
+        self.skip_new_lines();
+
+        let mut fields: Vec<(Token, Expr)> = vec![];
+        while !self.is_at(TokenKind::CloseBrace) && !self.eof() {
+            let field = self
+                .expect(TokenKind::Identifier)
+                .map_err(|_| self.trigger_error(ParserErr::MissingStructLitFieldName))?;
+
+            self.expect(TokenKind::Colon)
+                .map_err(|_| self.trigger_error(ParserErr::MissingStructLitColon))?;
+            self.skip_new_lines();
+
+            let value = self.parse_expr()?;
+
+            fields.push((field, value));
+
+            self.skip_new_lines();
+
+            if self.is_at(TokenKind::Comma) {
+                self.eat()?;
+                self.skip_new_lines();
+            } else if !self.is_at(TokenKind::CloseBrace) {
+                return Err(self.trigger_error(ParserErr::MissingStructLitComma));
+            }
+        }
+
+        let close_brace = self
+            .expect(TokenKind::CloseBrace)
+            .map_err(|_| self.trigger_error_with_loc(ParserErr::MissingStructLitCloseBrace, open_brace.loc.clone()))?;
+
+        Ok(Expr::StructLiteral(StructLiteralExpr {
+            strukt: Box::new(Expr::Identifier(IdentifierExpr {
+                name: name.value.clone(),
+                loc: name.loc.clone(),
+            })),
+            fields,
+            loc: Loc::span(&name.loc, &close_brace.loc),
         }))
     }
 
+    fn parse_condition(&mut self) -> ParserExprRes {
+        let prev = self.in_condition;
+        self.in_condition = true;
+
+        let expr = self.parse_expr();
+        self.in_condition = prev;
+
+        expr
+    }
+
     fn at(&self) -> &Token {
         self.tokens.get(self.current).unwrap()
     }
@@ -1186,6 +2072,7 @@ impl Parser {
                 | TokenKind::FloatType
                 | TokenKind::StringType
                 | TokenKind::BoolType
+                | TokenKind::CharType
                 | TokenKind::Null
                 | TokenKind::AnyType
                 | TokenKind::VoidType
@@ -1193,10 +2080,31 @@ impl Parser {
         )
     }
 
+    fn is_at_type_start(&self) -> bool {
+        self.is_at_type() || self.is_at(TokenKind::OpenBracket) || self.is_at(TokenKind::OpenBrace)
+    }
+
     fn prev(&self) -> &Token {
         self.tokens.get(self.current - 1).unwrap()
     }
 
+    // Foo { a: ... } or Foo {}
+    fn is_at_struct_literal(&self) -> bool {
+        if !self.is_at(TokenKind::Identifier) || !self.next_is(TokenKind::OpenBrace) {
+            return false;
+        }
+
+        let mut tokens = self.tokens[self.current + 2..]
+            .iter()
+            .filter(|t| t.kind != TokenKind::NewLine);
+
+        match tokens.next().map(|t| &t.kind) {
+            Some(TokenKind::CloseBrace) => !self.in_condition,
+            Some(TokenKind::Identifier) => tokens.next().map(|t| &t.kind) == Some(&TokenKind::Colon),
+            _ => false,
+        }
+    }
+
     fn next_is(&self, kind: TokenKind) -> bool {
         let next = self.tokens.get(self.current + 1);
 
@@ -1217,7 +2125,7 @@ impl Parser {
             self.current += 1;
         }
 
-        self.start_loc = self.at().loc.start;
+        self.start_loc = self.at().loc.clone();
     }
 
     fn is_at_brace_or_end_of(&mut self, err: ParserErr) -> Result<(), RizonResParser> {
@@ -1261,8 +2169,21 @@ impl Parser {
     // var = 8
     //    ^
     fn trigger_error_before_cur_len_one(&mut self, err: ParserErr) -> RizonResParser {
-        let prev_start = self.at().loc.start;
-        let prev_loc = Loc::new(prev_start - 1, prev_start - 1);
+        let at = &self.at().loc;
+
+        // Characters between two tokens are ASCII spaces, otherwise the
+        // previous one is the last character of the previous token
+        let prev_byte = match self.current.checked_sub(1).map(|i| &self.tokens[i].loc) {
+            Some(prev) if prev.end + 1 == at.start => prev.byte_end,
+            _ => at.byte_start - 1,
+        };
+
+        let prev_loc = Loc {
+            start: at.start - 1,
+            end: at.start - 1,
+            byte_start: prev_byte,
+            byte_end: prev_byte,
+        };
 
         self.trigger_error_with_loc(err, prev_loc)
     }
@@ -1347,32 +2268,11 @@ impl Parser {
 
                     self.exit_code_block();
                 }
-                Some(&CodeBlock::FnDeclBody) => {
-                    let mut nb_blocks = 0;
-
-                    while !self.eof() {
-                        match self.at().kind {
-                            TokenKind::CloseBrace => {
-                                if nb_blocks == 0 {
-                                    break;
-                                } else {
-                                    let _ = self.eat();
-
-                                    nb_blocks -= 1;
-                                }
-                            }
-                            TokenKind::OpenBrace => {
-                                let _ = self.eat();
-                                nb_blocks += 1;
-                            }
-                            _ => {
-                                let _ = self.eat();
-                            }
-                        }
-                    }
-
-                    self.exit_code_block();
-                }
+                // The body block is entered right after and its synchronization
+                // already skipped the whole body
+                Some(&CodeBlock::FnDeclBody) => self.exit_code_block(),
+                // The enclosing match block skips the remaining arms
+                Some(&CodeBlock::MatchArm) => self.exit_code_block(),
                 Some(&CodeBlock::FnCallArgs) => {
                     let mut nb_parens = 0;
 
@@ -1398,7 +2298,7 @@ impl Parser {
 
                     self.exit_code_block();
                 }
-                Some(&CodeBlock::Struct) => {
+                Some(&CodeBlock::Struct) | Some(&CodeBlock::Enum) => {
                     let mut nb_blocks = 0;
 
                     while !self.eof() {
@@ -1461,10 +2361,280 @@ impl Parser {
     }
 
     fn get_loc(&self) -> Loc {
-        Loc::new(self.start_loc, self.at().loc.start)
+        Loc::span(&self.start_loc, &Loc::new_len_one_from_start(self.at().loc.clone()))
     }
 
     fn get_loc_from_prev(&self) -> Loc {
-        Loc::new(self.start_loc, self.prev().loc.end)
+        Loc::span(&self.start_loc, &self.prev().loc)
+    }
+
+    // From the given start to the end of the last eaten token
+    fn get_loc_from(&self, start: &Loc) -> Loc {
+        Loc::span(start, &self.prev().loc)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{expr::Expr, stmt::Stmt},
+        lexer::Lexer,
+    };
+
+    use super::Parser;
+
+    // Source text covered by the location of the expression statement
+    fn expr_span(code: &str) -> String {
+        let tokens = Lexer::new().tokenize(code).unwrap();
+        let stmts = Parser::default().parse(tokens).unwrap();
+
+        let Some(Stmt::Expr(s)) = stmts.first() else {
+            panic!("expected an expression statement");
+        };
+
+        let loc = s.expr.get_loc();
+        code[loc.start..=loc.end].to_string()
+    }
+
+    #[test]
+    fn expressions_span_their_whole_source() {
+        let exprs = [
+            "-a",
+            "a.b",
+            "a.b.c(1, 2)",
+            "f(1)(2)",
+            "f(1, b: 2)",
+            "Point { x: 1, y: 2 }",
+            "a[0][1]",
+            "a and b or c",
+            "a ?? 0",
+            "x is int",
+            "1 as float",
+            "a = b = 5",
+            "a.b = 1 + 2",
+            "a[0] = 1",
+            "a += 1",
+            "a + b * c",
+        ];
+
+        for e in exprs {
+            assert_eq!(expr_span(e), e);
+        }
+    }
+
+    #[test]
+    fn expressions_byte_ranges_span_their_whole_source() {
+        let exprs = ["café.λ", "-é", "( \"€\" )", "f(\"λ\", b: é)", "[é][0] + ü"];
+
+        for code in exprs {
+            let tokens = Lexer::new().tokenize(code).unwrap();
+            let stmts = Parser::default().parse(tokens).unwrap();
+
+            let Some(Stmt::Expr(s)) = stmts.first() else {
+                panic!("expected an expression statement");
+            };
+
+            assert_eq!(&code[s.expr.get_loc().byte_range(code)], code);
+        }
+    }
+
+    #[test]
+    fn expressions_display_as_source() {
+        let exprs = [
+            "-a + (b - 1.5)",
+            "a.b.c(1, \"s\")",
+            "f(a, b: 1, c: [2])",
+            "p = Point { x: 1, y: a + 2 }",
+            "e = Empty {}",
+            "a[0] = [1, 2]",
+            "m = {\"a\": 1}",
+            "x is int and !y",
+            "v = if a { 1 } else if b { 2 } else { 3 }",
+        ];
+
+        for e in exprs {
+            let tokens = Lexer::new().tokenize(e).unwrap();
+            let stmts = Parser::default().parse(tokens).unwrap();
+
+            let Some(Stmt::Expr(s)) = stmts.first() else {
+                panic!("expected an expression statement");
+            };
+
+            assert_eq!(s.expr.to_string(), e);
+        }
+    }
+
+    #[test]
+    fn range_bounds_are_expressions() {
+        let tokens = Lexer::new().tokenize("r = a - 1..=b * 2").unwrap();
+        let stmts = Parser::default().parse(tokens).unwrap();
+
+        let Some(Stmt::Expr(s)) = stmts.first() else {
+            panic!("expected an expression statement");
+        };
+
+        let Expr::Assign(a) = &s.expr else {
+            panic!("expected an assignment, found {:?}", s.expr);
+        };
+
+        match &*a.value {
+            Expr::Range(r) => {
+                assert!(r.inclusive);
+                assert_eq!(r.start.to_string(), "a - 1");
+                assert_eq!(r.end.to_string(), "b * 2");
+            }
+            e => panic!("expected a range, found {:?}", e),
+        }
+    }
+
+    #[test]
+    fn range_step() {
+        let tokens = Lexer::new().tokenize("for i in 10..0:-2 {}").unwrap();
+        let stmts = Parser::default().parse(tokens).unwrap();
+
+        let Some(Stmt::For(f)) = stmts.first() else {
+            panic!("expected a for loop, found {:?}", stmts);
+        };
+
+        assert_eq!(f.iterable.to_string(), "10..0:-2");
+        assert_eq!(parse_errors("r = 0..10:"), ["missing step of range after ':'"]);
+        assert_eq!(parse_errors("for i in 10..0 {}"), ["end of range smaller than start"]);
+    }
+
+    #[test]
+    fn annotations_are_attached_to_declarations() {
+        let code = "@inline\nfn f() {}\n@derive(eq) @other(a, b)\nstruct S { fn g() {} }";
+        let tokens = Lexer::new().tokenize(code).unwrap();
+        let stmts = Parser::default().parse(tokens).unwrap();
+
+        let [Stmt::FnDecl(f), Stmt::Struct(s)] = stmts.as_slice() else {
+            panic!("expected a function and a structure, found {:?}", stmts);
+        };
+
+        assert_eq!(f.annotations[0].name.value, "inline");
+        assert!(f.annotations[0].args.is_empty());
+
+        let names: Vec<&str> = s.annotations.iter().map(|a| a.name.value.as_str()).collect();
+        let args: Vec<&str> = s.annotations[1].args.iter().map(|a| a.value.as_str()).collect();
+
+        assert_eq!(names, ["derive", "other"]);
+        assert_eq!(args, ["a", "b"]);
+        assert!(s.methods[0].annotations.is_empty());
+    }
+
+    #[test]
+    fn annotation_errors() {
+        assert_eq!(parse_errors("@ fn f() {}"), ["missing annotation name after '@'"]);
+        assert_eq!(parse_errors("@derive(1) struct S {}"), ["annotation arguments must be identifiers"]);
+        assert_eq!(
+            parse_errors("@inline\nvar a = 1"),
+            ["annotations must be followed by a function or structure declaration"]
+        );
+    }
+
+    #[test]
+    fn empty_braces_after_condition_are_a_block() {
+        let tokens = Lexer::new().tokenize("if a {}\nwhile b {}\nvar e = Empty {}").unwrap();
+        let stmts = Parser::default().parse(tokens).unwrap();
+
+        assert!(matches!(&stmts[0], Stmt::If(s) if matches!(&s.condition, Expr::Identifier(_))));
+        assert!(matches!(&stmts[1], Stmt::While(s) if matches!(&s.condition, Expr::Identifier(_))));
+        assert!(matches!(&stmts[2], Stmt::VarDecl(v) if matches!(&v.value, Some(Expr::StructLiteral(_)))));
+    }
+
+    #[test]
+    fn named_args_follow_positional_ones() {
+        assert_eq!(parse_errors("f(a: 1, 2)"), ["positional arguments can't follow named arguments"]);
+    }
+
+    #[test]
+    fn increment_is_a_statement_on_assignable_targets() {
+        assert!(parse_errors("a++\na.b--\na[0]++").is_empty());
+        assert_eq!(parse_errors("(a)++"), ["invalid assignment target"]);
+        assert_eq!(parse_errors("f()--"), ["invalid assignment target"]);
+    }
+
+    fn parse_errors(code: &str) -> Vec<String> {
+        let tokens = Lexer::new().tokenize(code).unwrap();
+
+        match Parser::default().parse(tokens) {
+            Ok(_) => vec![],
+            Err(errs) => errs.iter().map(|e| e.err.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn reports_independent_errors() {
+        let code = "
+var = 1
+print 2
+var y: int =
+print 3";
+
+        assert_eq!(
+            parse_errors(code),
+            [
+                "missing variable name after 'var' keyword in declaration",
+                "expected expression for variable assignment",
+            ]
+        );
+    }
+
+    #[test]
+    fn resumes_after_error_in_function_body() {
+        let code = "
+fn f() {
+    if true {
+        var = 1
+    }
+    print 2
+}
+struct S { x int }
+fn g() -> int { return 1 }
+var z =
+print 3";
+
+        assert_eq!(
+            parse_errors(code),
+            [
+                "missing variable name after 'var' keyword in declaration",
+                "missing ':' to declare type",
+                "expected expression for variable assignment",
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_underline_their_span() {
+        let code = "var a = 1\nwhile a > 0 print a";
+        let errors = Parser::default().parse(Lexer::new().tokenize(code).unwrap()).unwrap_err();
+
+        let snippet = errors[0].snippet(code).unwrap();
+        let lines: Vec<&str> = snippet.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("var a = 1"));
+        assert!(lines[1].ends_with("while a > 0 print a"));
+        assert_eq!(lines[2].trim(), "^");
+        assert_eq!(lines[2].find('^'), lines[1].find("print"));
+    }
+
+    #[test]
+    fn chained_comparisons_keep_each_operand_once() {
+        let tokens = Lexer::new().tokenize("0 < a <= 10").unwrap();
+        let stmts = Parser::default().parse(tokens).unwrap();
+
+        let Some(Stmt::Expr(s)) = stmts.first() else {
+            panic!("expected an expression statement");
+        };
+
+        let Expr::ChainedCompare(chain) = &s.expr else {
+            panic!("expected a chained comparison, found: {:?}", s.expr);
+        };
+
+        assert_eq!(chain.operands.len(), 3);
+        assert_eq!(chain.operators.iter().map(|o| o.value.as_str()).collect::<Vec<_>>(), ["<", "<="]);
+        assert_eq!(s.expr.to_string(), "0 < a <= 10");
     }
 }
@@ -7,9 +7,13 @@ use std::{
 };
 use colored::*;
 
-use rizon_frontend::{lexer::Lexer, parser::Parser};
-use rizon_static_analyzer::StaticAnalyzer;
-use rizon_runtime::{interpreter::Interpreter, values::RtVal};
+use rizon_frontend::{fold::fold_constants, lexer::Lexer, normalize::normalize, parser::Parser};
+use rizon_static_analyzer::{typed_ast::to_json, StaticAnalyzer};
+use rizon_runtime::{
+    interpreter::{Interpreter, ModuloMode},
+    repl::Repl,
+    values::RtVal,
+};
 
 // --------
 //   Cli
@@ -34,25 +38,64 @@ struct Cli {
     // Static analysis
     #[arg(short, long)]
     static_analyse: bool,
+
+    /// Prints the resolved tree with the inferred types as JSON
+    #[arg(long)]
+    typed_ast: bool,
+
+    /// Floored modulo like in Python, the result has the sign of the divisor: -7 % 3 == 2, 7 % -3 == -2
+    #[arg(long)]
+    floored_modulo: bool,
+
+    /// Release mode, 'debug_assert' calls are skipped
+    #[arg(long)]
+    release: bool,
+
+    /// Maximum number of statements executed, no limit by default
+    #[arg(long)]
+    max_steps: Option<u64>,
+
+    /// Activates a feature for '#if' blocks, can be repeated
+    #[arg(long = "feature", value_name = "FEATURE")]
+    features: Vec<String>,
 }
 
-struct Repl {
+struct Runner {
     cli: Cli,
     static_analyzer: StaticAnalyzer,
     interpreter: Interpreter,
 }
 
 fn main() {
-    let mut repl = Repl {
-        cli: Cli::parse(),
-        static_analyzer: StaticAnalyzer::default(),
-        interpreter: Interpreter::new(),
+    let cli = Cli::parse();
+    let interpreter = new_interpreter(&cli);
+
+    let mut static_analyzer = StaticAnalyzer::default();
+    static_analyzer.set_record_types(cli.typed_ast);
+
+    let mut runner = Runner {
+        cli,
+        static_analyzer,
+        interpreter,
     };
 
-    repl.run();
+    runner.run();
+}
+
+fn new_interpreter(cli: &Cli) -> Interpreter {
+    let mut interpreter = Interpreter::new();
+
+    if cli.floored_modulo {
+        interpreter.set_modulo_mode(ModuloMode::Floored);
+    }
+
+    interpreter.set_release_mode(cli.release);
+    interpreter.set_max_steps(cli.max_steps);
+
+    interpreter
 }
 
-impl Repl {
+impl Runner {
     pub fn run(&mut self) {
         let _ = match self.cli.file.as_str() {
             "placeholder.rz" => self.run_repl(),
@@ -72,6 +115,9 @@ impl Repl {
         let mut stdout = io::stdout();
         let mut input = String::new();
 
+        let mut repl = Repl::new(new_interpreter(&self.cli));
+        repl.set_features(self.cli.features.iter().map(|f| f.into()).collect());
+
         println!("\n  {}", "Rizon language interpreter v0.0\n".yellow());
 
         loop {
@@ -90,13 +136,17 @@ impl Repl {
                 continue;
             }
 
-            // Execute interpreter
-            self.sequence(trimmed_input.to_string());
+            let output = repl.feed(trimmed_input);
+
+            if output.value.is_some() || !output.reports.is_empty() {
+                println!("{}", output);
+            }
         }
     }
 
     fn sequence(&mut self, code: String) {
         let mut lexer = Lexer::new();
+        lexer.set_features(self.cli.features.iter().map(|f| f.into()).collect());
         let mut parser = Parser::default();
 
         let tokens = match lexer.tokenize(&code) {
@@ -114,7 +164,7 @@ impl Repl {
         }
 
         let nodes = match parser.parse(tokens) {
-            Ok(n) => n,
+            Ok(n) => normalize(n),
             Err(e) => {
                 e.iter()
                     .for_each(|e| e.report(&self.cli.file, &code));
@@ -123,7 +173,14 @@ impl Repl {
             }
         };
 
-        let locals = match self.static_analyzer.resolve(&nodes) {
+        let resolved = self.static_analyzer.resolve(&nodes);
+
+        self.static_analyzer
+            .take_warnings()
+            .iter()
+            .for_each(|w| w.report(&self.cli.file, &code));
+
+        let locals = match resolved {
             Ok(l) => l,
             Err(e) => {
                 e.iter()
@@ -133,7 +190,17 @@ impl Repl {
             }
         };
 
+        self.interpreter.widened.extend(self.static_analyzer.take_widened());
+
+        if let Some(types) = self.static_analyzer.expr_types() {
+            println!("{}", to_json(&nodes, types));
+        }
+
         if !self.cli.static_analyse {
+            let nodes = fold_constants(nodes);
+
+            self.interpreter.set_source(&code);
+
             match self.interpreter.interpret(&nodes, locals) {
                 Ok(res) => {
                     if *res.borrow() != RtVal::Null {
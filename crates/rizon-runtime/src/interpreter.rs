@@ -1,28 +1,34 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::time::{SystemTime, UNIX_EPOCH};
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
 
 use colored::Colorize;
 use ecow::EcoString;
-use rizon_frontend::ast::expr::{GetExpr, SelfExpr, SetExpr};
+use rizon_frontend::ast::expr::{
+    ArrayLiteralExpr, CastExpr, Expr, GetExpr, IfExpr, IndexExpr, IndexSetExpr, MapLiteralExpr, RangeExpr, SelfExpr,
+    SetExpr, StructLiteralExpr,
+};
 use thiserror::Error;
-use rizon_tools::results::{RizonReport, RizonResult, Loc};
+use rizon_tools::results::{LineIndex, RizonReport, RizonResult, Loc, TraceFrame};
 
-use crate::callable::Callable;
+use crate::callable::{CallErr, Callable};
 use crate::environment::Env;
-use crate::native_functions::RizonNativeFn;
-use crate::values::{Function, RtVal, Negate};
+use crate::native_functions::{
+    collection_functions, conversion_functions, debug_functions, io_functions, math_functions, string_functions,
+    RizonNativeFn,
+};
+use crate::values::{Function, Instance, RtVal, RtValErr, Negate, Struct};
 use crate::native_functions::NativeFnErr;
 use rizon_frontend::ast::{expr::{
-    AssignExpr, BinaryExpr, CallExpr, GroupingExpr, IdentifierExpr, IntLiteralExpr, LogicalExpr,
-    FloatLiteralExpr, StrLiteralExpr, UnaryExpr, VisitExpr, IsExpr,
-}, stmt::StructStmt};
+    AssignExpr, BinaryExpr, CallExpr, ChainedCompareExpr, GroupingExpr, IdentifierExpr, IntLiteralExpr, LogicalExpr,
+    FloatLiteralExpr, StrLiteralExpr, CharLiteralExpr, UnaryExpr, VisitExpr, IsExpr,
+}, stmt::{EnumDeclStmt, MatchPattern, MatchStmt, StructStmt, TypeAliasStmt}};
 use rizon_frontend::ast::stmt::{
-    BlockStmt, ExprStmt, FnDeclStmt, ForStmt, IfStmt, PrintStmt, ReturnStmt, Stmt, VarDeclStmt, VarTypeDecl, VisitStmt, WhileStmt
+    BlockStmt, BreakStmt, ContinueStmt, DoWhileStmt, ExprStmt, FnDeclStmt, ForStmt, IfStmt, PrintStmt, ReturnStmt, Stmt, VarDeclStmt, VarTypeDecl, VisitStmt, WhileStmt
 };
-use rizon_frontend::lexer::TokenKind;
+use rizon_frontend::lexer::{Token, TokenKind};
 
 
 // ----------------
@@ -80,6 +86,22 @@ pub enum InterpErr {
     #[error("{0}")]
     FnCall(String),
 
+    #[error("no function named '{0}'")]
+    UnknownFn(String),
+
+    #[error("argument {0} doesn't match the parameter type '{1}'")]
+    WrongArgType(usize, String),
+
+    #[error("assertion failed{}", .0.as_ref().map(|m| format!(": {}", m)).unwrap_or_default())]
+    AssertionFailed(Option<EcoString>),
+
+    // Execution limits
+    #[error("execution step limit of {0} reached")]
+    StepLimitReached(u64),
+
+    #[error("maximum call depth of {0} reached")]
+    MaxCallDepth(usize),
+
     // Property access
     // TODO: Remove, already done in static analysis
     #[error("only structure instances have properties")]
@@ -92,9 +114,40 @@ pub enum InterpErr {
     #[error("{0}")]
     InexistantFieldBis(String),
 
+    // TODO: Remove, already done in static analysis
+    #[error("only structures can be initialized with a literal")]
+    NonStructLiteral,
+
+    // Index access
+    #[error("only arrays and maps can be indexed")]
+    NonIndexable,
+
+    #[error("array index must be an int")]
+    NonIntIndex,
+
+    // Range
+    #[error("range bounds must be ints")]
+    NonIntRangeBound,
+
+    #[error("range step can't be zero")]
+    ZeroRangeStep,
+
+    #[error("{0}")]
+    IndexAccess(String),
+
     // Results
     #[error("return")]
     Return(Rc<RefCell<RtVal>>),
+
+    #[error("break")]
+    Break,
+
+    #[error("continue")]
+    Continue,
+
+    // Boxed, all the errors would be as big otherwise
+    #[error("tail call")]
+    TailCall(Box<(Function, Vec<Rc<RefCell<RtVal>>>)>),
 }
 
 impl RizonReport for InterpErr {
@@ -103,6 +156,17 @@ impl RizonReport for InterpErr {
     }
 }
 
+// Semantic of the '%' operator, the same for ints and floats. A float
+// modulo by zero gives 'nan' instead of an error
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum ModuloMode {
+    // Sign follows the dividend: -7 % 3 == -1, -3.5 % 2.0 == -1.5
+    #[default]
+    Truncated,
+    // Sign follows the divisor like in Python: -7 % 3 == 2, 7 % -3 == -2
+    Floored,
+}
+
 pub(crate) type RizonResInterp = RizonResult<InterpErr>;
 pub(crate) type InterpRes = Result<Rc<RefCell<RtVal>>, RizonResInterp>;
 
@@ -113,8 +177,35 @@ pub struct Interpreter {
     pub globals: Rc<RefCell<Env>>,
     pub env: Rc<RefCell<Env>>,
     pub locals: HashMap<Loc, usize>,
+    // Int values stored as floats in arrays and maps, given by the static analyzer
+    pub widened: HashSet<Loc>,
+    modulo_mode: ModuloMode,
+    // Lines of the interpreted code, for the locations printed by 'dbg'
+    lines: LineIndex,
+    // In release mode, 'debug_assert' calls are skipped
+    release_mode: bool,
+    // Type aliases are only declared at top level
+    aliases: HashMap<EcoString, VarTypeDecl>,
+    // Source of 'input' and 'read_line', can be replaced for tests. The
+    // process stdin isn't locked here, the REPL reads from it too
+    stdin: Option<Box<dyn BufRead>>,
+    // Statements executed during the current 'interpret' call. All calls,
+    // including the callbacks of natives, go through the same checks
+    steps: u64,
+    max_steps: Option<u64>,
+    call_depth: usize,
+    max_call_depth: usize,
+    // Calls being executed, outermost first, and if they are tail calls.
+    // Those run in their caller's frame, only the last one of a chain is kept
+    call_stack: Vec<(TraceFrame, bool)>,
+    // Stack when the error being propagated left its innermost call
+    error_trace: Option<Vec<TraceFrame>>,
 }
 
+// Deep enough for usual recursions, low enough to report an error
+// before the native stack overflows
+const DEFAULT_MAX_CALL_DEPTH: usize = 512;
+
 impl Interpreter {
     pub fn new() -> Self {
         let globals = Rc::new(RefCell::new(Env::new(None)));
@@ -139,13 +230,11 @@ impl Interpreter {
             Rc::new(RefCell::new(RtVal::NativeFnVal(RizonNativeFn {
                 name: EcoString::from("input"),
                 arity: 1,
-                func: |_, args| {
+                func: |interp, args| {
                     println!("{}", args[0].borrow());
                     std::io::stdout().flush().unwrap();
 
-                    let mut input = String::new();
-                    io::stdin().read_line(&mut input).expect("Failed to read line");
-
+                    let input = interp.read_line()?;
                     let input = input.trim();
 
                     Ok(RtVal::new_str(input.into()).into())
@@ -153,24 +242,196 @@ impl Interpreter {
             }))),
         );
 
+        let natives = math_functions()
+            .into_iter()
+            .chain(string_functions())
+            .chain(conversion_functions())
+            .chain(io_functions())
+            .chain(collection_functions())
+            .chain(debug_functions());
+
+        for f in natives {
+            let _ = globals
+                .borrow_mut()
+                .declare_var(f.name.clone(), Rc::new(RefCell::new(RtVal::NativeFnVal(f))));
+        }
+
         let env = globals.clone();
 
         Self {
             globals,
             env,
             locals: HashMap::new(),
+            widened: HashSet::new(),
+            modulo_mode: ModuloMode::default(),
+            lines: LineIndex::default(),
+            release_mode: false,
+            aliases: HashMap::new(),
+            stdin: None,
+            steps: 0,
+            max_steps: None,
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            call_stack: vec![],
+            error_trace: None,
+        }
+    }
+
+    pub fn set_modulo_mode(&mut self, mode: ModuloMode) {
+        self.modulo_mode = mode;
+    }
+
+    // Code the nodes come from, the locations are offsets in it
+    pub fn set_source(&mut self, code: &str) {
+        self.lines = LineIndex::new(code);
+    }
+
+    pub fn set_release_mode(&mut self, release: bool) {
+        self.release_mode = release;
+    }
+
+    // No limit by default
+    pub fn set_max_steps(&mut self, max_steps: Option<u64>) {
+        self.max_steps = max_steps;
+    }
+
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    pub(crate) fn enter_call(&mut self) -> Result<(), InterpErr> {
+        if self.call_depth >= self.max_call_depth {
+            return Err(InterpErr::MaxCallDepth(self.max_call_depth));
+        }
+
+        self.call_depth += 1;
+
+        Ok(())
+    }
+
+    pub(crate) fn exit_call(&mut self) {
+        self.call_depth -= 1;
+    }
+
+    fn step(&mut self, loc: &Loc) -> Result<(), RizonResInterp> {
+        self.steps += 1;
+
+        match self.max_steps {
+            Some(max) if self.steps > max => Err(RizonResult::new(InterpErr::StepLimitReached(max), Some(loc.clone()))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn set_stdin(&mut self, stdin: Box<dyn BufRead>) {
+        self.stdin = Some(stdin);
+    }
+
+    // Reads a line from the input without the trailing new line
+    pub(crate) fn read_line(&mut self) -> Result<String, RizonResult<CallErr>> {
+        let mut line = String::new();
+
+        let res = match &mut self.stdin {
+            Some(stdin) => stdin.read_line(&mut line),
+            None => io::stdin().read_line(&mut line),
+        };
+
+        match res {
+            Ok(0) => Err(RizonResult::new(NativeFnErr::EndOfInput.into(), None)),
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    line.pop();
+
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+
+                Ok(line)
+            }
+            Err(e) => Err(RizonResult::new(NativeFnErr::ReadInput(e.to_string()).into(), None)),
+        }
+    }
+
+    // Calls a function declared at top level from the host. Arguments
+    // are checked against the parameters types
+    pub fn call_function(&mut self, name: &str, args: Vec<RtVal>) -> Result<RtVal, RizonResult<InterpErr>> {
+        let callee = self
+            .globals
+            .borrow()
+            .get_var(name.into())
+            .map_err(|_| RizonResult::new(InterpErr::UnknownFn(name.into()), None))?;
+
+        let func = match &*callee.borrow() {
+            RtVal::FuncVal(f) => f.clone(),
+            _ => return Err(RizonResult::new(InterpErr::UnknownFn(name.into()), None)),
+        };
+
+        if func.arity() != args.len() {
+            return Err(RizonResult::new(
+                InterpErr::WrongArgsNb(func.arity(), args.len()),
+                None,
+            ));
+        }
+
+        let mut call_args: Vec<Rc<RefCell<RtVal>>> = vec![];
+        for (i, (arg, typ)) in args.into_iter().zip(func.param_types.iter()).enumerate() {
+            let arg = match (arg, self.resolve_alias(typ)) {
+                // Same int to float cast than in the language
+                (RtVal::IntVal(v), VarTypeDecl::Identifier(t)) if t.kind == TokenKind::FloatType => {
+                    RtVal::new_float(v.value as f64)
+                }
+                (arg, typ) if self.value_has_type(&arg, typ) => arg,
+                (_, typ) => {
+                    return Err(RizonResult::new(
+                        InterpErr::WrongArgType(i + 1, typ.to_string()),
+                        None,
+                    ))
+                }
+            };
+
+            call_args.push(arg.into());
         }
+
+        let res = func
+            .call(self, call_args)
+            .map_err(|e| RizonResult::new(InterpErr::FnCall(e.err.to_string()), None))?;
+
+        let val = res.borrow().clone();
+
+        Ok(val)
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Interpreter {
-    pub fn interpret(&mut self, nodes: &Vec<Stmt>, locals: HashMap<Loc, usize>) -> InterpRes {
+    // Errors raised in a function carry the calls leading to it
+    pub fn interpret(&mut self, nodes: &[Stmt], locals: HashMap<Loc, usize>) -> InterpRes {
         self.locals = locals;
+        self.steps = 0;
+        self.error_trace = None;
+
+        self.interpret_nodes(nodes)
+            .map_err(|e| e.with_trace(self.error_trace.take().unwrap_or_default()))
+    }
 
+    fn interpret_nodes(&mut self, nodes: &[Stmt]) -> InterpRes {
         let mut res = RtVal::new_null();
 
-        for node in nodes {
-            match node.accept(self) {
+        // Top level structures and functions are declared first, they can
+        // be used before their declaration
+        let hoisted = |n: &&Stmt| matches!(n, Stmt::Struct(_) | Stmt::FnDecl(_));
+
+        for node in nodes.iter().filter(hoisted) {
+            node.accept(self)?;
+        }
+
+        for node in nodes.iter().filter(|n| !hoisted(n)) {
+            match self.step(&node.get_loc()).and_then(|_| node.accept(self)) {
                 Ok(r) => res = r,
                 Err(e) => return Err(e),
             }
@@ -186,8 +447,12 @@ impl VisitStmt<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
     }
 
     fn visit_print_stmt(&mut self, stmt: &PrintStmt) -> InterpRes {
-        let value = stmt.expr.accept(self)?;
-        println!("{}", value.borrow());
+        let mut values: Vec<String> = vec![];
+        for e in &stmt.exprs {
+            values.push(e.accept(self)?.borrow().to_string());
+        }
+
+        println!("{}", values.join(" "));
 
         Ok(RtVal::new_null())
     }
@@ -199,19 +464,8 @@ impl VisitStmt<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
 
         // We check for the case where we init a float with a real to be sure
         // to keep the 'float' information: var a: float = 1 + 2
-        if let Some(VarTypeDecl::Identifier(i)) = &stmt.typ {
-            if i.kind == TokenKind::FloatType {
-                let mut val = None;
-
-                if let RtVal::IntVal(v) = &*value.borrow() {
-                    val = Some(v.value);
-                }
-
-                if let Some(i) = val {
-                    value = RtVal::new_float(i as f64).into();
-                }
-            }
-            
+        if let Some(t) = &stmt.typ {
+            value = self.coerce_to_type(value, t);
         }
 
         self.env
@@ -267,7 +521,9 @@ impl VisitStmt<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
             match tmp {
                 RtVal::BoolVal(b) => match b.value {
                     true => {
-                        stmt.body.accept(self)?;
+                        if !self.execute_loop_body(&stmt.body)? {
+                            break
+                        }
                     }
                     false => break,
                 },
@@ -283,31 +539,76 @@ impl VisitStmt<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
         Ok(RtVal::new_null())
     }
 
+    fn visit_do_while_stmt(&mut self, stmt: &DoWhileStmt) -> InterpRes {
+        while self.execute_loop_body(&stmt.body)? {
+            let cond = stmt.condition.accept(self)?;
+            let tmp = &*cond.borrow();
+
+            match tmp {
+                RtVal::BoolVal(b) if b.value => {}
+                RtVal::BoolVal(_) => break,
+                _ => {
+                    return Err(RizonResult::new(
+                        InterpErr::NonBoolWhileCond,
+                        Some(stmt.loc.clone()),
+                    ))
+                }
+            }
+        }
+
+        Ok(RtVal::new_null())
+    }
+
     fn visit_for_stmt(&mut self, stmt: &ForStmt) -> InterpRes {
+        // Evaluated in the enclosing scope. Arrays and maps are iterated over a
+        // copy of their elements or keys, modifying them in the body doesn't change it
+        let iterable = stmt.iterable.accept(self)?;
+
+        let values: Box<dyn Iterator<Item = RtVal>> = match &*iterable.borrow() {
+            RtVal::RangeVal(r) => Box::new(r.values().map(RtVal::new_int)),
+            RtVal::ArrayVal(a) => {
+                Box::new(a.values.iter().map(|v| v.borrow().clone()).collect::<Vec<_>>().into_iter())
+            }
+            RtVal::MapVal(m) => {
+                Box::new(m.entries.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>().into_iter())
+            }
+            v => {
+                return Err(RizonResult::new(
+                    InterpErr::ForLoop(format!("value '{}' can't be iterated over", v)),
+                    Some(stmt.iterable.get_loc()),
+                ))
+            }
+        };
+
         let new_env = Env::new(Some(self.env.clone()));
         let prev_env = std::mem::replace(&mut self.env, Rc::new(RefCell::new(new_env)));
 
         self.visit_var_decl_stmt(&stmt.placeholder)?;
-        let mut range = 0..stmt.range.start;
 
-        if let Some(i) = stmt.range.end {
-            range = stmt.range.start..i;
-        }
+        // The loop environment is left on any exit
+        let mut res = Ok(RtVal::new_null());
 
-        for i in range {
-            self.env
+        for value in values {
+            let assigned = self.env
                 .borrow_mut()
-                .assign(stmt.placeholder.name.value.clone(), RtVal::new_int(i).into())
+                .assign(stmt.placeholder.name.value.clone(), value.into())
                 .map_err(|e| {
                     RizonResult::new(InterpErr::ForLoop(e.to_string()), Some(stmt.loc.clone()))
-                })?;
-
-            stmt.body.accept(self)?;
+                });
+
+            match assigned.and_then(|_| self.execute_loop_body(&stmt.body)) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    res = Err(e);
+                    break
+                }
+            }
         }
 
         let _ = std::mem::replace(&mut self.env, prev_env);
 
-        Ok(RtVal::new_null())
+        res
     }
 
     fn visit_fn_decl_stmt(&mut self, stmt: &FnDeclStmt) -> InterpRes {
@@ -326,11 +627,24 @@ impl VisitStmt<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
         Ok(RtVal::new_null())
     }
 
+    fn visit_break_stmt(&mut self, _: &BreakStmt) -> InterpRes {
+        Err(RizonResult::new(InterpErr::Break, None))
+    }
+
+    fn visit_continue_stmt(&mut self, _: &ContinueStmt) -> InterpRes {
+        Err(RizonResult::new(InterpErr::Continue, None))
+    }
+
     fn visit_return_stmt(&mut self, stmt: &ReturnStmt) -> InterpRes {
         let mut value = RtVal::new_null();
 
-        if let Some(v) = &stmt.value {
-            value = v.accept(self)?;
+        match &stmt.value {
+            Some(Expr::Call(call)) => {
+                let callee = call.callee.accept(self)?;
+                value = self.call(call, callee, true)?;
+            }
+            Some(v) => value = v.accept(self)?,
+            None => {}
         }
 
         Err(RizonResult::new(InterpErr::Return(value), None))
@@ -354,21 +668,25 @@ impl VisitStmt<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
                 )
             })?;
 
-        let mut fields: HashMap<EcoString, Rc<RefCell<RtVal>>> = HashMap::new();
-        for f in &stmt.fields {
-            let value = match &f.value {
-                Some(v) => v.accept(self)?,
-                None => RtVal::new_null(),
-            };
+        let struct_val = RtVal::new_struct(stmt, methods, self.env.clone());
 
-            fields.insert(f.name.value.clone(), value);
-        }
+        self.env
+            .borrow_mut()
+            .assign(stmt.name.value.clone(), Rc::new(RefCell::new(struct_val.clone())))
+            .map_err(|_| {
+                RizonResult::new(
+                    InterpErr::VarDeclEnv(stmt.name.to_string()),
+                    Some(stmt.loc.clone()),
+                )
+            })?;
 
-        let struct_val = RtVal::new_struct(stmt, fields, methods);
+        Ok(RtVal::new_null())
+    }
 
+    fn visit_enum_decl_stmt(&mut self, stmt: &EnumDeclStmt) -> InterpRes {
         self.env
             .borrow_mut()
-            .assign(stmt.name.value.clone(), Rc::new(RefCell::new(struct_val.clone())))
+            .declare_var(stmt.name.value.clone(), RtVal::new_enum(stmt).into())
             .map_err(|_| {
                 RizonResult::new(
                     InterpErr::VarDeclEnv(stmt.name.to_string()),
@@ -378,129 +696,540 @@ impl VisitStmt<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
 
         Ok(RtVal::new_null())
     }
-}
 
-impl Interpreter {
-    pub fn execute_block_stmt(&mut self, stmts: &Vec<Stmt>, env: Env) -> InterpRes {
-        let prev_env = std::mem::replace(&mut self.env, Rc::new(RefCell::new(env)));
+    fn visit_type_alias_stmt(&mut self, stmt: &TypeAliasStmt) -> InterpRes {
+        self.aliases.insert(stmt.name.value.clone(), stmt.typ.clone());
 
-        let mut res = Ok(RtVal::new_null());
-        for s in stmts {
-            res = s.accept(self);
+        Ok(RtVal::new_null())
+    }
 
-            if res.is_err() {
+    fn visit_match_stmt(&mut self, stmt: &MatchStmt) -> InterpRes {
+        let value = stmt.expr.accept(self)?;
+
+        for arm in &stmt.arms {
+            let is_match = match &arm.pattern {
+                MatchPattern::Wildcard(_) => true,
+                MatchPattern::Value(p) => {
+                    let pattern = p.accept(self)?;
+                    let (v, p) = (&*value.borrow(), &*pattern.borrow());
+
+                    v == p || matches!(v.operate(p, "=="), Ok(RtVal::BoolVal(b)) if b.value)
+                }
+            };
+
+            if is_match {
+                arm.body.accept(self)?;
                 break;
             }
         }
 
-        let _ = std::mem::replace(&mut self.env, prev_env);
-
-        res
+        Ok(RtVal::new_null())
     }
 }
 
-impl VisitExpr<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
-    fn visit_binary_expr(&mut self, expr: &BinaryExpr) -> InterpRes {
-        let lhs = expr.left.accept(self)?;
-        if lhs == RtVal::new_null() {
-            return Err(RizonResult::new(
-                InterpErr::UninitializedValue,
-                Some(expr.left.get_loc()),
-            ));
+impl Interpreter {
+    // The message can be built by a 'fn() -> str', only called if the assertion fails
+    fn check_assert(&mut self, args: &[Rc<RefCell<RtVal>>], loc: &Loc) -> InterpRes {
+        if args.first().is_some_and(|a| matches!(&*a.borrow(), RtVal::BoolVal(b) if b.value)) {
+            return Ok(RtVal::new_null())
         }
 
-        let rhs = expr.right.accept(self)?;
-        if rhs == RtVal::new_null() {
-            return Err(RizonResult::new(
-                InterpErr::UninitializedValue,
-                Some(expr.right.get_loc()),
-            ));
-        }
+        let msg = match args.get(1).map(|a| a.borrow().clone()) {
+            Some(RtVal::FuncVal(f)) => f
+                .call(self, vec![])
+                .map_err(|e| RizonResult::new(InterpErr::FnCall(e.err.to_string()), e.loc.or(Some(loc.clone()))))?
+                .borrow()
+                .clone(),
+            Some(v) => v,
+            None => RtVal::Null,
+        };
 
-        let tmp = rhs.borrow();
-        let tmp2 = lhs.borrow();
+        let msg = match msg {
+            RtVal::StrVal(s) => Some(s.value),
+            _ => None,
+        };
 
-        match tmp2.operate(&tmp, &expr.operator.value) {
-            Ok(res) => Ok(res.into()),
-            Err(e) => Err(RizonResult::new(
-                InterpErr::OperationEvaluation(e.to_string()),
-                Some(expr.right.get_loc()),
-            )),
-        }
+        Err(RizonResult::new(InterpErr::AssertionFailed(msg), Some(loc.clone())))
     }
 
-    fn visit_assign_expr(&mut self, expr: &AssignExpr) -> InterpRes {
-        let value = expr.value.accept(self)?;
+    // Prints the argument source and value in debug mode, returns the value
+    fn dbg(&mut self, expr: &CallExpr) -> InterpRes {
+        let arg = &expr.args[0];
+        let value = arg.accept(self)?;
 
-        match self.locals.get(&expr.loc) {
-            Some(i) => self
-                .env
-                .borrow_mut()
-                .assign_at(expr.name.clone(), value.clone(), i)
-                .map_err(|e| {
-                    RizonResult::new(InterpErr::AssignEnv(e.to_string()), Some(expr.loc.clone()))
-                })?,
-            None => self.globals.borrow_mut().assign(expr.name.clone(), value.clone()).map_err(|e| {
-                RizonResult::new(InterpErr::AssignEnv(e.to_string()), Some(expr.loc.clone()))
-            })?,
+        if !self.release_mode {
+            let (line, col) = self.lines.line_col(expr.loc.start);
+            println!("[{}:{}] {} = {}", line, col, arg, value.borrow());
         }
 
         Ok(value)
     }
 
-    fn visit_grouping_expr(&mut self, expr: &GroupingExpr) -> InterpRes {
-        expr.expr.accept(self)
-    }
+    // In tail position, calls to functions are made by the caller's 'Function::call'
+    // that reuses its frame
+    fn call(&mut self, expr: &CallExpr, callee: Rc<RefCell<RtVal>>, tail: bool) -> InterpRes {
+        // Assertions are removed in release mode, their argument isn't even evaluated
+        if self.release_mode {
+            if let RtVal::NativeFnVal(f) = &*callee.borrow() {
+                if f.name == "debug_assert" {
+                    return Ok(RtVal::new_null())
+                }
+            }
+        }
 
-    fn visit_int_literal_expr(&mut self, expr: &IntLiteralExpr) -> InterpRes {
-        Ok(RtVal::new_int(expr.value).into())
-    }
+        // Arity is checked by static analysis
+        if matches!(&*callee.borrow(), RtVal::NativeFnVal(f) if f.name == "dbg") && expr.args.len() == 1 {
+            return self.dbg(expr)
+        }
 
-    fn visit_float_literal_expr(&mut self, expr: &FloatLiteralExpr) -> InterpRes {
-        Ok(RtVal::new_float(expr.value).into())
-    }
+        let mut args: Vec<Rc<RefCell<RtVal>>> = vec![];
+        for a in &expr.args {
+            args.push(a.accept(self)?);
+        }
 
-    fn visit_str_literal_expr(&mut self, expr: &StrLiteralExpr) -> InterpRes {
-        Ok(RtVal::new_str(expr.value.clone()).into())
-    }
+        let tmp = &*callee.borrow();
 
-    fn visit_identifier_expr(&mut self, expr: &IdentifierExpr) -> InterpRes {
-        match expr.name.as_str() {
-            "true" => Ok(RtVal::new_bool(true).into()),
-            "false" => Ok(RtVal::new_bool(false).into()),
-            "null" => Ok(RtVal::new_null()),
-            _ => match self.locals.get(&expr.loc) {
-                Some(i) => self
-                    .env
-                    .borrow()
-                    .get_var_at(expr.name.clone(), i)
-                    .map_err(|e| {
-                        RizonResult::new(InterpErr::GetVarEnv(e.to_string()), Some(expr.loc.clone()))
-                    }),
-                None => self.globals.borrow().get_var(expr.name.clone()).map_err(|e| {
-                    RizonResult::new(InterpErr::GetVarEnv(e.to_string()), Some(expr.loc.clone()))
-                }),
-            },
-        }
-    }
+        if expr.arg_names.iter().any(|n| n.is_some()) {
+            let params = match tmp {
+                RtVal::FuncVal(f) => f.params.clone(),
+                RtVal::StructVal(s) => s.borrow().methods.get("init").map(|f| f.params.clone()).unwrap_or_default(),
+                _ => return Err(RizonResult::new(InterpErr::NonFnCall, Some(expr.callee.get_loc()))),
+            };
 
-    fn visit_unary_expr(&mut self, expr: &UnaryExpr) -> InterpRes {
-        let value = expr.right.accept(self)?;
+            args = Interpreter::order_named_args(&params, &expr.arg_names, args);
+        }
 
-        match expr.operator.kind {
-            TokenKind::Bang => match &mut *value.borrow_mut() {
-                RtVal::BoolVal(v) => v.negate(),
-                _ => return Err(RizonResult::new(
-                    InterpErr::BangOpOnNonBool,
-                    Some(expr.right.get_loc()),
-                ))
+        if let RtVal::NativeFnVal(f) = tmp {
+            if f.name == "assert" {
+                return self.check_assert(&args, &expr.loc)
             }
-            TokenKind::Minus => match &mut *value.borrow_mut() {
-                RtVal::IntVal(v) => v.negate(),
-                RtVal::FloatVal(v) => v.negate(),
-                _ => return Err(RizonResult::new(
-                    InterpErr::NegateNonNumeric,
-                    Some(expr.right.get_loc()),
+        }
+
+        let (callable, name): (Box<&dyn Callable>, EcoString) = match tmp {
+            RtVal::FuncVal(f) => (Box::new(f), f.name.clone()),
+            RtVal::NativeFnVal(f) => (Box::new(f), f.name.clone()),
+            RtVal::StructVal(s) => (Box::new(s), s.borrow().name.clone()),
+            _ => return Err(RizonResult::new(InterpErr::NonFnCall, Some(expr.callee.get_loc())))
+        };
+
+        // 'format' is variadic, its arguments are checked by the function
+        let variadic = matches!(tmp, RtVal::NativeFnVal(f) if f.name == "format");
+
+        // TODO: already done by static analysis
+        if !variadic && callable.arity() != args.len() {
+            return Err(RizonResult::new(
+                InterpErr::WrongArgsNb(callable.arity(), args.len()),
+                Some(expr.loc.clone()),
+            ));
+        }
+
+        let frame = TraceFrame { name: name.to_string(), loc: expr.loc.clone() };
+
+        if let (RtVal::FuncVal(f), true) = (tmp, tail) {
+            if matches!(self.call_stack.last(), Some((_, true))) {
+                self.call_stack.pop();
+            }
+            self.call_stack.push((frame, true));
+
+            return Err(RizonResult::new(InterpErr::TailCall(Box::new((f.clone(), args))), None))
+        }
+
+        self.call_stack.push((frame, false));
+
+        let res = callable.call(self, args);
+
+        if res.is_err() && self.error_trace.is_none() {
+            self.error_trace = Some(self.call_stack.iter().rev().map(|(f, _)| f.clone()).collect());
+        }
+
+        while let Some((_, true)) = self.call_stack.pop() {}
+
+        // Errors raised in the body of the function keep their own location
+        res.map_err(|e| {
+            RizonResult::new(InterpErr::FnCall(e.err.to_string()), e.loc.or(Some(expr.loc.clone())))
+        })
+    }
+
+    // Arguments are evaluated in the order they're written but bound in the
+    // order of the parameters. Names were validated by static analysis
+    fn order_named_args(
+        params: &[EcoString],
+        names: &[Option<Token>],
+        args: Vec<Rc<RefCell<RtVal>>>,
+    ) -> Vec<Rc<RefCell<RtVal>>> {
+        let mut ordered: Vec<Option<Rc<RefCell<RtVal>>>> = vec![None; params.len().max(args.len())];
+
+        for (i, (arg, name)) in args.into_iter().zip(names).enumerate() {
+            let idx = name
+                .as_ref()
+                .and_then(|n| params.iter().position(|p| p == &n.value))
+                .unwrap_or(i);
+
+            ordered[idx] = Some(arg);
+        }
+
+        ordered.into_iter().flatten().collect()
+    }
+
+    fn operate_binary(&self, expr: &BinaryExpr, lhs: Rc<RefCell<RtVal>>, rhs: Rc<RefCell<RtVal>>) -> InterpRes {
+        let tmp = rhs.borrow();
+        let tmp2 = lhs.borrow();
+
+        let res = if expr.operator.kind == TokenKind::Modulo && self.modulo_mode == ModuloMode::Floored {
+            tmp2.mod_floor(&tmp)
+        } else {
+            tmp2.operate(&tmp, &expr.operator.value)
+        };
+
+        // Divisions by zero point at the divisor, other failures at the whole operation
+        let loc = match res {
+            Err(RtValErr::DivisionByZero | RtValErr::ModuloByZero) => expr.right.get_loc(),
+            _ => expr.get_loc(),
+        };
+
+        res.map(|r| r.into())
+            .map_err(|e| RizonResult::new(InterpErr::OperationEvaluation(e.to_string()), Some(loc)))
+    }
+
+    // Applies the int to float cast allowed by the static analysis
+    pub(crate) fn coerce_to_type(&self, value: Rc<RefCell<RtVal>>, typ: &VarTypeDecl) -> Rc<RefCell<RtVal>> {
+        let typ = match self.resolve_alias(typ) {
+            VarTypeDecl::Optional { typ, .. } => self.resolve_alias(typ),
+            t => t,
+        };
+
+        let int_value = match &*value.borrow() {
+            RtVal::IntVal(v) => Some(v.value),
+            _ => None,
+        };
+
+        match (typ, int_value) {
+            (VarTypeDecl::Identifier(t), Some(i)) if t.kind == TokenKind::FloatType => {
+                RtVal::new_float(i as f64).into()
+            }
+            _ => value,
+        }
+    }
+
+    fn coerce_to_field(&self, strukt: &Rc<RefCell<Struct>>, field: &EcoString, value: Rc<RefCell<RtVal>>) -> Rc<RefCell<RtVal>> {
+        match strukt.borrow().field_types.get(field) {
+            Some(t) => self.coerce_to_type(value, t),
+            None => value,
+        }
+    }
+
+    fn value_has_type(&self, value: &RtVal, typ: &VarTypeDecl) -> bool {
+        match (value, self.resolve_alias(typ)) {
+            (_, VarTypeDecl::Identifier(t)) if t.kind == TokenKind::AnyType => true,
+            (v, VarTypeDecl::Identifier(t)) => v.is_of_type(&t.value) == RtVal::new_bool(true),
+            (RtVal::FuncVal(_) | RtVal::NativeFnVal(_), VarTypeDecl::Fn { .. }) => true,
+            (RtVal::ArrayVal(a), VarTypeDecl::Array { typ, .. }) => a
+                .values
+                .iter()
+                .all(|v| self.value_has_type(&v.borrow(), typ)),
+            (RtVal::MapVal(m), VarTypeDecl::Map { key, value, .. }) => m
+                .entries
+                .iter()
+                .all(|(k, v)| self.value_has_type(k, key) && self.value_has_type(&v.borrow(), value)),
+            (RtVal::Null, VarTypeDecl::Optional { .. }) => true,
+            (v, VarTypeDecl::Optional { typ, .. }) => self.value_has_type(v, typ),
+            _ => false,
+        }
+    }
+
+    // Structural comparison, the locations are ignored
+    fn same_type(&self, t1: &VarTypeDecl, t2: &VarTypeDecl) -> bool {
+        match (self.resolve_alias(t1), self.resolve_alias(t2)) {
+            (VarTypeDecl::Identifier(i1), VarTypeDecl::Identifier(i2)) => i1.value == i2.value,
+            (
+                VarTypeDecl::Fn { param_types: p1, return_type: r1, .. },
+                VarTypeDecl::Fn { param_types: p2, return_type: r2, .. },
+            ) => {
+                p1.len() == p2.len()
+                    && p1.iter().zip(p2).all(|(p1, p2)| self.same_type(p1, p2))
+                    && self.same_return_type(r1.as_deref(), r2.as_deref())
+            }
+            (VarTypeDecl::Array { typ: t1, .. }, VarTypeDecl::Array { typ: t2, .. })
+            | (VarTypeDecl::Optional { typ: t1, .. }, VarTypeDecl::Optional { typ: t2, .. }) => {
+                self.same_type(t1, t2)
+            }
+            (VarTypeDecl::Map { key: k1, value: v1, .. }, VarTypeDecl::Map { key: k2, value: v2, .. }) => {
+                self.same_type(k1, k2) && self.same_type(v1, v2)
+            }
+            _ => false,
+        }
+    }
+
+    // No return type is the same as 'void'
+    fn same_return_type(&self, t1: Option<&VarTypeDecl>, t2: Option<&VarTypeDecl>) -> bool {
+        let is_void = |t: Option<&VarTypeDecl>| match t.map(|t| self.resolve_alias(t)) {
+            None => true,
+            Some(VarTypeDecl::Identifier(i)) => i.kind == TokenKind::VoidType,
+            _ => false,
+        };
+
+        match (t1, t2) {
+            (Some(t1), Some(t2)) => self.same_type(t1, t2),
+            _ => is_void(t1) && is_void(t2),
+        }
+    }
+
+    // Follows the aliases chain until the underlying type
+    fn resolve_alias<'a>(&'a self, type_decl: &'a VarTypeDecl) -> &'a VarTypeDecl {
+        match type_decl {
+            VarTypeDecl::Identifier(i) => match self.aliases.get(&i.value) {
+                Some(t) => self.resolve_alias(t),
+                None => type_decl,
+            },
+            _ => type_decl,
+        }
+    }
+
+    pub(crate) fn evaluate_in(&mut self, expr: &Expr, env: Rc<RefCell<Env>>) -> InterpRes {
+        let prev_env = std::mem::replace(&mut self.env, env);
+        let res = expr.accept(self);
+        let _ = std::mem::replace(&mut self.env, prev_env);
+
+        res
+    }
+
+    // Returns false if the loop is exited with 'break'
+    fn execute_loop_body(&mut self, body: &Stmt) -> Result<bool, RizonResInterp> {
+        match body.accept(self) {
+            Ok(_) => Ok(true),
+            Err(e) => match e.err {
+                InterpErr::Break => Ok(false),
+                InterpErr::Continue => Ok(true),
+                _ => Err(e),
+            },
+        }
+    }
+
+    pub fn execute_block_stmt(&mut self, stmts: &Vec<Stmt>, env: Env) -> InterpRes {
+        let prev_env = std::mem::replace(&mut self.env, Rc::new(RefCell::new(env)));
+
+        let mut res = Ok(RtVal::new_null());
+        for s in stmts {
+            res = self.step(&s.get_loc()).and_then(|_| s.accept(self));
+
+            if res.is_err() {
+                break;
+            }
+        }
+
+        let _ = std::mem::replace(&mut self.env, prev_env);
+
+        res
+    }
+}
+
+impl Interpreter {
+    // Evaluates the object before the index: a[f()] evaluates 'a' first
+    fn eval_indexed(&mut self, object: &Expr, index: &Expr) -> Result<(Rc<RefCell<RtVal>>, RtVal), RizonResInterp> {
+        let obj = object.accept(self)?;
+        let idx = index.accept(self)?;
+        let idx = self.widen(idx, &index.get_loc()).borrow().clone();
+
+        Ok((obj, idx))
+    }
+
+    // The left operand is already evaluated, it can come from a compound
+    // assignment target
+    fn eval_binary(&mut self, expr: &BinaryExpr, lhs: Rc<RefCell<RtVal>>) -> InterpRes {
+        // Null checks on nullable values: a == null, a != null
+        if matches!(expr.operator.kind, TokenKind::EqualEqual | TokenKind::BangEqual) {
+            let rhs = expr.right.accept(self)?;
+
+            let lhs_null = matches!(&*lhs.borrow(), RtVal::Null);
+            let rhs_null = matches!(&*rhs.borrow(), RtVal::Null);
+
+            if lhs_null || rhs_null {
+                let equal = lhs_null && rhs_null;
+
+                return Ok(RtVal::new_bool(equal == (expr.operator.kind == TokenKind::EqualEqual)).into())
+            }
+
+            return self.operate_binary(expr, lhs, rhs)
+        }
+
+        if lhs == RtVal::new_null() {
+            return Err(RizonResult::new(
+                InterpErr::UninitializedValue,
+                Some(expr.left.get_loc()),
+            ));
+        }
+
+        let rhs = expr.right.accept(self)?;
+        if rhs == RtVal::new_null() {
+            return Err(RizonResult::new(
+                InterpErr::UninitializedValue,
+                Some(expr.right.get_loc()),
+            ));
+        }
+
+        let res = self.operate_binary(expr, lhs, rhs)?;
+
+        // Int powers typed as float by the static analyzer, their exponent
+        // wasn't known before running
+        if expr.operator.kind == TokenKind::StarStar {
+            return Ok(self.widen(res, &expr.get_loc()));
+        }
+
+        Ok(res)
+    }
+
+    fn get_member(&self, obj: &Rc<RefCell<RtVal>>, expr: &GetExpr) -> InterpRes {
+        let tmp = &*obj.borrow();
+
+        if let RtVal::EnumVal(e) = tmp {
+            return match e.get_variant(&expr.name.value) {
+                Some(v) => Ok(v.into()),
+                None => Err(RizonResult::new(InterpErr::InexistantField(expr.name.value.clone()), Some(expr.name.loc.clone()))),
+            }
+        }
+
+        if let RtVal::InstanceVal(inst) = tmp {
+            // Field
+            if let Some(v) = inst.fields.get(&expr.name.value) {
+                Ok(v.clone())
+            // Methods
+            } else if let Some(m) = inst.strukt.borrow().methods.get(&expr.name.value) {
+                Ok(m.wrap_bind(obj.clone()))
+            } else {
+                Err(RizonResult::new(InterpErr::InexistantField(expr.name.value.clone()), Some(expr.name.loc.clone())))
+            }
+        } else {
+            Err(RizonResult::new(InterpErr::NonInstPropAccess, Some(expr.object.get_loc())))
+        }
+    }
+
+    fn get_index(&self, obj: &Rc<RefCell<RtVal>>, idx: &RtVal, expr: &IndexExpr) -> InterpRes {
+        let tmp = &*obj.borrow();
+
+        let res = match (tmp, idx) {
+            (RtVal::ArrayVal(a), RtVal::IntVal(i)) => a.get(i.value),
+            (RtVal::ArrayVal(_), _) => {
+                return Err(RizonResult::new(InterpErr::NonIntIndex, Some(expr.index.get_loc())))
+            }
+            (RtVal::MapVal(m), k) => m.get(k),
+            _ => return Err(RizonResult::new(InterpErr::NonIndexable, Some(expr.object.get_loc()))),
+        };
+
+        res.map_err(|e| RizonResult::new(InterpErr::IndexAccess(e.to_string()), Some(expr.index.get_loc())))
+    }
+
+    // Value of 'a.b op= v' and 'a[i] op= v' lowered to 'a.b op v', the
+    // current value is read from the object and index already evaluated
+    fn eval_compound(&mut self, value: &Expr, obj: &Rc<RefCell<RtVal>>, idx: Option<&RtVal>) -> InterpRes {
+        let Expr::Binary(operation) = value else {
+            unreachable!("compound assignments are lowered to binary operations");
+        };
+
+        let current = match (&*operation.left, idx) {
+            (Expr::Get(e), None) => self.get_member(obj, e)?,
+            (Expr::Index(e), Some(idx)) => self.get_index(obj, idx, e)?,
+            _ => unreachable!("compound assignment target doesn't match its assignment"),
+        };
+
+        self.eval_binary(operation, current)
+    }
+
+    fn widen(&self, value: Rc<RefCell<RtVal>>, loc: &Loc) -> Rc<RefCell<RtVal>> {
+        let int_value = match &*value.borrow() {
+            RtVal::IntVal(v) if self.widened.contains(loc) => Some(v.value),
+            _ => None,
+        };
+
+        match int_value {
+            Some(i) => RtVal::new_float(i as f64).into(),
+            None => value,
+        }
+    }
+}
+
+impl VisitExpr<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
+    fn visit_binary_expr(&mut self, expr: &BinaryExpr) -> InterpRes {
+        let lhs = expr.left.accept(self)?;
+
+        self.eval_binary(expr, lhs)
+    }
+
+    fn visit_assign_expr(&mut self, expr: &AssignExpr) -> InterpRes {
+        let value = expr.value.accept(self)?;
+
+        match self.locals.get(&expr.loc) {
+            Some(i) => self
+                .env
+                .borrow_mut()
+                .assign_at(expr.name.clone(), value.clone(), i)
+                .map_err(|e| {
+                    RizonResult::new(InterpErr::AssignEnv(e.to_string()), Some(expr.loc.clone()))
+                })?,
+            None => self.globals.borrow_mut().assign(expr.name.clone(), value.clone()).map_err(|e| {
+                RizonResult::new(InterpErr::AssignEnv(e.to_string()), Some(expr.loc.clone()))
+            })?,
+        }
+
+        Ok(value)
+    }
+
+    fn visit_grouping_expr(&mut self, expr: &GroupingExpr) -> InterpRes {
+        expr.expr.accept(self)
+    }
+
+    fn visit_int_literal_expr(&mut self, expr: &IntLiteralExpr) -> InterpRes {
+        Ok(RtVal::new_int(expr.value).into())
+    }
+
+    fn visit_float_literal_expr(&mut self, expr: &FloatLiteralExpr) -> InterpRes {
+        Ok(RtVal::new_float(expr.value).into())
+    }
+
+    fn visit_str_literal_expr(&mut self, expr: &StrLiteralExpr) -> InterpRes {
+        Ok(RtVal::new_str(expr.value.clone()).into())
+    }
+
+    fn visit_char_literal_expr(&mut self, expr: &CharLiteralExpr) -> InterpRes {
+        Ok(RtVal::new_char(expr.value).into())
+    }
+
+    fn visit_identifier_expr(&mut self, expr: &IdentifierExpr) -> InterpRes {
+        match expr.name.as_str() {
+            "true" => Ok(RtVal::new_bool(true).into()),
+            "false" => Ok(RtVal::new_bool(false).into()),
+            "null" => Ok(RtVal::new_null()),
+            _ => match self.locals.get(&expr.loc) {
+                Some(i) => self
+                    .env
+                    .borrow()
+                    .get_var_at(expr.name.clone(), i)
+                    .map_err(|e| {
+                        RizonResult::new(InterpErr::GetVarEnv(e.to_string()), Some(expr.loc.clone()))
+                    }),
+                None => self.globals.borrow().get_var(expr.name.clone()).map_err(|e| {
+                    RizonResult::new(InterpErr::GetVarEnv(e.to_string()), Some(expr.loc.clone()))
+                }),
+            },
+        }
+    }
+
+    fn visit_unary_expr(&mut self, expr: &UnaryExpr) -> InterpRes {
+        let value = expr.right.accept(self)?;
+
+        match expr.operator.kind {
+            TokenKind::Bang => match &mut *value.borrow_mut() {
+                RtVal::BoolVal(v) => v.negate(),
+                _ => return Err(RizonResult::new(
+                    InterpErr::BangOpOnNonBool,
+                    Some(expr.right.get_loc()),
+                ))
+            }
+            TokenKind::Minus => match &mut *value.borrow_mut() {
+                RtVal::IntVal(v) => v.negate(),
+                RtVal::FloatVal(v) => v.negate(),
+                _ => return Err(RizonResult::new(
+                    InterpErr::NegateNonNumeric,
+                    Some(expr.right.get_loc()),
                 ))
             },
             _ => return Err(RizonResult::new(
@@ -512,10 +1241,47 @@ impl VisitExpr<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
         Ok(value)
     }
 
+    fn visit_chained_compare_expr(&mut self, expr: &ChainedCompareExpr) -> InterpRes {
+        let mut lhs = expr.operands[0].accept(self)?;
+
+        for (i, operator) in expr.operators.iter().enumerate() {
+            let (lhs_expr, rhs_expr) = (&expr.operands[i], &expr.operands[i + 1]);
+
+            if lhs == RtVal::new_null() {
+                return Err(RizonResult::new(InterpErr::UninitializedValue, Some(lhs_expr.get_loc())));
+            }
+
+            let rhs = rhs_expr.accept(self)?;
+            if rhs == RtVal::new_null() {
+                return Err(RizonResult::new(InterpErr::UninitializedValue, Some(rhs_expr.get_loc())));
+            }
+
+            let res = lhs.borrow().operate(&rhs.borrow(), &operator.value).map_err(|e| {
+                RizonResult::new(
+                    InterpErr::OperationEvaluation(e.to_string()),
+                    Some(Loc::span(&lhs_expr.get_loc(), &rhs_expr.get_loc())),
+                )
+            })?;
+
+            // Stops at the first false comparison, like 'and'
+            if matches!(&res, RtVal::BoolVal(b) if !b.value) {
+                return Ok(res.into())
+            }
+
+            lhs = rhs;
+        }
+
+        Ok(RtVal::new_bool(true).into())
+    }
+
     fn visit_logical_expr(&mut self, expr: &LogicalExpr) -> InterpRes {
         let left = expr.left.accept(self)?;
 
-        if expr.operator.kind == TokenKind::Or {
+        if expr.operator.kind == TokenKind::QuestionQuestion {
+            if !matches!(&*left.borrow(), RtVal::Null) {
+                return Ok(left)
+            }
+        } else if expr.operator.kind == TokenKind::Or {
             match &*left.clone().borrow() {
                 RtVal::BoolVal(b) => {
                     if b.value {
@@ -551,60 +1317,39 @@ impl VisitExpr<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
     fn visit_call_expr(&mut self, expr: &CallExpr) -> InterpRes {
         let callee = expr.callee.accept(self)?;
 
-        let mut args: Vec<Rc<RefCell<RtVal>>> = vec![];
-        for a in &expr.args {
-            args.push(a.accept(self)?);
-        }
-
-        let tmp = &*callee.borrow();
-
-        let callable: Box<&dyn Callable> = match tmp {
-            RtVal::FuncVal(f) => Box::new(f),
-            RtVal::NativeFnVal(f) => Box::new(f),
-            RtVal::StructVal(s) => Box::new(s),
-            _ => return Err(RizonResult::new(InterpErr::NonFnCall, Some(expr.callee.get_loc())))
-        };
-
-        // TODO: already done by static analysis
-        if callable.arity() != args.len() {
-            return Err(RizonResult::new(
-                InterpErr::WrongArgsNb(callable.arity(), args.len()),
-                Some(expr.loc.clone()),
-            ));
-        }
-
-        callable.call(self, args).map_err(|e| {
-            RizonResult::new(InterpErr::FnCall(e.err.to_string()), Some(expr.loc.clone()))
-        })
+        self.call(expr, callee, false)
     }
     
     fn visit_get_expr(&mut self, expr: &GetExpr) -> InterpRes {
         let obj = expr.object.accept(self)?;
-        let tmp = &*obj.borrow();
 
-        if let RtVal::InstanceVal(inst) = tmp {
-            // Field
-            if let Some(v) = inst.fields.get(&expr.name.value) {
-                Ok(v.clone())
-            // Methods
-            } else if let Some(m) = inst.strukt.borrow().methods.get(&expr.name.value) {
-                Ok(m.wrap_bind(obj.clone()))
-            } else {
-                Err(RizonResult::new(InterpErr::InexistantField(expr.name.value.clone()), Some(expr.loc.clone())))
-            }
-        } else {
-            Err(RizonResult::new(InterpErr::NonInstPropAccess, Some(expr.loc.clone())))
-        }
+        self.get_member(&obj, expr)
     }
-    
+
     fn visit_set_expr(&mut self, expr: &SetExpr) -> InterpRes {
         let obj = expr.object.accept(self)?;
+        // Evaluated before borrowing the object, the value can read it: self.x = self.x + 1
+        let val = if expr.compound {
+            self.eval_compound(&expr.value, &obj, None)?
+        } else {
+            expr.value.accept(self)?
+        };
+
+        // Coerced before borrowing the object mutably, the value can be the
+        // object itself: a.next = a
+        let strukt = match &*obj.borrow() {
+            RtVal::InstanceVal(inst) => Some(inst.strukt.clone()),
+            _ => None,
+        };
+        let val = match strukt {
+            Some(s) => self.coerce_to_field(&s, &expr.name.value, val),
+            None => val,
+        };
+
         let mut tmp = obj.borrow_mut();
 
         match &mut *tmp {
             RtVal::InstanceVal(inst) => {
-                let val = expr.value.accept(self)?;
-
                 inst.set(expr.name.value.clone(), val.clone())
                     .map_err(|e| RizonResult::new(InterpErr::InexistantFieldBis(e.to_string()), Some(expr.loc.clone())))?;
 
@@ -626,6 +1371,466 @@ impl VisitExpr<Rc<RefCell<RtVal>>, InterpErr> for Interpreter {
         let value = expr.left.accept(self)?;
         let tmp = value.borrow();
 
-        Ok(tmp.is_of_type(&expr.typ.value).into())
+        let res = match (&*tmp, self.resolve_alias(&expr.typ)) {
+            (RtVal::FuncVal(f), VarTypeDecl::Fn { param_types, return_type, .. }) => {
+                f.param_types.len() == param_types.len()
+                    && f.param_types.iter().zip(param_types).all(|(p1, p2)| self.same_type(p1, p2))
+                    && self.same_return_type(f.return_type.as_deref(), return_type.as_deref())
+            }
+            // Natives only know their arity
+            (RtVal::NativeFnVal(f), VarTypeDecl::Fn { param_types, .. }) => f.arity == param_types.len(),
+            (v, typ) => self.value_has_type(v, typ),
+        };
+
+        Ok(RtVal::new_bool(res).into())
+    }
+
+    fn visit_cast_expr(&mut self, expr: &CastExpr) -> InterpRes {
+        let value = expr.expr.accept(self)?;
+
+        let typ = match self.aliases.get(&expr.typ.value).map(|t| self.resolve_alias(t)) {
+            Some(VarTypeDecl::Identifier(i)) => &i.value,
+            _ => &expr.typ.value,
+        };
+
+        let res = value.borrow().cast(typ);
+
+        match res {
+            Ok(v) => Ok(v.into()),
+            Err(e) => Err(RizonResult::new(
+                InterpErr::OperationEvaluation(e.to_string()),
+                Some(expr.loc.clone()),
+            )),
+        }
+    }
+
+    fn visit_if_expr(&mut self, expr: &IfExpr) -> InterpRes {
+        let cond = expr.condition.accept(self)?;
+        let tmp = &*cond.borrow();
+
+        match tmp {
+            RtVal::BoolVal(b) => match b.value {
+                true => expr.then_expr.accept(self),
+                false => match &expr.else_expr {
+                    Some(e) => e.accept(self),
+                    None => Ok(RtVal::new_null()),
+                },
+            },
+            _ => Err(RizonResult::new(
+                InterpErr::NonBoolIfCond,
+                Some(expr.loc.clone()),
+            )),
+        }
+    }
+
+    fn visit_array_literal_expr(&mut self, expr: &ArrayLiteralExpr) -> InterpRes {
+        let mut values: Vec<Rc<RefCell<RtVal>>> = vec![];
+        for v in &expr.values {
+            values.push(v.accept(self)?);
+        }
+
+        Ok(RtVal::new_array(values).into())
+    }
+
+    fn visit_map_literal_expr(&mut self, expr: &MapLiteralExpr) -> InterpRes {
+        let mut entries: Vec<(RtVal, Rc<RefCell<RtVal>>)> = vec![];
+        for (k, v) in &expr.entries {
+            let key = k.accept(self)?.borrow().clone();
+            entries.push((key, v.accept(self)?));
+        }
+
+        Ok(RtVal::new_map(entries).into())
+    }
+
+    // Fields that aren't given keep their default value
+    fn visit_struct_literal_expr(&mut self, expr: &StructLiteralExpr) -> InterpRes {
+        let strukt = match &*expr.strukt.accept(self)?.borrow() {
+            RtVal::StructVal(s) => s.clone(),
+            _ => return Err(RizonResult::new(InterpErr::NonStructLiteral, Some(expr.strukt.get_loc()))),
+        };
+
+        let mut instance = Instance::new(strukt, self)?;
+
+        for (name, value) in &expr.fields {
+            let value = value.accept(self)?;
+            let value = self.coerce_to_field(&instance.strukt, &name.value, value);
+
+            instance
+                .set(name.value.clone(), value)
+                .map_err(|e| RizonResult::new(InterpErr::InexistantFieldBis(e.to_string()), Some(name.loc.clone())))?;
+        }
+
+        Ok(RtVal::InstanceVal(instance).into())
+    }
+
+    fn visit_index_expr(&mut self, expr: &IndexExpr) -> InterpRes {
+        let (obj, idx) = self.eval_indexed(&expr.object, &expr.index)?;
+
+        self.get_index(&obj, &idx, expr)
+    }
+
+    fn visit_index_set_expr(&mut self, expr: &IndexSetExpr) -> InterpRes {
+        let (obj, idx) = self.eval_indexed(&expr.object, &expr.index)?;
+        let val = if expr.compound {
+            self.eval_compound(&expr.value, &obj, Some(&idx))?
+        } else {
+            expr.value.accept(self)?
+        };
+        let val = self.widen(val, &expr.value.get_loc());
+        let mut tmp = obj.borrow_mut();
+
+        let res = match (&mut *tmp, idx) {
+            (RtVal::ArrayVal(a), RtVal::IntVal(i)) => a.set(i.value, val.clone()),
+            (RtVal::ArrayVal(_), _) => {
+                return Err(RizonResult::new(InterpErr::NonIntIndex, Some(expr.index.get_loc())))
+            }
+            (RtVal::MapVal(m), k) => m.set(k, val.clone()),
+            _ => return Err(RizonResult::new(InterpErr::NonIndexable, Some(expr.object.get_loc()))),
+        };
+
+        res.map_err(|e| RizonResult::new(InterpErr::IndexAccess(e.to_string()), Some(expr.index.get_loc())))?;
+
+        Ok(val)
+    }
+
+    fn visit_range_expr(&mut self, expr: &RangeExpr) -> InterpRes {
+        let start = expr.start.accept(self)?;
+        let end = expr.end.accept(self)?;
+
+        let step = match &expr.step {
+            Some(s) => match &*s.accept(self)?.borrow() {
+                RtVal::IntVal(i) if i.value == 0 => {
+                    return Err(RizonResult::new(InterpErr::ZeroRangeStep, Some(s.get_loc())))
+                }
+                RtVal::IntVal(i) => i.value,
+                _ => return Err(RizonResult::new(InterpErr::NonIntRangeBound, Some(s.get_loc()))),
+            },
+            None => 1,
+        };
+
+        let range = match (&*start.borrow(), &*end.borrow()) {
+            (RtVal::IntVal(s), RtVal::IntVal(e)) => RtVal::new_range(s.value, e.value, step, expr.inclusive),
+            _ => return Err(RizonResult::new(InterpErr::NonIntRangeBound, Some(expr.loc.clone()))),
+        };
+
+        Ok(range.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, io::Cursor};
+
+    use rizon_frontend::{fold::fold_constants, lexer::Lexer, parser::Parser};
+    use rizon_static_analyzer::StaticAnalyzer;
+
+    use super::{InterpErr, Interpreter, ModuloMode};
+    use rizon_tools::results::Loc;
+    use crate::values::RtVal;
+
+    fn eval(interpreter: &mut Interpreter, code: &str) -> RtVal {
+        let tokens = Lexer::new().tokenize(code).unwrap();
+        let nodes = Parser::default().parse(tokens).unwrap();
+
+        let res = interpreter.interpret(&nodes, HashMap::new()).unwrap();
+        let val = res.borrow().clone();
+
+        val
+    }
+
+    #[test]
+    fn truncated_modulo() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(eval(&mut interpreter, "-7 % 3"), RtVal::new_int(-1));
+        assert_eq!(eval(&mut interpreter, "3.5 % 2.0"), RtVal::new_float(1.5));
+        assert_eq!(eval(&mut interpreter, "-3.5 % 2.0"), RtVal::new_float(-1.5));
+        assert_eq!(eval(&mut interpreter, "3.5 % -2.0"), RtVal::new_float(1.5));
+        assert_eq!(eval(&mut interpreter, "mod_euclid(-7, 3)"), RtVal::new_int(2));
+    }
+
+    #[test]
+    fn floored_modulo() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_modulo_mode(ModuloMode::Floored);
+
+        assert_eq!(eval(&mut interpreter, "-7 % 3"), RtVal::new_int(2));
+        assert_eq!(eval(&mut interpreter, "7 % -3"), RtVal::new_int(-2));
+        assert_eq!(eval(&mut interpreter, "-7 % -3"), RtVal::new_int(-1));
+        assert_eq!(eval(&mut interpreter, "6 % -3"), RtVal::new_int(0));
+        assert_eq!(eval(&mut interpreter, "-7.5 % 2"), RtVal::new_float(0.5));
+        assert_eq!(eval(&mut interpreter, "7.5 % -2.0"), RtVal::new_float(-0.5));
+        // The native stays Euclidean whatever the mode
+        assert_eq!(eval(&mut interpreter, "mod_euclid(-7, 3)"), RtVal::new_int(2));
+        assert_eq!(eval(&mut interpreter, "mod_euclid(7, -3)"), RtVal::new_int(1));
+    }
+
+    #[test]
+    fn special_floats_display() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(eval(&mut interpreter, "-0.0").to_string(), "-0.0");
+        assert_eq!(eval(&mut interpreter, "0.0").to_string(), "0.0");
+        assert_eq!(eval(&mut interpreter, "-3.0").to_string(), "-3.0");
+        assert_eq!(eval(&mut interpreter, "2.5").to_string(), "2.5");
+        assert_eq!(eval(&mut interpreter, "to_str(4.0)").to_string(), "\"4.0\"");
+        assert_eq!(eval(&mut interpreter, "1.0 / 0.0").to_string(), "inf");
+        assert_eq!(eval(&mut interpreter, "-1.0 / 0.0").to_string(), "-inf");
+        assert_eq!(eval(&mut interpreter, "pow(-1.0, 0.5)").to_string(), "nan");
+        assert_eq!(eval(&mut interpreter, "to_str(1.0 / 0.0)").to_string(), "\"inf\"");
+    }
+
+    #[test]
+    fn debug_assert_in_debug_mode() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(eval(&mut interpreter, "debug_assert(1 < 2)"), RtVal::Null);
+
+        let tokens = Lexer::new().tokenize("debug_assert(false)").unwrap();
+        let nodes = Parser::default().parse(tokens).unwrap();
+        assert!(interpreter.interpret(&nodes, HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn debug_assert_in_release_mode() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_release_mode(true);
+
+        let code = "
+var count = 0
+fn check() -> bool {
+    count = count + 1
+    return false
+}
+debug_assert(check())
+count";
+
+        assert_eq!(eval(&mut interpreter, code), RtVal::new_int(0));
+    }
+
+    #[test]
+    fn folded_constants_evaluate_the_same() {
+        let mut interpreter = Interpreter::new();
+
+        for code in ["2 + 3 * 4", "(1 - 4) * 2.5", "-7 / 2", "\"a\" + \"b\" + \"c\"", "1.0 / 3"] {
+            let tokens = Lexer::new().tokenize(code).unwrap();
+            let nodes = fold_constants(Parser::default().parse(tokens).unwrap());
+
+            let folded = interpreter.interpret(&nodes, HashMap::new()).unwrap().borrow().clone();
+            assert_eq!(folded, eval(&mut interpreter, code));
+        }
+    }
+
+    #[test]
+    fn dbg_returns_its_argument() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(eval(&mut interpreter, "dbg(1 + 2)"), RtVal::new_int(3));
+        assert_eq!(eval(&mut interpreter, "dbg(dbg(2) * 2)"), RtVal::new_int(4));
+
+        interpreter.set_release_mode(true);
+
+        let code = "
+var count = 0
+fn incr() -> int {
+    count = count + 1
+    return count
+}
+dbg(incr())
+dbg(1 + 2) + count";
+
+        // Still evaluated once in release mode
+        assert_eq!(eval(&mut interpreter, code), RtVal::new_int(4));
+    }
+
+    #[test]
+    fn native_callbacks_respect_limits() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_max_steps(Some(10_000));
+        interpreter.set_max_call_depth(64);
+
+        let mut run = |code: &str| {
+            let tokens = Lexer::new().tokenize(code).unwrap();
+            let nodes = Parser::default().parse(tokens).unwrap();
+            let locals = StaticAnalyzer::default().resolve(&nodes).unwrap();
+
+            interpreter.interpret(&nodes, locals).map(|v| v.borrow().clone())
+        };
+
+        let values = (0..100_000).map(|i| i.to_string()).collect::<Vec<_>>().join(", ");
+        let code = format!("
+fn spin(x: int) -> int {{
+    while true {{ x = x + 1 }}
+    return x
+}}
+map([{}], spin)", values);
+
+        let err = run(&code).err().unwrap();
+        assert_eq!(err.err.to_string(), InterpErr::StepLimitReached(10_000).to_string());
+
+        let err = run("fn down(x: int) -> int { return 1 + down(x + 1) }\nmap([1, 2, 3], down)").err().unwrap();
+        assert_eq!(err.err.to_string(), InterpErr::MaxCallDepth(64).to_string());
+
+        // The budget is per run and the call depth is restored
+        let res = run("fn id(x: int) -> int { return x }\nmap([1], id)").unwrap();
+        assert_eq!(res, RtVal::new_array(vec![RtVal::new_int(1).into()]));
+    }
+
+    #[test]
+    fn tail_calls_reuse_the_frame() {
+        let mut interpreter = Interpreter::new();
+
+        let code = "
+fn fact(n: int, acc: int) -> int {
+    if n == 0 { return acc }
+    return fact(n - 1, acc * n % 1000000007)
+}
+fact(1000000, 1)";
+
+        let tokens = Lexer::new().tokenize(code).unwrap();
+        let nodes = Parser::default().parse(tokens).unwrap();
+        let locals = StaticAnalyzer::default().resolve(&nodes).unwrap();
+
+        let res = interpreter.interpret(&nodes, locals).unwrap();
+        assert_eq!(*res.borrow(), RtVal::new_int(641102369));
+    }
+
+    #[test]
+    fn assert_failure_carries_message() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(eval(&mut interpreter, "assert(true, \"unused\")"), RtVal::Null);
+
+        let tokens = Lexer::new().tokenize("assert(1 > 2, \"wrong order\")").unwrap();
+        let nodes = Parser::default().parse(tokens).unwrap();
+        let err = interpreter.interpret(&nodes, HashMap::new()).err().unwrap();
+
+        assert_eq!(err.err, InterpErr::AssertionFailed(Some("wrong order".into())));
+        assert!(err.loc.is_some());
+    }
+
+    #[test]
+    fn iterate_returned_collections() {
+        let mut interpreter = Interpreter::new();
+
+        let code = "
+fn squares() -> [int] { return [1, 4, 9] }
+fn ages() -> {str: int} { return {\"bob\": 32, \"alice\": 27} }
+fn answer() -> int { return 42 }
+";
+        let tokens = Lexer::new().tokenize(code).unwrap();
+        let nodes = Parser::default().parse(tokens).unwrap();
+        let locals = StaticAnalyzer::default().resolve(&nodes).unwrap();
+        interpreter.interpret(&nodes, locals).unwrap();
+
+        let squares = interpreter.call_function("squares", vec![]).unwrap();
+        let values: Vec<RtVal> = squares.as_array_iter().unwrap().collect();
+        assert_eq!(values, vec![RtVal::new_int(1), RtVal::new_int(4), RtVal::new_int(9)]);
+        assert!(squares.as_map_iter().is_none());
+
+        let ages = interpreter.call_function("ages", vec![]).unwrap();
+        let entries: Vec<(RtVal, RtVal)> = ages.as_map_iter().unwrap().collect();
+        assert_eq!(entries, vec![
+            (RtVal::new_str("bob".into()), RtVal::new_int(32)),
+            (RtVal::new_str("alice".into()), RtVal::new_int(27)),
+        ]);
+
+        let answer = interpreter.call_function("answer", vec![]).unwrap();
+        assert!(answer.as_array_iter().is_none());
+        assert!(answer.as_map_iter().is_none());
+    }
+
+    #[test]
+    fn read_line_from_injected_stdin() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_stdin(Box::new(Cursor::new("first line\nsecond\r\n")));
+
+        assert_eq!(eval(&mut interpreter, "read_line()"), RtVal::new_str("first line".into()));
+        assert_eq!(eval(&mut interpreter, "read_line()"), RtVal::new_str("second".into()));
+
+        // End of input
+        let tokens = Lexer::new().tokenize("read_line()").unwrap();
+        let nodes = Parser::default().parse(tokens).unwrap();
+        assert!(interpreter.interpret(&nodes, HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn call_function_from_host() {
+        let mut interpreter = Interpreter::new();
+
+        // Parameters are resolved as locals
+        let tokens = Lexer::new().tokenize("fn add(a: int, b: int) -> int { return a + b }").unwrap();
+        let nodes = Parser::default().parse(tokens).unwrap();
+        let locals = StaticAnalyzer::default().resolve(&nodes).unwrap();
+        interpreter.interpret(&nodes, locals).unwrap();
+
+        let res = interpreter.call_function("add", vec![RtVal::new_int(2), RtVal::new_int(3)]);
+        assert_eq!(res.unwrap(), RtVal::new_int(5));
+
+        let res = interpreter.call_function("sub", vec![]);
+        assert_eq!(res.err().unwrap().err, InterpErr::UnknownFn("sub".into()));
+
+        let res = interpreter.call_function("add", vec![RtVal::new_int(2)]);
+        assert_eq!(res.err().unwrap().err, InterpErr::WrongArgsNb(2, 1));
+
+        let res = interpreter.call_function("add", vec![RtVal::new_int(2), RtVal::new_float(1.)]);
+        assert_eq!(res.err().unwrap().err, InterpErr::WrongArgType(2, "int".into()));
+    }
+
+    #[test]
+    fn runtime_errors_point_at_failing_sub_expression() {
+        let run = |code: &str| {
+            let tokens = Lexer::new().tokenize(code).unwrap();
+            let nodes = Parser::default().parse(tokens).unwrap();
+            let locals = StaticAnalyzer::default().resolve(&nodes).unwrap();
+            Interpreter::new().interpret(&nodes, locals).err().unwrap()
+        };
+
+        // The out of range index, not the whole indexing nor the operator
+        let code = "var a = [1, 2]\nprint 1 + a[5] * 2";
+        let idx = code.find('5').unwrap();
+        assert_eq!(run(code).loc, Some(Loc::new(idx, idx)));
+
+        // Inside the body of the called function, not at the call site
+        let code = "fn f(n: int) -> int { return 10 / n }\nprint 1 + f(0)";
+        let idx = code.find("n }").unwrap();
+        assert_eq!(run(code).loc, Some(Loc::new(idx, idx)));
+    }
+
+    #[test]
+    fn runtime_errors_carry_the_call_stack() {
+        let code = "
+fn c(x: int) -> int {
+    return 10 / x
+}
+fn b(x: int) -> int {
+    return c(x - 1) + 1
+}
+fn a() -> int {
+    // Tail call, still listed
+    return b(1)
+}
+print a()";
+        let tokens = Lexer::new().tokenize(code).unwrap();
+        let nodes = Parser::default().parse(tokens).unwrap();
+        let locals = StaticAnalyzer::default().resolve(&nodes).unwrap();
+
+        let mut interpreter = Interpreter::new();
+        let err = interpreter.interpret(&nodes, locals.clone()).err().unwrap();
+
+        let frames: Vec<(&str, &str)> = err
+            .trace
+            .iter()
+            .map(|f| (f.name.as_str(), &code[f.loc.start..=f.loc.end]))
+            .collect();
+        assert_eq!(frames, [("c", "c(x - 1)"), ("b", "b(1)"), ("a", "a()")]);
+
+        let report = err.format_report("test.rz", code);
+        assert!(report.contains("in 'c' called at [line 6]"));
+        assert!(report.contains("in 'a' called at [line 12]"));
+
+        // Errors outside of functions have no trace
+        let nodes = Parser::default().parse(Lexer::new().tokenize("print 1 / 0").unwrap()).unwrap();
+        assert!(interpreter.interpret(&nodes, HashMap::new()).err().unwrap().trace.is_empty());
     }
 }
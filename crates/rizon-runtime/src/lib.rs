@@ -3,6 +3,7 @@ pub mod values;
 pub mod environment;
 pub mod callable;
 pub mod native_functions;
+pub mod repl;
 
 extern crate rizon_frontend;
 extern crate rizon_tools;
\ No newline at end of file
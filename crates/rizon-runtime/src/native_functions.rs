@@ -1,20 +1,66 @@
 use colored::*;
 use ecow::EcoString;
-use std::{cell::RefCell, fmt, rc::Rc};
+use std::{cell::RefCell, cmp::Ordering, fmt, rc::Rc};
 use thiserror::Error;
 
 use crate::{
-    callable::{CallRes, Callable},
+    callable::{CallErr, CallRes, Callable},
     interpreter::Interpreter,
     values::RtVal,
 };
-use rizon_tools::results::RizonReport;
+use rizon_static_analyzer::static_analyzer::{format_segments, plural, plural_given};
+use rizon_tools::results::{RizonReport, RizonResult};
 
 
 #[derive(Debug, Error)]
 pub enum NativeFnErr {
     #[error("time access failed")]
     GetTime,
+
+    #[error("{0}")]
+    Operation(String),
+
+    #[error("can't take the square root of negative number {0}")]
+    NegativeSqrt(f64),
+
+    #[error("integer overflow in absolute value of {0}")]
+    AbsOverflow(i64),
+
+    #[error("function '{0}' expects a numeric argument")]
+    NonNumericArg(EcoString),
+
+    #[error("function '{0}' expects {1} argument")]
+    WrongArgType(EcoString, EcoString),
+
+    #[error("substring from {0} of length {1} is out of range for string of length {2}")]
+    SubstrOutOfRange(i64, i64, usize),
+
+    #[error("separator of 'split' can't be empty")]
+    EmptySeparator,
+
+    #[error("lower bound {0} of 'clamp' is greater than upper bound {1}")]
+    InvalidClampBounds(String, String),
+
+    #[error("function '{0}' can't be called on an empty array")]
+    EmptyArray(EcoString),
+
+    #[error("can't convert value '{0}' to type '{1}'")]
+    Conversion(String, EcoString),
+
+    #[error("entries of 'to_map' must be [key, value] arrays")]
+    InvalidMapEntry,
+
+    #[error("debug assertion failed")]
+    DebugAssertFailed,
+
+    #[error("end of input reached")]
+    EndOfInput,
+
+    #[error("failed to read input: {0}")]
+    ReadInput(String),
+
+    #[error("format string has {} but {} given", plural(*.0, "placeholder"), plural_given(*.1))]
+    FormatArgMismatch(usize, usize),
 }
 
 impl RizonReport for NativeFnErr {
@@ -59,3 +105,596 @@ impl fmt::Display for RizonNativeFn {
         write!(f, "<native fn>")
     }
 }
+
+
+// -------------
+//  Math module
+// -------------
+pub fn math_functions() -> Vec<RizonNativeFn> {
+    vec![
+        RizonNativeFn {
+            name: "mod_euclid".into(),
+            arity: 2,
+            func: |_, args| {
+                args[0]
+                    .borrow()
+                    .mod_euclid(&args[1].borrow())
+                    .map(|v| v.into())
+                    .map_err(|e| RizonResult::new(NativeFnErr::Operation(e.to_string()).into(), None))
+            },
+        },
+        RizonNativeFn {
+            name: "sqrt".into(),
+            arity: 1,
+            func: |_, args| {
+                let value = float_arg("sqrt", &args[0])?;
+
+                if value < 0. {
+                    return Err(RizonResult::new(NativeFnErr::NegativeSqrt(value).into(), None));
+                }
+
+                Ok(RtVal::new_float(value.sqrt()).into())
+            },
+        },
+        RizonNativeFn {
+            name: "pow".into(),
+            arity: 2,
+            func: |_, args| {
+                let base = float_arg("pow", &args[0])?;
+                let exp = float_arg("pow", &args[1])?;
+
+                Ok(RtVal::new_float(base.powf(exp)).into())
+            },
+        },
+        RizonNativeFn {
+            name: "abs".into(),
+            arity: 1,
+            func: |_, args| match &*args[0].borrow() {
+                RtVal::IntVal(i) => match i.value.checked_abs() {
+                    Some(v) => Ok(RtVal::new_int(v).into()),
+                    None => Err(RizonResult::new(NativeFnErr::AbsOverflow(i.value).into(), None)),
+                },
+                RtVal::FloatVal(f) => Ok(RtVal::new_float(f.value.abs()).into()),
+                _ => Err(RizonResult::new(NativeFnErr::NonNumericArg("abs".into()).into(), None)),
+            },
+        },
+        RizonNativeFn {
+            name: "floor".into(),
+            arity: 1,
+            func: |_, args| Ok(RtVal::new_int(float_arg("floor", &args[0])?.floor() as i64).into()),
+        },
+        RizonNativeFn {
+            name: "ceil".into(),
+            arity: 1,
+            func: |_, args| Ok(RtVal::new_int(float_arg("ceil", &args[0])?.ceil() as i64).into()),
+        },
+        RizonNativeFn {
+            name: "round".into(),
+            arity: 1,
+            func: |_, args| Ok(RtVal::new_int(float_arg("round", &args[0])?.round() as i64).into()),
+        },
+        RizonNativeFn {
+            name: "max".into(),
+            arity: 2,
+            func: |_, args| numeric_extremum("max", &args, Ordering::Greater),
+        },
+        RizonNativeFn {
+            name: "min".into(),
+            arity: 2,
+            func: |_, args| numeric_extremum("min", &args, Ordering::Less),
+        },
+        RizonNativeFn {
+            name: "clamp".into(),
+            arity: 3,
+            func: |_, args| {
+                let (value, lo, hi) = (&*args[0].borrow(), &*args[1].borrow(), &*args[2].borrow());
+
+                if let (RtVal::IntVal(v), RtVal::IntVal(l), RtVal::IntVal(h)) = (value, lo, hi) {
+                    if l.value > h.value {
+                        return Err(RizonResult::new(
+                            NativeFnErr::InvalidClampBounds(lo.to_string(), hi.to_string()).into(),
+                            None,
+                        ));
+                    }
+
+                    return Ok(RtVal::new_int(v.value.max(l.value).min(h.value)).into());
+                }
+
+                let (v, l, h) = (
+                    float_arg("clamp", &args[0])?,
+                    float_arg("clamp", &args[1])?,
+                    float_arg("clamp", &args[2])?,
+                );
+
+                // Also rejects NaN bounds
+                if !matches!(l.partial_cmp(&h), Some(Ordering::Less | Ordering::Equal)) {
+                    return Err(RizonResult::new(
+                        NativeFnErr::InvalidClampBounds(lo.to_string(), hi.to_string()).into(),
+                        None,
+                    ));
+                }
+
+                Ok(RtVal::new_float(v.max(l).min(h)).into())
+            },
+        },
+        RizonNativeFn {
+            name: "max_of".into(),
+            arity: 1,
+            func: |_, args| array_extremum("max_of", &args[0], Ordering::Greater),
+        },
+        RizonNativeFn {
+            name: "min_of".into(),
+            arity: 1,
+            func: |_, args| array_extremum("min_of", &args[0], Ordering::Less),
+        },
+    ]
+}
+
+// Argument that compares with 'ord' to the other one. Ints stay ints,
+// a float argument makes the result a float
+fn numeric_extremum(fn_name: &str, args: &[Rc<RefCell<RtVal>>], ord: Ordering) -> CallRes {
+    if let (RtVal::IntVal(a), RtVal::IntVal(b)) = (&*args[0].borrow(), &*args[1].borrow()) {
+        let res = if b.value.cmp(&a.value) == ord { b.value } else { a.value };
+
+        return Ok(RtVal::new_int(res).into());
+    }
+
+    let (a, b) = (float_arg(fn_name, &args[0])?, float_arg(fn_name, &args[1])?);
+    let res = if b.partial_cmp(&a) == Some(ord) { b } else { a };
+
+    Ok(RtVal::new_float(res).into())
+}
+
+// Element of a numeric array that compares with 'ord' to all the others
+fn array_extremum(fn_name: &str, value: &Rc<RefCell<RtVal>>, ord: Ordering) -> CallRes {
+    let RtVal::ArrayVal(arr) = &*value.borrow() else {
+        return Err(RizonResult::new(
+            NativeFnErr::WrongArgType(fn_name.into(), "an array".into()).into(),
+            None,
+        ));
+    };
+
+    let mut res: Option<RtVal> = None;
+
+    for v in &arr.values {
+        let v = v.borrow().clone();
+
+        let replace = match (&res, &v) {
+            (None, RtVal::IntVal(_) | RtVal::FloatVal(_)) => true,
+            (Some(RtVal::IntVal(r)), RtVal::IntVal(i)) => i.value.cmp(&r.value) == ord,
+            (Some(r), RtVal::IntVal(_) | RtVal::FloatVal(_)) => {
+                let r = float_arg(fn_name, &r.clone().into())?;
+                let f = float_arg(fn_name, &v.clone().into())?;
+
+                f.partial_cmp(&r) == Some(ord)
+            }
+            _ => return Err(RizonResult::new(NativeFnErr::NonNumericArg(fn_name.into()).into(), None)),
+        };
+
+        if replace {
+            res = Some(v);
+        }
+    }
+
+    match res {
+        Some(v) => Ok(v.into()),
+        None => Err(RizonResult::new(NativeFnErr::EmptyArray(fn_name.into()).into(), None)),
+    }
+}
+
+// ---------------
+//  String module
+// ---------------
+pub fn string_functions() -> Vec<RizonNativeFn> {
+    vec![
+        RizonNativeFn {
+            name: "substr".into(),
+            arity: 3,
+            func: |_, args| {
+                let s = str_arg("substr", &args[0])?;
+                let start = int_arg("substr", &args[1])?;
+                let len = int_arg("substr", &args[2])?;
+
+                let char_nb = s.chars().count();
+                let in_range = start >= 0 && len >= 0 && (start + len) as usize <= char_nb;
+
+                if !in_range {
+                    return Err(RizonResult::new(
+                        NativeFnErr::SubstrOutOfRange(start, len, char_nb).into(),
+                        None,
+                    ));
+                }
+
+                let sub: String = s.chars().skip(start as usize).take(len as usize).collect();
+
+                Ok(RtVal::new_str(sub.into()).into())
+            },
+        },
+        RizonNativeFn {
+            name: "index_of".into(),
+            arity: 2,
+            func: |_, args| {
+                let s = str_arg("index_of", &args[0])?;
+                let needle = str_arg("index_of", &args[1])?;
+
+                // Index in characters, -1 if not found
+                let idx = match s.find(needle.as_str()) {
+                    Some(i) => s[..i].chars().count() as i64,
+                    None => -1,
+                };
+
+                Ok(RtVal::new_int(idx).into())
+            },
+        },
+        RizonNativeFn {
+            name: "to_upper".into(),
+            arity: 1,
+            func: |_, args| {
+                Ok(RtVal::new_str(str_arg("to_upper", &args[0])?.to_uppercase()).into())
+            },
+        },
+        RizonNativeFn {
+            name: "to_lower".into(),
+            arity: 1,
+            func: |_, args| {
+                Ok(RtVal::new_str(str_arg("to_lower", &args[0])?.to_lowercase()).into())
+            },
+        },
+        RizonNativeFn {
+            name: "split".into(),
+            arity: 2,
+            func: |_, args| {
+                let s = str_arg("split", &args[0])?;
+                let sep = str_arg("split", &args[1])?;
+
+                if sep.is_empty() {
+                    return Err(RizonResult::new(NativeFnErr::EmptySeparator.into(), None));
+                }
+
+                let parts = s
+                    .split(sep.as_str())
+                    .map(|p| RtVal::new_str(p.into()).into())
+                    .collect();
+
+                Ok(RtVal::new_array(parts).into())
+            },
+        },
+        // Variadic, the interpreter doesn't check its arity
+        RizonNativeFn {
+            name: "format".into(),
+            arity: 1,
+            func: |_, args| {
+                let fmt = str_arg("format", &args[0])?;
+                let values = &args[1..];
+
+                let mut parts = format_segments(&fmt).into_iter();
+                let placeholders = parts.len() - 1;
+
+                if placeholders != values.len() {
+                    return Err(RizonResult::new(
+                        NativeFnErr::FormatArgMismatch(placeholders, values.len()).into(),
+                        None,
+                    ));
+                }
+
+                let mut res = parts.next().unwrap_or_default();
+                for (part, value) in parts.zip(values) {
+                    res.push_str(&display_str(&value.borrow()));
+                    res.push_str(&part);
+                }
+
+                Ok(RtVal::new_str(res.into()).into())
+            },
+        },
+    ]
+}
+
+// -------------------
+//  Conversion module
+// -------------------
+pub fn conversion_functions() -> Vec<RizonNativeFn> {
+    vec![
+        RizonNativeFn {
+            name: "to_str".into(),
+            arity: 1,
+            func: |_, args| Ok(RtVal::new_str(display_str(&args[0].borrow())).into()),
+        },
+        RizonNativeFn {
+            name: "repr".into(),
+            arity: 1,
+            func: |_, args| Ok(RtVal::new_str(args[0].borrow().repr().into()).into()),
+        },
+        RizonNativeFn {
+            name: "to_int".into(),
+            arity: 1,
+            func: |_, args| {
+                let value = args[0].borrow();
+
+                let res = match &*value {
+                    RtVal::IntVal(i) => Some(i.value),
+                    RtVal::FloatVal(f) => Some(f.value as i64),
+                    RtVal::BoolVal(b) => Some(b.value as i64),
+                    // Code point of the character
+                    RtVal::CharVal(c) => Some(c.value as i64),
+                    RtVal::StrVal(s) => s.value.trim().parse::<i64>().ok(),
+                    _ => None,
+                };
+
+                match res {
+                    Some(i) => Ok(RtVal::new_int(i).into()),
+                    None => Err(RizonResult::new(
+                        NativeFnErr::Conversion(value.to_string(), "int".into()).into(),
+                        None,
+                    )),
+                }
+            },
+        },
+        RizonNativeFn {
+            name: "to_float".into(),
+            arity: 1,
+            func: |_, args| {
+                let value = args[0].borrow();
+
+                let res = match &*value {
+                    RtVal::IntVal(i) => Some(i.value as f64),
+                    RtVal::FloatVal(f) => Some(f.value),
+                    RtVal::BoolVal(b) => Some(b.value as i64 as f64),
+                    RtVal::StrVal(s) => s.value.trim().parse::<f64>().ok(),
+                    _ => None,
+                };
+
+                match res {
+                    Some(f) => Ok(RtVal::new_float(f).into()),
+                    None => Err(RizonResult::new(
+                        NativeFnErr::Conversion(value.to_string(), "float".into()).into(),
+                        None,
+                    )),
+                }
+            },
+        },
+    ]
+}
+
+// -----------
+//  IO module
+// -----------
+pub fn io_functions() -> Vec<RizonNativeFn> {
+    vec![
+        RizonNativeFn {
+            name: "read_line".into(),
+            arity: 0,
+            func: |interp, _| {
+                let line = interp.read_line()?;
+
+                Ok(RtVal::new_str(line.into()).into())
+            },
+        },
+    ]
+}
+
+// -------------------
+//  Collection module
+// -------------------
+pub fn collection_functions() -> Vec<RizonNativeFn> {
+    vec![
+        RizonNativeFn {
+            name: "len".into(),
+            arity: 1,
+            func: |_, args| {
+                let len = match &*args[0].borrow() {
+                    RtVal::StrVal(s) => s.value.chars().count(),
+                    RtVal::ArrayVal(a) => a.values.len(),
+                    RtVal::MapVal(m) => m.entries.len(),
+                    RtVal::RangeVal(r) => r.len(),
+                    v => {
+                        return Err(RizonResult::new(
+                            NativeFnErr::Operation(format!("can't get the length of value '{}'", v)).into(),
+                            None,
+                        ))
+                    }
+                };
+
+                Ok(RtVal::new_int(len as i64).into())
+            },
+        },
+        RizonNativeFn {
+            name: "map".into(),
+            arity: 2,
+            func: |interp, args| {
+                let values = match &*args[0].borrow() {
+                    RtVal::ArrayVal(a) => a.values.clone(),
+                    _ => {
+                        return Err(RizonResult::new(
+                            NativeFnErr::WrongArgType("map".into(), "an array".into()).into(),
+                            None,
+                        ))
+                    }
+                };
+
+                let callback = args[1].borrow().clone();
+
+                // Same path as the calls from the code, limits apply
+                let callable: Box<&dyn Callable> = match &callback {
+                    RtVal::FuncVal(f) => Box::new(f),
+                    RtVal::NativeFnVal(f) => Box::new(f),
+                    _ => {
+                        return Err(RizonResult::new(
+                            NativeFnErr::WrongArgType("map".into(), "a function".into()).into(),
+                            None,
+                        ))
+                    }
+                };
+
+                let mapped = values
+                    .iter()
+                    .map(|v| callable.call(interp, vec![v.borrow().clone().into()]))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(RtVal::new_array(mapped).into())
+            },
+        },
+        // Maps keep their insertion order, so do their keys
+        RizonNativeFn {
+            name: "keys".into(),
+            arity: 1,
+            func: |_, args| match args[0].borrow().as_map_iter() {
+                Some(entries) => Ok(RtVal::new_array(entries.map(|(k, _)| k.into()).collect()).into()),
+                None => Err(RizonResult::new(
+                    NativeFnErr::WrongArgType("keys".into(), "a map".into()).into(),
+                    None,
+                )),
+            },
+        },
+        // Entries are [key, value] arrays, in insertion order
+        RizonNativeFn {
+            name: "to_array".into(),
+            arity: 1,
+            func: |_, args| match args[0].borrow().as_map_iter() {
+                Some(entries) => Ok(RtVal::new_array(
+                    entries
+                        .map(|(k, v)| RtVal::new_array(vec![k.into(), v.into()]).into())
+                        .collect(),
+                )
+                .into()),
+                None => Err(RizonResult::new(
+                    NativeFnErr::WrongArgType("to_array".into(), "a map".into()).into(),
+                    None,
+                )),
+            },
+        },
+        // Duplicated keys keep the last value
+        RizonNativeFn {
+            name: "to_map".into(),
+            arity: 1,
+            func: |_, args| {
+                let pairs = match &*args[0].borrow() {
+                    RtVal::ArrayVal(a) => a.values.clone(),
+                    _ => {
+                        return Err(RizonResult::new(
+                            NativeFnErr::WrongArgType("to_map".into(), "an array".into()).into(),
+                            None,
+                        ))
+                    }
+                };
+
+                let entries = pairs
+                    .iter()
+                    .map(|p| match &*p.borrow() {
+                        RtVal::ArrayVal(a) if a.values.len() == 2 => {
+                            Ok((a.values[0].borrow().clone(), a.values[1].borrow().clone().into()))
+                        }
+                        _ => Err(RizonResult::new(NativeFnErr::InvalidMapEntry.into(), None)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(RtVal::new_map(entries).into())
+            },
+        },
+        RizonNativeFn {
+            name: "remove".into(),
+            arity: 2,
+            func: |_, args| {
+                // The key can be the map itself
+                let key = args[1].borrow().clone();
+
+                match &mut *args[0].borrow_mut() {
+                    RtVal::MapVal(m) => {
+                        m.remove(&key).map_err(|e| {
+                            RizonResult::new(NativeFnErr::Operation(e.to_string()).into(), None)
+                        })?;
+
+                        Ok(RtVal::new_null())
+                    }
+                    _ => Err(RizonResult::new(
+                        NativeFnErr::WrongArgType("remove".into(), "a map".into()).into(),
+                        None,
+                    )),
+                }
+            },
+        },
+        // Return their argument: var p = freeze(Point(1, 2))
+        RizonNativeFn {
+            name: "freeze".into(),
+            arity: 1,
+            func: |_, args| {
+                args[0].borrow_mut().freeze(false);
+                Ok(args[0].clone())
+            },
+        },
+        RizonNativeFn {
+            name: "deep_freeze".into(),
+            arity: 1,
+            func: |_, args| {
+                args[0].borrow_mut().freeze(true);
+                Ok(args[0].clone())
+            },
+        },
+    ]
+}
+
+// --------------
+//  Debug module
+// --------------
+pub fn debug_functions() -> Vec<RizonNativeFn> {
+    vec![
+        RizonNativeFn {
+            name: "debug_assert".into(),
+            arity: 1,
+            func: |_, args| match &*args[0].borrow() {
+                RtVal::BoolVal(b) if b.value => Ok(RtVal::new_null()),
+                _ => Err(RizonResult::new(NativeFnErr::DebugAssertFailed.into(), None)),
+            },
+        },
+        // The message is optional, calls are checked in 'visit_call_expr'
+        // to report the assertion location
+        RizonNativeFn {
+            name: "assert".into(),
+            arity: 1,
+            func: |_, _| unreachable!("'assert' calls are evaluated by the interpreter"),
+        },
+        // Calls are evaluated in 'visit_call_expr' to print the argument source
+        RizonNativeFn {
+            name: "dbg".into(),
+            arity: 1,
+            func: |_, _| unreachable!("'dbg' calls are evaluated by the interpreter"),
+        },
+    ]
+}
+
+// Same representation as 'print', except for strings and chars that are kept as is
+fn display_str(value: &RtVal) -> EcoString {
+    match value {
+        RtVal::StrVal(s) => s.value.clone(),
+        RtVal::CharVal(c) => c.value.into(),
+        v => v.to_string().into(),
+    }
+}
+
+fn str_arg(fn_name: &str, value: &Rc<RefCell<RtVal>>) -> Result<EcoString, RizonResult<CallErr>> {
+    match &*value.borrow() {
+        RtVal::StrVal(s) => Ok(s.value.clone()),
+        _ => Err(RizonResult::new(
+            NativeFnErr::WrongArgType(fn_name.into(), "a string".into()).into(),
+            None,
+        )),
+    }
+}
+
+fn int_arg(fn_name: &str, value: &Rc<RefCell<RtVal>>) -> Result<i64, RizonResult<CallErr>> {
+    match &*value.borrow() {
+        RtVal::IntVal(i) => Ok(i.value),
+        _ => Err(RizonResult::new(
+            NativeFnErr::WrongArgType(fn_name.into(), "an int".into()).into(),
+            None,
+        )),
+    }
+}
+
+// Ints are accepted where floats are expected
+fn float_arg(fn_name: &str, value: &Rc<RefCell<RtVal>>) -> Result<f64, RizonResult<CallErr>> {
+    match &*value.borrow() {
+        RtVal::IntVal(i) => Ok(i.value as f64),
+        RtVal::FloatVal(f) => Ok(f.value),
+        _ => Err(RizonResult::new(NativeFnErr::NonNumericArg(fn_name.into()).into(), None)),
+    }
+}
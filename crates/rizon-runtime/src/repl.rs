@@ -0,0 +1,203 @@
+use std::{collections::{HashMap, HashSet}, fmt::Display};
+
+use ecow::EcoString;
+use rizon_frontend::{ast::stmt::Stmt, fold::fold_constants, lexer::Lexer, normalize::normalize, parser::Parser};
+use rizon_static_analyzer::StaticAnalyzer;
+use rizon_tools::results::Loc;
+
+use crate::{interpreter::Interpreter, values::RtVal};
+
+
+// Name used for the code location in reports
+const REPL_SOURCE: &str = "repl";
+
+// Result of one input, reports are errors and warnings
+#[derive(Debug, Default)]
+pub struct ReplOutput {
+    pub reports: Vec<String>,
+    pub value: Option<RtVal>,
+}
+
+impl Display for ReplOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = self.reports.clone();
+
+        if let Some(v) = &self.value {
+            lines.push(v.to_string());
+        }
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+// Interactive session, declarations of previous inputs stay
+// available to the following ones
+pub struct Repl {
+    static_analyzer: StaticAnalyzer,
+    interpreter: Interpreter,
+    // Accumulated over all the inputs, functions declared in
+    // a previous input still use theirs
+    locals: HashMap<Loc, usize>,
+    features: HashSet<EcoString>,
+    // All the inputs, one per line. Locations of each input start where
+    // it is in this source, so that they never collide with previous ones
+    source: String,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new(Interpreter::new())
+    }
+}
+
+impl Repl {
+    pub fn new(interpreter: Interpreter) -> Self {
+        Self {
+            static_analyzer: StaticAnalyzer::default(),
+            interpreter,
+            locals: HashMap::new(),
+            features: HashSet::new(),
+            source: String::new(),
+        }
+    }
+
+    pub fn set_features(&mut self, features: HashSet<EcoString>) {
+        self.features = features;
+    }
+
+    pub fn feed(&mut self, line: &str) -> ReplOutput {
+        let mut output = ReplOutput::default();
+
+        let mut lexer = Lexer::new();
+        lexer.set_features(self.features.clone());
+
+        let mut tokens = match lexer.tokenize(line) {
+            Ok(tk) => tk,
+            Err(e) => {
+                output.reports = e.iter().map(|e| e.format_report(REPL_SOURCE, line)).collect();
+                return output
+            }
+        };
+
+        if !self.source.is_empty() {
+            self.source.push('\n');
+        }
+
+        let offset = self.source.chars().count();
+        let byte_offset = self.source.len() as u32;
+        self.source.push_str(line);
+
+        for tk in tokens.iter_mut() {
+            tk.loc.start += offset;
+            tk.loc.end += offset;
+            tk.loc.byte_start += byte_offset;
+            tk.loc.byte_end += byte_offset;
+        }
+
+        let source = self.source.as_str();
+
+        let nodes = match Parser::default().parse(tokens) {
+            Ok(n) => normalize(n),
+            Err(e) => {
+                output.reports = e.iter().map(|e| e.format_report(REPL_SOURCE, source)).collect();
+                return output
+            }
+        };
+
+        let resolved = self.static_analyzer.resolve(&nodes);
+
+        output.reports = self
+            .static_analyzer
+            .take_warnings()
+            .iter()
+            .map(|w| w.format_report(REPL_SOURCE, source))
+            .collect();
+
+        match resolved {
+            Ok(l) => {
+                self.locals.extend(l);
+                self.interpreter.widened.extend(self.static_analyzer.take_widened());
+            }
+            Err(e) => {
+                output.reports.extend(e.iter().map(|e| e.format_report(REPL_SOURCE, source)));
+                return output
+            }
+        }
+
+        let nodes = fold_constants(nodes);
+        self.interpreter.set_source(source);
+
+        match self.interpreter.interpret(&nodes, self.locals.clone()) {
+            // Only expression statements have a printable result
+            Ok(res) if matches!(nodes.last(), Some(Stmt::Expr(_))) => {
+                let value = res.borrow().clone();
+
+                if value != RtVal::Null {
+                    output.value = Some(value);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => output.reports.push(e.format_report(REPL_SOURCE, source)),
+        }
+
+        output
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Repl;
+    use crate::values::RtVal;
+
+    #[test]
+    fn keeps_declarations_between_inputs() {
+        let mut repl = Repl::default();
+
+        assert_eq!(repl.feed("var x = 1").value, None);
+        assert_eq!(repl.feed("x + 1").value, Some(RtVal::new_int(2)));
+
+        repl.feed("fn add(a: int, b: int) -> int { return a + b + x }");
+        assert_eq!(repl.feed("add(2, 3)").value, Some(RtVal::new_int(6)));
+    }
+
+    #[test]
+    fn int_elements_of_float_arrays_are_widened() {
+        let mut repl = Repl::default();
+
+        repl.feed("var values = [1.5]");
+        repl.feed("values[0] = 2");
+        assert_eq!(repl.feed("values[0]").value, Some(RtVal::new_float(2.)));
+    }
+
+    #[test]
+    fn locations_of_previous_inputs_dont_apply_to_new_ones() {
+        let mut repl = Repl::default();
+
+        repl.feed("var values = [1.5]");
+        repl.feed("values[0] = 2");
+        repl.feed("var ints = [1]");
+        repl.feed("ints[0]   = 2");
+
+        assert_eq!(repl.feed("ints[0]").value, Some(RtVal::new_int(2)));
+        assert_eq!(repl.feed("ints[0] is int").value, Some(RtVal::new_bool(true)));
+    }
+
+    #[test]
+    fn errors_dont_end_the_session() {
+        let mut repl = Repl::default();
+
+        repl.feed("var x = 1");
+
+        let output = repl.feed("var y =");
+        assert!(output.value.is_none());
+        assert_eq!(output.reports.len(), 1);
+
+        let output = repl.feed("x + \"a\"");
+        assert_eq!(output.reports.len(), 1);
+
+        let output = repl.feed("x");
+        assert!(output.reports.is_empty());
+        assert_eq!(output.to_string(), "1");
+    }
+}
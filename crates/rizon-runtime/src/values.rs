@@ -1,6 +1,7 @@
 use colored::*;
 use ecow::EcoString;
-use rizon_frontend::ast::stmt::{BlockStmt, FnDeclStmt, StructStmt};
+use rizon_frontend::ast::expr::Expr;
+use rizon_frontend::ast::stmt::{BlockStmt, EnumDeclStmt, FnDeclStmt, StructStmt, VarTypeDecl};
 use std::{
     cell::RefCell,
     collections::{
@@ -39,6 +40,9 @@ pub enum RtValErr {
     #[error("operator '{0}' is not supported for string manipulation")]
     StringManip(String),
 
+    #[error("can't repeat a string {0} times")]
+    InvalidRepeatCount(i64),
+
     // Instance
     #[error("structure has no field '{0}'")]
     MissingFieldInStruct(EcoString),
@@ -46,6 +50,46 @@ pub enum RtValErr {
     #[error("assigning to method is not allowed")]
     AssignToMethod,
 
+    // Arrays and maps
+    #[error("index {0} is out of range for array of length {1}")]
+    IndexOutOfRange(i64, usize),
+
+    #[error("key {0} doesn't exist in map")]
+    MissingMapKey(String),
+
+    #[error("integer overflow in addition")]
+    AddOverflow,
+
+    #[error("integer overflow in subtraction")]
+    SubOverflow,
+
+    #[error("division by zero")]
+    DivisionByZero,
+
+    #[error("integer overflow in division")]
+    DivisionOverflow,
+
+    #[error("modulo by zero")]
+    ModuloByZero,
+
+    #[error("integer overflow in modulo")]
+    ModuloOverflow,
+
+    // Freeze
+    #[error("modification of frozen value")]
+    FrozenModification,
+
+    // Power
+    #[error("zero can't be raised to a negative power")]
+    NegativePowOfZero,
+
+    #[error("integer overflow in power")]
+    PowOverflow,
+
+    // Cast
+    #[error("can't cast value '{0}' to type '{1}'")]
+    InvalidCast(String, EcoString),
+
     // Others
     #[error("can't use a null value in a binary operation")]
     OperationOnNull,
@@ -69,10 +113,16 @@ pub enum RtVal {
     FloatVal(Float),
     StrVal(Str),
     BoolVal(Bool),
+    CharVal(Char),
     FuncVal(Function),
     NativeFnVal(RizonNativeFn),
     StructVal(Rc<RefCell<Struct>>),
     InstanceVal(Instance),
+    EnumVal(Rc<Enum>),
+    EnumVariantVal(EnumVariant),
+    ArrayVal(Array),
+    MapVal(Map),
+    RangeVal(Range),
     Null,
 }
 
@@ -100,20 +150,81 @@ impl RtVal {
             (RtVal::StrVal(s1), RtVal::IntVal(i1)) => s1.operate(i1, operator),
             (RtVal::IntVal(i1), RtVal::StrVal(s1)) => i1.operate(s1, operator),
             (RtVal::BoolVal(b1), RtVal::BoolVal(b2)) => b1.operate(b2, operator),
+            (RtVal::CharVal(c1), RtVal::CharVal(c2)) => c1.operate(c2, operator),
             (RtVal::StructVal(s1), RtVal::StructVal(s2)) => {
                 s1.borrow().operate(&*s2.borrow(), operator)
             },
+            (RtVal::EnumVariantVal(v1), RtVal::EnumVariantVal(v2)) => v1.operate(v2, operator),
+            (RtVal::InstanceVal(i1), RtVal::InstanceVal(i2)) => i1.operate(i2, operator),
+            (RtVal::ArrayVal(a1), RtVal::ArrayVal(a2)) => a1.operate(a2, operator),
+            (RtVal::MapVal(m1), RtVal::MapVal(m2)) => m1.operate(m2, operator),
+            (RtVal::Null, _) | (_, RtVal::Null) => Err(RtValErr::OperationOnNull),
+            _ => Err(RtValErr::UnknownOperation),
+        }
+    }
+
+    // Euclidean modulo, the result is never negative: mod_euclid(-7, 3) == 2
+    // and mod_euclid(7, -3) == 1
+    pub fn mod_euclid(&self, rhs: &RtVal) -> Result<RtVal, RtValErr> {
+        match (self, rhs) {
+            (RtVal::IntVal(_), RtVal::IntVal(i2)) if i2.value == 0 => Err(RtValErr::ModuloByZero),
+            (RtVal::IntVal(i1), RtVal::IntVal(i2)) => i1
+                .value
+                .checked_rem_euclid(i2.value)
+                .map(RtVal::new_int)
+                .ok_or(RtValErr::ModuloOverflow),
+            (RtVal::IntVal(i1), RtVal::FloatVal(r1)) => {
+                Ok((i1.value as f64).rem_euclid(r1.value).into())
+            }
+            (RtVal::FloatVal(r1), RtVal::IntVal(i1)) => {
+                Ok(r1.value.rem_euclid(i1.value as f64).into())
+            }
+            (RtVal::FloatVal(r1), RtVal::FloatVal(r2)) => Ok(r1.value.rem_euclid(r2.value).into()),
             (RtVal::Null, _) | (_, RtVal::Null) => Err(RtValErr::OperationOnNull),
             _ => Err(RtValErr::UnknownOperation),
         }
     }
 
+    // Floored modulo like in Python, the result has the sign of the
+    // divisor: mod_floor(-7, 3) == 2 and mod_floor(7, -3) == -2
+    pub fn mod_floor(&self, rhs: &RtVal) -> Result<RtVal, RtValErr> {
+        match (self, rhs) {
+            (RtVal::IntVal(_), RtVal::IntVal(i2)) if i2.value == 0 => Err(RtValErr::ModuloByZero),
+            (RtVal::IntVal(i1), RtVal::IntVal(i2)) => {
+                let rem = i1.value.checked_rem(i2.value).ok_or(RtValErr::ModuloOverflow)?;
+
+                // Can't overflow, both have opposite signs
+                if rem != 0 && (rem < 0) != (i2.value < 0) {
+                    Ok(RtVal::new_int(rem + i2.value))
+                } else {
+                    Ok(RtVal::new_int(rem))
+                }
+            }
+            (RtVal::IntVal(i1), RtVal::FloatVal(r1)) => Ok(float_mod_floor(i1.value as f64, r1.value).into()),
+            (RtVal::FloatVal(r1), RtVal::IntVal(i1)) => Ok(float_mod_floor(r1.value, i1.value as f64).into()),
+            (RtVal::FloatVal(r1), RtVal::FloatVal(r2)) => Ok(float_mod_floor(r1.value, r2.value).into()),
+            (RtVal::Null, _) | (_, RtVal::Null) => Err(RtValErr::OperationOnNull),
+            _ => Err(RtValErr::UnknownOperation),
+        }
+    }
+
+    // Conversion between numeric types, floats are truncated: 3.9 as int == 3
+    pub fn cast(&self, typ: &EcoString) -> Result<RtVal, RtValErr> {
+        match (self, typ.as_str()) {
+            (RtVal::IntVal(i), "float") => Ok(RtVal::new_float(i.value as f64)),
+            (RtVal::FloatVal(f), "int") => Ok(RtVal::new_int(f.value as i64)),
+            (v, t) if v.is_of_type(typ) == RtVal::new_bool(true) || t == "any" => Ok(v.clone()),
+            (v, _) => Err(RtValErr::InvalidCast(v.to_string(), typ.clone())),
+        }
+    }
+
     pub fn is_of_type(&self, typ: &EcoString) -> RtVal {
         match (self, typ.as_str()) {
             (RtVal::IntVal(_), "int") => RtVal::new_bool(true),
             (RtVal::FloatVal(_), "float") => RtVal::new_bool(true),
             (RtVal::StrVal(_), "str") => RtVal::new_bool(true),
             (RtVal::BoolVal(_), "bool") => RtVal::new_bool(true),
+            (RtVal::CharVal(_), "char") => RtVal::new_bool(true),
             (RtVal::StructVal(s), t) => {
                 if s.borrow().name == t {
                     RtVal::new_bool(true)
@@ -128,6 +239,8 @@ impl RtVal {
                     RtVal::new_bool(false)
                 }
             },
+            (RtVal::EnumVal(e), t) => RtVal::new_bool(e.name == t),
+            (RtVal::EnumVariantVal(v), t) => RtVal::new_bool(v.enum_name == t),
             (RtVal::Null, "null") => RtVal::new_bool(true),
             _ => RtVal::new_bool(false)
         }
@@ -154,14 +267,50 @@ impl Negate for Int {
     }
 }
 
+// Negative exponents give a float, like typed by the static analyzer.
+// Exponents too big for 'checked_pow' only fit for bases 0, 1 and -1
+fn int_pow(base: i64, exp: i64) -> Result<RtVal, RtValErr> {
+    if exp < 0 {
+        return float_pow(base as f64, exp as f64);
+    }
+
+    match (u32::try_from(exp), base) {
+        (Ok(e), _) => base.checked_pow(e).map(RtVal::new_int).ok_or(RtValErr::PowOverflow),
+        (Err(_), 0 | 1) => Ok(RtVal::new_int(base)),
+        (Err(_), -1) => Ok(RtVal::new_int(if exp % 2 == 0 { 1 } else { -1 })),
+        (Err(_), _) => Err(RtValErr::PowOverflow),
+    }
+}
+
+fn float_mod_floor(lhs: f64, rhs: f64) -> f64 {
+    let rem = lhs % rhs;
+
+    if rem != 0. && (rem < 0.) != (rhs < 0.) {
+        rem + rhs
+    } else {
+        rem
+    }
+}
+
+fn float_pow(base: f64, exp: f64) -> Result<RtVal, RtValErr> {
+    if base == 0. && exp < 0. {
+        return Err(RtValErr::NegativePowOfZero)
+    }
+
+    Ok(base.powf(exp).into())
+}
+
 impl Operate<Int> for Int {
     fn operate(&self, rhs: &Int, operator: &str) -> Result<RtVal, RtValErr> {
         match operator {
-            "+" => Ok((self.value + rhs.value).into()),
-            "-" => Ok((self.value - rhs.value).into()),
+            "+" => self.value.checked_add(rhs.value).map(RtVal::new_int).ok_or(RtValErr::AddOverflow),
+            "-" => self.value.checked_sub(rhs.value).map(RtVal::new_int).ok_or(RtValErr::SubOverflow),
             "*" => Ok((self.value * rhs.value).into()),
-            "/" => Ok((self.value / rhs.value).into()),
-            "%" => Ok((self.value % rhs.value).into()),
+            "/" | "~/" if rhs.value == 0 => Err(RtValErr::DivisionByZero),
+            "%" if rhs.value == 0 => Err(RtValErr::ModuloByZero),
+            "/" | "~/" => self.value.checked_div(rhs.value).map(RtVal::new_int).ok_or(RtValErr::DivisionOverflow),
+            "%" => self.value.checked_rem(rhs.value).map(RtVal::new_int).ok_or(RtValErr::ModuloOverflow),
+            "**" => int_pow(self.value, rhs.value),
             "<" => Ok((self.value < rhs.value).into()),
             ">" => Ok((self.value > rhs.value).into()),
             "<=" => Ok((self.value <= rhs.value).into()),
@@ -181,6 +330,7 @@ impl Operate<Float> for Int {
             "*" => Ok((self.value as f64 * rhs.value).into()),
             "/" => Ok((self.value as f64 / rhs.value).into()),
             "%" => Ok((self.value as f64 % rhs.value).into()),
+            "**" => float_pow(self.value as f64, rhs.value),
             "<" => Ok(((self.value as f64) < rhs.value).into()),
             ">" => Ok((self.value as f64 > rhs.value).into()),
             "<=" => Ok((self.value as f64 <= rhs.value).into()),
@@ -195,7 +345,7 @@ impl Operate<Float> for Int {
 impl Operate<Str> for Int {
     fn operate(&self, rhs: &Str, operator: &str) -> Result<RtVal, RtValErr> {
         match operator {
-            "*" => Ok(rhs.value.repeat(self.value as usize).into()),
+            "*" => repeat_str(&rhs.value, self.value),
             _ => Err(RtValErr::OpStrInt),
         }
     }
@@ -209,6 +359,20 @@ pub struct Float {
     pub value: f64,
 }
 
+// Special values are displayed the same way on every platform. Integral
+// values keep a decimal part to tell them apart from ints: 2.0, -0.0
+impl Display for Float {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.value {
+            v if v.is_nan() => write!(f, "nan"),
+            v if v == f64::INFINITY => write!(f, "inf"),
+            v if v == f64::NEG_INFINITY => write!(f, "-inf"),
+            v if v.fract() == 0. => write!(f, "{:.1}", v),
+            v => write!(f, "{}", v),
+        }
+    }
+}
+
 impl Negate for Float {
     fn negate(&mut self) {
         self.value *= -1.;
@@ -223,6 +387,7 @@ impl Operate<Int> for Float {
             "*" => Ok((self.value * rhs.value as f64).into()),
             "/" => Ok((self.value / rhs.value as f64).into()),
             "%" => Ok((self.value % rhs.value as f64).into()),
+            "**" => float_pow(self.value, rhs.value as f64),
             "<" => Ok((self.value < rhs.value as f64).into()),
             ">" => Ok((self.value > rhs.value as f64).into()),
             "<=" => Ok((self.value <= rhs.value as f64).into()),
@@ -242,6 +407,7 @@ impl Operate<Float> for Float {
             "*" => Ok((self.value * rhs.value).into()),
             "/" => Ok((self.value / rhs.value).into()),
             "%" => Ok((self.value % rhs.value).into()),
+            "**" => float_pow(self.value, rhs.value),
             "<" => Ok((self.value < rhs.value).into()),
             ">" => Ok((self.value > rhs.value).into()),
             "<=" => Ok((self.value <= rhs.value).into()),
@@ -265,6 +431,11 @@ impl Operate<Str> for Str {
     fn operate(&self, rhs: &Str, operator: &str) -> Result<RtVal, RtValErr> {
         match operator {
             "+" => Ok(EcoString::from(format!("{}{}", self.value, rhs.value)).into()),
+            // Lexicographic order
+            "<" => Ok((self.value < rhs.value).into()),
+            ">" => Ok((self.value > rhs.value).into()),
+            "<=" => Ok((self.value <= rhs.value).into()),
+            ">=" => Ok((self.value >= rhs.value).into()),
             "==" => Ok((self.value == rhs.value).into()),
             "!=" => Ok((self.value != rhs.value).into()),
             op => Err(RtValErr::StringManip(op.to_string())),
@@ -272,10 +443,18 @@ impl Operate<Str> for Str {
     }
 }
 
+// The length of the result must fit in memory addressing
+fn repeat_str(value: &str, count: i64) -> Result<RtVal, RtValErr> {
+    match usize::try_from(count).ok().and_then(|c| value.len().checked_mul(c)) {
+        Some(len) if len <= isize::MAX as usize => Ok(value.repeat(count as usize).into()),
+        _ => Err(RtValErr::InvalidRepeatCount(count)),
+    }
+}
+
 impl Operate<Int> for Str {
     fn operate(&self, rhs: &Int, operator: &str) -> Result<RtVal, RtValErr> {
         match operator {
-            "*" => Ok(self.value.repeat(rhs.value as usize).into()),
+            "*" => repeat_str(&self.value, rhs.value),
             _ => Err(RtValErr::OpStrInt),
         }
     }
@@ -307,6 +486,28 @@ impl Operate<Bool> for Bool {
     }
 }
 
+// --------
+//   Char
+// --------
+#[derive(Debug, PartialEq, Clone)]
+pub struct Char {
+    pub value: char,
+}
+
+impl Operate<Char> for Char {
+    fn operate(&self, rhs: &Char, operator: &str) -> Result<RtVal, RtValErr> {
+        match operator {
+            "<" => Ok((self.value < rhs.value).into()),
+            ">" => Ok((self.value > rhs.value).into()),
+            "<=" => Ok((self.value <= rhs.value).into()),
+            ">=" => Ok((self.value >= rhs.value).into()),
+            "==" => Ok((self.value == rhs.value).into()),
+            "!=" => Ok((self.value != rhs.value).into()),
+            op => Err(RtValErr::UnsupportedOpOnType(op.to_string(), "char".into())),
+        }
+    }
+}
+
 // ------------
 //   Function
 // ------------
@@ -314,6 +515,9 @@ impl Operate<Bool> for Bool {
 pub struct Function {
     pub name: EcoString,
     pub params: Rc<Vec<EcoString>>,
+    // Used to check arguments of calls made from the host
+    pub param_types: Rc<Vec<VarTypeDecl>>,
+    pub return_type: Option<Rc<VarTypeDecl>>,
     pub body: Arc<BlockStmt>,
     pub closure: Rc<RefCell<Env>>,
 }
@@ -323,11 +527,27 @@ impl Function {
         Self {
             name: stmt.name.value.clone(),
             params: Rc::new(stmt.params.iter().map(|p| p.name.value.clone()).collect()),
+            param_types: Rc::new(stmt.params.iter().map(|p| p.typ.clone()).collect()),
+            return_type: stmt.return_type.clone().map(Rc::new),
             body: stmt.body.clone(),
             closure: Rc::new(RefCell::new(Env::new(Some(closure)))),
         }
     }
 
+    fn bind_params(&self, interpreter: &Interpreter, args: Vec<Rc<RefCell<RtVal>>>) -> Result<Env, RizonResult<CallErr>> {
+        let mut env = Env::new(Some(self.closure.clone()));
+
+        for ((p, t), v) in self.params.iter().zip(self.param_types.iter()).zip(args) {
+            // Ints can be given for float parameters
+            let v = interpreter.coerce_to_type(v, t);
+
+            env.declare_var(p.clone(), v)
+                .map_err(|_| RizonResult::new(CallErr::WrongFnParamDecl, None))?;
+        }
+
+        Ok(env)
+    }
+
     pub fn wrap_bind(&self, instance: Rc<RefCell<RtVal>>) -> Rc<RefCell<RtVal>> {
         Rc::new(RefCell::new(RtVal::FuncVal(self.bind(instance))))
     }
@@ -342,6 +562,8 @@ impl Function {
         Function {
             name: self.name.clone(),
             params: self.params.clone(),
+            param_types: self.param_types.clone(),
+            return_type: self.return_type.clone(),
             body: self.body.clone(),
             closure: Rc::new(RefCell::new(env)),
         }
@@ -366,24 +588,38 @@ impl Callable for Function {
         interpreter: &mut Interpreter,
         args: Vec<Rc<RefCell<RtVal>>>,
     ) -> Result<Rc<RefCell<RtVal>>, RizonResult<CallErr>> {
-        let mut new_env = Env::new(Some(self.closure.clone()));
+        interpreter
+            .enter_call()
+            .map_err(|e| RizonResult::new(CallErr::FnExecution(e.to_string()), None))?;
+
+        // Tail calls are executed here, in the same frame
+        let mut func = self.clone();
+        let mut args = args;
+
+        let res = loop {
+            let new_env = match func.bind_params(interpreter, args) {
+                Ok(env) => env,
+                Err(e) => break Err(e),
+            };
+
+            match interpreter.execute_block_stmt(&func.body.stmts, new_env) {
+                Ok(_) => break Ok(RtVal::new_null()),
+                Err(e) => match e.err {
+                    InterpErr::TailCall(call) => (func, args) = *call,
+                    // Return types can only widen the value along tail calls (int to
+                    // float), the outermost function has the widest
+                    InterpErr::Return(v) => match &self.return_type {
+                        Some(t) => break Ok(interpreter.coerce_to_type(v, t)),
+                        None => break Ok(v),
+                    },
+                    _ => break Err(RizonResult::new(CallErr::FnExecution(e.err.to_string()), e.loc)),
+                },
+            }
+        };
 
-        for (p, v) in self.params.iter().zip(args) {
-            new_env
-                .declare_var(p.clone(), v)
-                .map_err(|_| RizonResult::new(CallErr::WrongFnParamDecl, None))?;
-        }
+        interpreter.exit_call();
 
-        match interpreter.execute_block_stmt(&self.body.stmts, new_env) {
-            Ok(_) => Ok(RtVal::new_null()),
-            Err(e) => match e.err {
-                InterpErr::Return(v) => Ok(v),
-                _ => Err(RizonResult::new(
-                    CallErr::FnExecution(e.err.to_string()),
-                    None,
-                )),
-            },
-        }
+        res
     }
 
     fn arity(&self) -> usize {
@@ -395,24 +631,72 @@ impl Callable for Function {
 // -------------
 //   Structure
 // -------------
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub struct Struct {
     pub name: EcoString,
+    // Fields without default value, null until assigned
     pub fields: HashMap<EcoString, Rc<RefCell<RtVal>>>,
     pub methods: HashMap<EcoString, Function>,
+    // In declaration order
+    pub field_names: Vec<EcoString>,
+    // Declared types, int values assigned to float fields are cast
+    pub field_types: HashMap<EcoString, VarTypeDecl>,
+    // Arguments of its '@derive' annotations
+    pub derives: Vec<EcoString>,
+    // Evaluated for each instance, in the environment of the declaration
+    pub defaults: Rc<Vec<FieldDefault>>,
+    pub closure: Rc<RefCell<Env>>,
+}
+
+#[derive(Debug)]
+pub struct FieldDefault {
+    pub name: EcoString,
+    pub typ: Option<VarTypeDecl>,
+    pub value: Expr,
+}
+
+impl PartialEq for Struct {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.fields == other.fields && self.methods == other.methods
+    }
 }
 
 impl RtVal {
     pub fn new_struct(
         stmt: &StructStmt,
-        fields: HashMap<EcoString, Rc<RefCell<RtVal>>>,
-        methods: HashMap<EcoString, Function>
+        methods: HashMap<EcoString, Function>,
+        closure: Rc<RefCell<Env>>,
     ) -> Self{
         RtVal::StructVal(Rc::new(RefCell::new(
             Struct {
                 name: stmt.name.value.clone(),
-                fields,
+                fields: stmt.fields.iter().map(|f| (f.name.value.clone(), RtVal::new_null())).collect(),
                 methods,
+                field_names: stmt.fields.iter().map(|f| f.name.value.clone()).collect(),
+                field_types: stmt
+                    .fields
+                    .iter()
+                    .filter_map(|f| f.typ.clone().map(|t| (f.name.value.clone(), t)))
+                    .collect(),
+                derives: stmt
+                    .annotations
+                    .iter()
+                    .filter(|a| a.name.value == "derive")
+                    .flat_map(|a| a.args.iter().map(|arg| arg.value.clone()))
+                    .collect(),
+                defaults: Rc::new(
+                    stmt.fields
+                        .iter()
+                        .filter_map(|f| {
+                            f.value.as_ref().map(|v| FieldDefault {
+                                name: f.name.value.clone(),
+                                typ: f.typ.clone(),
+                                value: v.clone(),
+                            })
+                        })
+                        .collect(),
+                ),
+                closure,
             }
         )))
     }
@@ -434,10 +718,9 @@ impl Callable for Rc<RefCell<Struct>> {
         interpreter: &mut Interpreter,
         args: Vec<Rc<RefCell<RtVal>>>,
     ) -> Result<Rc<RefCell<RtVal>>, RizonResult<CallErr>> {
-        let instance = Rc::new(RefCell::new(RtVal::InstanceVal(Instance {
-            strukt: self.clone(),
-            fields: self.borrow().fields.clone(),
-        })));
+        let instance = Instance::new(self.clone(), interpreter)
+            .map_err(|e| RizonResult::new(CallErr::FnExecution(e.err.to_string()), None))?;
+        let instance = Rc::new(RefCell::new(RtVal::InstanceVal(instance)));
 
         let tmp = self.borrow();
         let initializer = tmp.methods.get("init");
@@ -467,11 +750,34 @@ impl Operate<Struct> for Struct {
 pub struct Instance {
     pub strukt: Rc<RefCell<Struct>>,
     pub fields: HashMap<EcoString, Rc<RefCell<RtVal>>>,
+    pub frozen: bool,
 }
 
 impl Instance {
+    // Default values are evaluated again for each instance, they aren't shared
+    pub fn new(strukt: Rc<RefCell<Struct>>, interpreter: &mut Interpreter) -> Result<Self, RizonResult<InterpErr>> {
+        let (mut fields, defaults, closure) = {
+            let tmp = strukt.borrow();
+            (tmp.fields.clone(), tmp.defaults.clone(), tmp.closure.clone())
+        };
+
+        for d in defaults.iter() {
+            let value = interpreter.evaluate_in(&d.value, closure.clone())?;
+            let value = match &d.typ {
+                Some(t) => interpreter.coerce_to_type(value, t),
+                None => value,
+            };
+
+            fields.insert(d.name.clone(), value);
+        }
+
+        Ok(Instance { strukt, fields, frozen: false })
+    }
+
     pub fn set(&mut self, name: EcoString, value: Rc<RefCell<RtVal>>) -> Result<(), RtValErr> {
-        if let Occupied(mut v) = self.fields.entry(name.clone()) {
+        if self.frozen {
+            Err(RtValErr::FrozenModification)
+        } else if let Occupied(mut v) = self.fields.entry(name.clone()) {
             v.insert(value);
             Ok(())
         } else if self.strukt.borrow().methods.contains_key(&name) {
@@ -481,6 +787,277 @@ impl Instance {
             Err(RtValErr::MissingFieldInStruct(name))
         }
     }
+
+    fn equals(&self, rhs: &Instance, visited: &mut Visited) -> bool {
+        self.strukt.borrow().name == rhs.strukt.borrow().name
+            && self.fields.len() == rhs.fields.len()
+            && self.fields.iter().all(|(name, v1)| {
+                rhs.fields.get(name).is_some_and(|v2| values_equal(v1, v2, visited))
+            })
+    }
+}
+
+// Structural equality: instances of the same structure whose fields
+// are equal. Fields are compared by name, not by storage order
+impl Operate<Instance> for Instance {
+    fn operate(&self, rhs: &Instance, operator: &str) -> Result<RtVal, RtValErr> {
+        let equal = || self.equals(rhs, &mut vec![]);
+
+        match operator {
+            "==" => Ok(equal().into()),
+            "!=" => Ok((!equal()).into()),
+            op => Err(RtValErr::UnsupportedOpOnType(op.to_string(), "instance".into())),
+        }
+    }
+}
+
+// Pairs of elements being compared, to stop on cyclic values
+type Visited = Vec<(*const RefCell<RtVal>, *const RefCell<RtVal>)>;
+
+// Structural equality of two elements of a compound value. A pair met again
+// while comparing is part of a cycle, it is equal if everything else is
+fn values_equal(v1: &Rc<RefCell<RtVal>>, v2: &Rc<RefCell<RtVal>>, visited: &mut Visited) -> bool {
+    if Rc::ptr_eq(v1, v2) {
+        return true;
+    }
+
+    let pair = (Rc::as_ptr(v1), Rc::as_ptr(v2));
+    if visited.contains(&pair) {
+        return true;
+    }
+
+    visited.push(pair);
+
+    let equal = match (&*v1.borrow(), &*v2.borrow()) {
+        (RtVal::InstanceVal(i1), RtVal::InstanceVal(i2)) => i1.equals(i2, visited),
+        (RtVal::ArrayVal(a1), RtVal::ArrayVal(a2)) => a1.equals(a2, visited),
+        (RtVal::MapVal(m1), RtVal::MapVal(m2)) => m1.equals(m2, visited),
+        (RtVal::Null, RtVal::Null) => true,
+        (v1, v2) => matches!(v1.operate(v2, "=="), Ok(RtVal::BoolVal(b)) if b.value),
+    };
+
+    visited.pop();
+
+    equal
+}
+
+// --------
+//   Enum
+// --------
+#[derive(Debug, PartialEq)]
+pub struct Enum {
+    pub name: EcoString,
+    pub variants: Vec<EcoString>,
+}
+
+impl Enum {
+    pub fn get_variant(&self, name: &EcoString) -> Option<RtVal> {
+        self.variants.contains(name).then(|| {
+            RtVal::EnumVariantVal(EnumVariant {
+                enum_name: self.name.clone(),
+                name: name.clone(),
+            })
+        })
+    }
+}
+
+impl RtVal {
+    pub fn new_enum(stmt: &EnumDeclStmt) -> Self {
+        RtVal::EnumVal(Rc::new(Enum {
+            name: stmt.name.value.clone(),
+            variants: stmt.variants.iter().map(|v| v.name.value.clone()).collect(),
+        }))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct EnumVariant {
+    pub enum_name: EcoString,
+    pub name: EcoString,
+}
+
+impl Operate<EnumVariant> for EnumVariant {
+    fn operate(&self, rhs: &EnumVariant, operator: &str) -> Result<RtVal, RtValErr> {
+        match operator {
+            "==" => Ok((self == rhs).into()),
+            "!=" => Ok((self != rhs).into()),
+            op => Err(RtValErr::UnsupportedOpOnType(op.to_string(), "enum".into())),
+        }
+    }
+}
+
+// ---------
+//   Array
+// ---------
+#[derive(Debug, PartialEq, Clone)]
+pub struct Array {
+    pub values: Vec<Rc<RefCell<RtVal>>>,
+    pub frozen: bool,
+}
+
+impl Array {
+    pub fn get(&self, index: i64) -> Result<Rc<RefCell<RtVal>>, RtValErr> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| self.values.get(i))
+            .cloned()
+            .ok_or(RtValErr::IndexOutOfRange(index, self.values.len()))
+    }
+
+    pub fn set(&mut self, index: i64, value: Rc<RefCell<RtVal>>) -> Result<(), RtValErr> {
+        if self.frozen {
+            return Err(RtValErr::FrozenModification);
+        }
+
+        let len = self.values.len();
+
+        match usize::try_from(index).ok().and_then(|i| self.values.get_mut(i)) {
+            Some(v) => {
+                *v = value;
+                Ok(())
+            }
+            None => Err(RtValErr::IndexOutOfRange(index, len)),
+        }
+    }
+
+    fn equals(&self, rhs: &Array, visited: &mut Visited) -> bool {
+        self.values.len() == rhs.values.len()
+            && self.values.iter().zip(&rhs.values).all(|(v1, v2)| values_equal(v1, v2, visited))
+    }
+}
+
+impl Operate<Array> for Array {
+    fn operate(&self, rhs: &Array, operator: &str) -> Result<RtVal, RtValErr> {
+        let equal = || self.equals(rhs, &mut vec![]);
+
+        match operator {
+            "==" => Ok(equal().into()),
+            "!=" => Ok((!equal()).into()),
+            op => Err(RtValErr::UnsupportedOpOnType(op.to_string(), "array".into())),
+        }
+    }
+}
+
+// -------
+//   Map
+// -------
+// Entries are kept in insertion order
+#[derive(Debug, PartialEq, Clone)]
+pub struct Map {
+    pub entries: Vec<(RtVal, Rc<RefCell<RtVal>>)>,
+    pub frozen: bool,
+}
+
+impl Map {
+    pub fn get(&self, key: &RtVal) -> Result<Rc<RefCell<RtVal>>, RtValErr> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+            .ok_or_else(|| RtValErr::MissingMapKey(key.to_string()))
+    }
+
+    pub fn set(&mut self, key: RtVal, value: Rc<RefCell<RtVal>>) -> Result<(), RtValErr> {
+        if self.frozen {
+            return Err(RtValErr::FrozenModification);
+        }
+
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.entries.push((key, value)),
+        }
+
+        Ok(())
+    }
+
+    // Inserting the key again puts it at the end
+    pub fn remove(&mut self, key: &RtVal) -> Result<Option<Rc<RefCell<RtVal>>>, RtValErr> {
+        if self.frozen {
+            return Err(RtValErr::FrozenModification);
+        }
+
+        Ok(self
+            .entries
+            .iter()
+            .position(|(k, _)| k == key)
+            .map(|idx| self.entries.remove(idx).1))
+    }
+
+    // Insertion order doesn't matter
+    fn equals(&self, rhs: &Map, visited: &mut Visited) -> bool {
+        self.entries.len() == rhs.entries.len()
+            && self
+                .entries
+                .iter()
+                .all(|(k, v1)| rhs.get(k).is_ok_and(|v2| values_equal(v1, &v2, visited)))
+    }
+}
+
+impl Operate<Map> for Map {
+    fn operate(&self, rhs: &Map, operator: &str) -> Result<RtVal, RtValErr> {
+        let equal = || self.equals(rhs, &mut vec![]);
+
+        match operator {
+            "==" => Ok(equal().into()),
+            "!=" => Ok((!equal()).into()),
+            op => Err(RtValErr::UnsupportedOpOnType(op.to_string(), "map".into())),
+        }
+    }
+}
+
+// ---------
+//   Range
+// ---------
+// The step is never zero, a negative one counts down from start to end
+#[derive(Debug, PartialEq, Clone)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub step: i64,
+    pub inclusive: bool,
+}
+
+impl Range {
+    pub fn values(&self) -> impl Iterator<Item = i64> {
+        let (start, step) = (self.start, self.step);
+
+        (0..self.len() as i64).map(move |i| start + i * step)
+    }
+
+    pub fn len(&self) -> usize {
+        let step = self.step as i128;
+        // Distance covered in the direction of the step
+        let span = (self.end as i128 - self.start as i128) * step.signum() + self.inclusive as i128;
+
+        ((span.max(0) + step.abs() - 1) / step.abs()) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl RtVal {
+    pub fn new_range(start: i64, end: i64, step: i64, inclusive: bool) -> Self {
+        RtVal::RangeVal(Range { start, end, step, inclusive })
+    }
+
+    pub fn new_array(values: Vec<Rc<RefCell<RtVal>>>) -> Self {
+        RtVal::ArrayVal(Array { values, frozen: false })
+    }
+
+    pub fn new_map(entries: Vec<(RtVal, Rc<RefCell<RtVal>>)>) -> Self {
+        let mut map = Map { entries: vec![], frozen: false };
+        // Last value wins for duplicated keys
+        for (k, v) in entries {
+            match map.entries.iter_mut().find(|(key, _)| *key == k) {
+                Some((_, value)) => *value = v,
+                None => map.entries.push((k, v)),
+            }
+        }
+
+        RtVal::MapVal(map)
+    }
 }
 
 // --------
@@ -516,6 +1093,12 @@ impl From<bool> for RtVal {
     }
 }
 
+impl From<char> for RtVal {
+    fn from(value: char) -> Self {
+        RtVal::CharVal(Char { value })
+    }
+}
+
 impl RtVal {
     pub fn new_int(value: i64) -> Self {
         value.into()
@@ -532,6 +1115,55 @@ impl RtVal {
     pub fn new_bool(value: bool) -> Self {
         value.into()
     }
+
+    pub fn new_char(value: char) -> Self {
+        value.into()
+    }
+
+    // Later modifications of the value are errors. A deep freeze also
+    // freezes the values it holds, a value being frozen higher in the
+    // same call (a cycle) is left as is
+    pub fn freeze(&mut self, deep: bool) {
+        let held: Vec<Rc<RefCell<RtVal>>> = match self {
+            RtVal::InstanceVal(i) => {
+                i.frozen = true;
+                i.fields.values().cloned().collect()
+            }
+            RtVal::ArrayVal(a) => {
+                a.frozen = true;
+                a.values.clone()
+            }
+            RtVal::MapVal(m) => {
+                m.frozen = true;
+                m.entries.iter().map(|(_, v)| v.clone()).collect()
+            }
+            _ => vec![],
+        };
+
+        if deep {
+            for v in held {
+                if let Ok(mut v) = v.try_borrow_mut() {
+                    v.freeze(true);
+                }
+            }
+        }
+    }
+
+    // Iterators for the host over collection values, the values are
+    // copies of the ones held by the collection
+    pub fn as_array_iter(&self) -> Option<impl Iterator<Item = RtVal> + '_> {
+        match self {
+            RtVal::ArrayVal(a) => Some(a.values.iter().map(|v| v.borrow().clone())),
+            _ => None,
+        }
+    }
+
+    pub fn as_map_iter(&self) -> Option<impl Iterator<Item = (RtVal, RtVal)> + '_> {
+        match self {
+            RtVal::MapVal(m) => Some(m.entries.iter().map(|(k, v)| (k.clone(), v.borrow().clone()))),
+            _ => None,
+        }
+    }
 }
 
 // -----------
@@ -541,14 +1173,111 @@ impl Display for RtVal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
             RtVal::IntVal(i) => write!(f, "{}", i.value),
-            RtVal::FloatVal(r) => write!(f, "{}", r.value),
+            RtVal::FloatVal(r) => write!(f, "{}", r),
             RtVal::BoolVal(b) => write!(f, "{}", b.value),
             RtVal::StrVal(s) => write!(f, "\"{}\"", s.value),
+            RtVal::CharVal(c) => write!(f, "'{}'", c.value),
             RtVal::FuncVal(func) => write!(f, "<fn {}>", func.name),
             RtVal::NativeFnVal(func) => write!(f, "{}", func),
             RtVal::StructVal(s) => write!(f, "<struct {}>", s.borrow().name),
-            RtVal::InstanceVal(i) => write!(f, "<{} instance>", i.strukt.borrow().name),
+            RtVal::InstanceVal(i) => {
+                let strukt = i.strukt.borrow();
+
+                if !strukt.derives.iter().any(|d| d == "to_string") {
+                    return write!(f, "<{} instance>", strukt.name);
+                }
+
+                let fields: Vec<String> = strukt
+                    .field_names
+                    .iter()
+                    .map(|name| format!("{}: {}", name, i.fields[name].borrow()))
+                    .collect();
+
+                match fields.is_empty() {
+                    true => write!(f, "{} {{}}", strukt.name),
+                    false => write!(f, "{} {{ {} }}", strukt.name, fields.join(", ")),
+                }
+            }
+            RtVal::EnumVal(e) => write!(f, "<enum {}>", e.name),
+            RtVal::EnumVariantVal(v) => write!(f, "{}.{}", v.enum_name, v.name),
+            RtVal::ArrayVal(a) => {
+                write!(f, "[")?;
+
+                for (i, v) in a.values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    write!(f, "{}", v.borrow())?;
+                }
+
+                write!(f, "]")
+            }
+            RtVal::MapVal(m) => {
+                write!(f, "{{")?;
+
+                for (i, (k, v)) in m.entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    write!(f, "{}: {}", k, v.borrow())?;
+                }
+
+                write!(f, "}}")
+            }
+            RtVal::RangeVal(r) => {
+                let op = if r.inclusive { "..=" } else { ".." };
+
+                write!(f, "{}{}{}", r.start, op, r.end)?;
+
+                match r.step {
+                    1 => Ok(()),
+                    s => write!(f, ":{}", s),
+                }
+            }
             RtVal::Null => write!(f, "null"),
         }
     }
 }
+
+impl RtVal {
+    // Unambiguous representation: escaped strings and chars, floats always
+    // with a decimal part and instances with their fields, derived or not
+    pub fn repr(&self) -> String {
+        match self {
+            RtVal::StrVal(s) => format!("{:?}", s.value.as_str()),
+            RtVal::CharVal(c) => format!("{:?}", c.value),
+            RtVal::FloatVal(r) if r.value.is_finite() => format!("{:?}", r.value),
+            RtVal::InstanceVal(i) => {
+                let strukt = i.strukt.borrow();
+
+                let fields: Vec<String> = strukt
+                    .field_names
+                    .iter()
+                    .map(|name| format!("{}: {}", name, i.fields[name].borrow().repr()))
+                    .collect();
+
+                match fields.is_empty() {
+                    true => format!("{} {{}}", strukt.name),
+                    false => format!("{} {{ {} }}", strukt.name, fields.join(", ")),
+                }
+            }
+            RtVal::ArrayVal(a) => {
+                let values: Vec<String> = a.values.iter().map(|v| v.borrow().repr()).collect();
+
+                format!("[{}]", values.join(", "))
+            }
+            RtVal::MapVal(m) => {
+                let entries: Vec<String> = m
+                    .entries
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k.repr(), v.borrow().repr()))
+                    .collect();
+
+                format!("{{{}}}", entries.join(", "))
+            }
+            v => v.to_string(),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod static_analyzer;
+pub mod typed_ast;
 // Allow to use it as resolver::Resolver instead of resolver::resolver::Resolver
 pub use static_analyzer::StaticAnalyzer;
 
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::{HashMap, HashSet}, fmt::Display};
 
 use colored::Colorize;
 use ecow::EcoString;
@@ -8,13 +8,15 @@ use rizon_tools::results::{Loc, RizonReport, RizonResult};
 use rizon_frontend::{
     ast::{
         expr::{
-            AssignExpr, BinaryExpr, CallExpr, Expr, FloatLiteralExpr, GetExpr, GroupingExpr,
-            IdentifierExpr, IntLiteralExpr, IsExpr, LogicalExpr, SelfExpr, SetExpr, StrLiteralExpr,
-            UnaryExpr, VisitExpr,
+            ArrayLiteralExpr, AssignExpr, BinaryExpr, CallExpr, CastExpr, ChainedCompareExpr, CharLiteralExpr, Expr, FloatLiteralExpr, GetExpr,
+            GroupingExpr, IdentifierExpr, IfExpr, IndexExpr, IndexSetExpr, IntLiteralExpr, IsExpr,
+            LogicalExpr, MapLiteralExpr, RangeExpr, SelfExpr, SetExpr, StrLiteralExpr, StructLiteralExpr, UnaryExpr,
+            VisitExpr,
         },
         stmt::{
-            BlockStmt, ExprStmt, FnDeclStmt, ForStmt, IfStmt, PrintStmt, ReturnStmt, Stmt,
-            StructStmt, VarDeclStmt, VarTypeDecl, VisitStmt, WhileStmt,
+            Annotation, BlockStmt, BreakStmt, ContinueStmt, DoWhileStmt, EnumDeclStmt, ExprStmt, FnDeclStmt, ForStmt, IfStmt, MatchPattern,
+            MatchStmt, PrintStmt, ReturnStmt, Stmt, StructStmt, TypeAliasStmt, VarDeclStmt,
+            VarTypeDecl, VisitStmt, WhileStmt,
         },
     },
     lexer::{Token, TokenKind},
@@ -25,6 +27,9 @@ pub enum StaticAnalyzerErr {
     #[error("local variable initializer is shadoweding global variable")]
     LocalVarInOwnInit,
 
+    #[error("can't use '{0}' outside of a loop")]
+    JumpOutsideLoop(String),
+
     #[error("can't return from top level code")]
     TopLevelReturn,
 
@@ -44,15 +49,88 @@ pub enum StaticAnalyzerErr {
     #[error("structure '{0}' has no field '{1}'")]
     InexistantField(String, String),
 
+    #[error("field '{1}' of structure '{0}' is never initialized")]
+    UninitializedField(String, String),
+
+    #[error("field '{0}' is initialized more than once")]
+    FieldInitTwice(String),
+
+    #[error("'{0}' isn't a structure, it can't be initialized with a literal")]
+    NonStructLiteral(String),
+
     #[error("structure '{0}' has no method constructor")]
     InexistantConstructor(String),
 
     #[error("only structure instances have fields")]
     NonStructFieldAccess,
 
+    #[error("value of type '{0}' may be null, check it against null before accessing its fields")]
+    NullableFieldAccess(String),
+
     #[error("constructor can't return anything")]
     ConstructorReturnType,
 
+    // Enums
+    #[error("enum '{0}' has no variant '{1}'")]
+    InexistantVariant(String, String),
+
+    #[error("enum variant value must be a constant integer expression")]
+    NonConstVariantValue,
+
+    #[error("division by zero in constant expression")]
+    ConstDivisionByZero,
+
+    #[error("integer overflow in constant expression")]
+    ConstOverflow,
+
+    #[error("enum variant value {0} is already used by variant '{1}'")]
+    DuplicateVariantValue(i64, String),
+
+    // Match
+    #[error("pattern of type '{0}' can't match a value of type '{1}'")]
+    WrongMatchPatternType(String, String),
+
+    #[error("only enum variants can be used as match patterns, found '{0}'")]
+    NonVariantMatchPattern(String),
+
+    #[error("non exhaustive 'match', missing: {0}")]
+    NonExhaustiveMatch(String),
+
+    // If expressions
+    #[error("'if' expression must have an 'else' branch")]
+    NonExhaustiveIfExpr,
+
+    #[error("branches of 'if' expression have different types: '{0}' and '{1}'")]
+    MismatchedIfExprBranches(String, String),
+
+    // Arrays and maps
+    #[error("all values of an array must have the same type, expected '{0}' but found '{1}'")]
+    MixedArrayTypes(String, String),
+
+    #[error("all entries of a map must have the same types, expected '{0}' but found '{1}'")]
+    MixedMapTypes(String, String),
+
+    #[error("type '{0}' can't be indexed")]
+    NonIndexable(String),
+
+    #[error("type '{0}' can't be iterated over")]
+    NonIterable(String),
+
+    #[error("range bounds must be of type 'int', found '{0}'")]
+    NonIntRangeBound(String),
+
+    #[error("range step must be of type 'int', found '{0}'")]
+    NonIntRangeStep(String),
+
+    #[error("range step can't be zero")]
+    ZeroRangeStep,
+
+    #[error("wrong index type, expected '{0}' but found '{1}'")]
+    WrongIndexType(String, String),
+
+    #[error("index {0} is out of range for array of length {1}")]
+    IndexOutOfRange(i64, usize),
+
     // Types
     #[error("unknown type '{0}'")]
     UnknownType(String),
@@ -60,9 +138,18 @@ pub enum StaticAnalyzerErr {
     #[error("a {0} with the same name as already been declared in this scope")]
     AlreadyDecl(String),
 
+    #[error("type alias '{0}' can't reference itself")]
+    RecursiveTypeAlias(String),
+
     #[error("operation '{0}' is not allowed between types '{1}' and '{2}'")]
     InvalidOp(String, String, String),
 
+    #[error("can't cast value of type '{0}' to type '{1}'")]
+    InvalidCast(String, String),
+
+    #[error("condition must be of type 'bool', found '{0}'")]
+    NonBoolCondition(String),
+
     #[error("unary operator '!' can only be used on 'bool' type")]
     NonBoolBangUnary,
 
@@ -78,8 +165,8 @@ pub enum StaticAnalyzerErr {
     #[error("trying to assign value of type '{0}' to variable of type '{1}'")]
     WrongTypeAssign(String, String),
 
-    #[error("logical operators must have same type on each side, found '{0}' and '{1}'")]
-    WrongTypeLogical(String, String),
+    #[error("can't assign to {1} '{0}'")]
+    AssignToCallable(String, String),
 
     #[error("variable is not of type '{0}'")]
     WrongVarType(String),
@@ -91,19 +178,50 @@ pub enum StaticAnalyzerErr {
     #[error("wrong type returned, expected '{0}' but found '{1}'")]
     WrongReturnType(String, String),
 
+    #[error("'return' without value in function returning '{0}'")]
+    MissingReturnValue(String),
+
     #[error("wrong arguments number, expected {0} but found {1}")]
     WrongArgsNb(usize, usize),
 
     #[error("wrong arguments type, expected '{0}' but found '{1}'")]
     WrongArgsType(String, String),
 
+    #[error("format string has {} but {} given", plural(*.0, "placeholder"), plural_given(*.1))]
+    FormatArgMismatch(usize, usize),
+
     // Call
     #[error("only functions and structures are callable")]
     NonFnCall,
 
+    #[error("name '{0}' refers to a value, not a type, the structure is shadowed by a variable")]
+    ShadowedTypeCall(String),
+
+    #[error("unknown argument name '{0}'")]
+    UnknownArgName(String),
+
+    #[error("argument '{0}' is given more than once")]
+    DuplicateArgName(String),
+
+    #[error("named arguments can only be used when calling declared functions")]
+    NamedArgsNotAllowed,
+
+    // Annotations
+    #[error("unknown annotation '@{0}'")]
+    UnknownAnnotation(String),
+
+    #[error("annotation '@{0}' can't be used on {1}")]
+    MisplacedAnnotation(String, String),
+
+    #[error("invalid argument '{1}' for annotation '@{0}'")]
+    InvalidAnnotationArg(String, String),
+
     // Warings
     #[error("{0}")]
     Warning(#[from] Warning),
+
+    #[error("{0}")]
+    DeniedWarning(Warning),
 }
 
 impl RizonReport for StaticAnalyzerErr {
@@ -115,13 +233,54 @@ impl RizonReport for StaticAnalyzerErr {
     }
 }
 
-#[derive(Debug, Error, PartialEq, Clone)]
+#[derive(Debug, Error, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum StaticAnalyzerWarning {
     #[error("comparison between int and float can lead to misleading result")]
     CompIntFloat,
 
     #[error("unreachable code after 'return'")]
     UnreachAfterReturn,
+
+    #[error("unreachable code after 'break' or 'continue'")]
+    UnreachAfterBreak,
+
+    #[error("integer division or modulo by a literal zero")]
+    DivisionByZero,
+
+    #[error("declaration without type is implicitly of type 'any'")]
+    ImplicitAny,
+}
+
+impl StaticAnalyzerWarning {
+    pub fn all() -> &'static [StaticAnalyzerWarning] {
+        &[
+            StaticAnalyzerWarning::CompIntFloat,
+            StaticAnalyzerWarning::UnreachAfterReturn,
+            StaticAnalyzerWarning::UnreachAfterBreak,
+            StaticAnalyzerWarning::DivisionByZero,
+            StaticAnalyzerWarning::ImplicitAny,
+        ]
+    }
+
+    // Style checks are opt-in
+    pub fn default_level(&self) -> WarningLevel {
+        match self {
+            StaticAnalyzerWarning::ImplicitAny => WarningLevel::Allow,
+            _ => WarningLevel::Warn,
+        }
+    }
+}
+
+// What to do when a warning is triggered
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum WarningLevel {
+    // Ignored
+    Allow,
+    // Reported but doesn't prevent execution
+    #[default]
+    Warn,
+    // Reported as an error
+    Deny,
 }
 
 #[derive(Debug, Error, PartialEq, Clone)]
@@ -145,6 +304,7 @@ impl Display for Warning {
 pub type RizonResResolv = RizonResult<StaticAnalyzerErr>;
 pub type StaticAnalyzerRes = Result<bool, RizonResResolv>;
 pub type StaticAnalyzerExprRes = Result<VarType, RizonResResolv>;
+type MembersTypes = HashMap<EcoString, VarType>;
 
 #[derive(Clone, Copy, PartialEq)]
 enum FnKind {
@@ -157,6 +317,8 @@ enum FnKind {
 struct FnCtx {
     kind: FnKind,
     return_type: VarType,
+    // Loops enclosing the code in this function, 'break' can't exit the function
+    loop_depth: usize,
 }
 
 impl Default for FnCtx {
@@ -164,6 +326,7 @@ impl Default for FnCtx {
         Self {
             kind: FnKind::None,
             return_type: VarType::Void,
+            loop_depth: 0,
         }
     }
 }
@@ -175,10 +338,16 @@ pub enum VarType {
     Float,
     Str,
     Bool,
+    Char,
     Null,
     Void,
     Struct(EcoString),
+    Enum(EcoString),
     Fn(Box<FnType>),
+    Array(Box<VarType>),
+    Map(Box<VarType>, Box<VarType>),
+    Optional(Box<VarType>),
+    Range,
 }
 
 impl VarType {
@@ -190,36 +359,125 @@ impl VarType {
     }
 
     pub fn new_fn(args_type: Vec<VarType>, return_type: VarType) -> Self {
-        VarType::Fn(Box::new(FnType {
-            args_type,
-            return_type,
-        }))
+        VarType::Fn(Box::new(FnType::new(args_type, return_type)))
     }
 }
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, Debug)]
 pub struct FnType {
     args_type: Vec<VarType>,
     return_type: VarType,
+    // Only known for declared functions, used by named arguments
+    param_names: Vec<EcoString>,
+}
+
+impl FnType {
+    fn new(args_type: Vec<VarType>, return_type: VarType) -> Self {
+        FnType {
+            args_type,
+            return_type,
+            param_names: vec![],
+        }
+    }
+
+    pub fn args_type(&self) -> &[VarType] {
+        &self.args_type
+    }
+
+    pub fn return_type(&self) -> &VarType {
+        &self.return_type
+    }
+}
+
+// Parameter names aren't part of the type
+impl PartialEq for FnType {
+    fn eq(&self, other: &Self) -> bool {
+        self.args_type == other.args_type && self.return_type == other.return_type
+    }
 }
 
 impl Default for FnType {
     fn default() -> Self {
-        FnType {
-            args_type: vec![],
-            return_type: VarType::Void,
+        FnType::new(vec![], VarType::Void)
+    }
+}
+
+#[derive(Default, PartialEq, Clone)]
+struct EnumType {
+    name: EcoString,
+    variants: Vec<EcoString>,
+}
+
+impl EnumType {
+    fn get_variant_type(&self, variant_name: &Token) -> StaticAnalyzerExprRes {
+        if self.variants.contains(&variant_name.value) {
+            return Ok(VarType::Enum(self.name.clone()));
         }
+
+        Err(RizonResult::new(
+            StaticAnalyzerErr::InexistantVariant(
+                self.name.clone().into(),
+                variant_name.value.clone().into(),
+            ),
+            Some(variant_name.loc.clone()),
+        ))
     }
 }
 
-#[derive(Default, PartialEq)]
+#[derive(Default, PartialEq, Clone)]
 struct StructType {
     name: EcoString,
     fields: HashMap<EcoString, VarType>,
     methods: HashMap<EcoString, VarType>,
+    // Fields without default value that can't be null, in declaration order
+    required_fields: Vec<EcoString>,
+    // Members names in declaration order, the implicit 'init' comes last
+    fields_order: Vec<EcoString>,
+    methods_order: Vec<EcoString>,
 }
 
 impl StructType {
+    fn new(stmt: &StructStmt, fields: MembersTypes, methods: MembersTypes) -> Self {
+        let mut methods_order: Vec<EcoString> =
+            stmt.methods.iter().map(|m| m.name.value.clone()).collect();
+
+        if !methods_order.iter().any(|m| m == "init") {
+            methods_order.push("init".into());
+        }
+
+        StructType {
+            name: stmt.name.value.clone(),
+            required_fields: StaticAnalyzer::required_fields(&stmt.fields, &fields),
+            fields_order: stmt.fields.iter().map(|f| f.name.value.clone()).collect(),
+            methods_order,
+            fields,
+            methods,
+        }
+    }
+
+    fn layout(&self) -> StructLayout {
+        let fields = self
+            .fields_order
+            .iter()
+            .map(|f| (f.clone(), self.fields[f].clone()))
+            .collect();
+
+        let methods = self
+            .methods_order
+            .iter()
+            .filter_map(|m| match &self.methods[m] {
+                VarType::Fn(f) => Some((m.clone(), (**f).clone())),
+                _ => None,
+            })
+            .collect();
+
+        StructLayout {
+            name: self.name.clone(),
+            fields,
+            methods,
+        }
+    }
+
     fn get_member_type(&self, member_name: &Token) -> StaticAnalyzerExprRes {
         if let Some(t) = self
             .fields
@@ -247,9 +505,11 @@ impl Display for VarType {
             VarType::Float => write!(f, "float"),
             VarType::Str => write!(f, "str"),
             VarType::Bool => write!(f, "bool"),
+            VarType::Char => write!(f, "char"),
             VarType::Null => write!(f, "null"),
             VarType::Void => write!(f, "void"),
             VarType::Struct(t) => write!(f, "{}", t),
+            VarType::Enum(t) => write!(f, "{}", t),
             VarType::Fn(t) => {
                 write!(f, "fn(")?;
 
@@ -263,6 +523,10 @@ impl Display for VarType {
 
                 write!(f, ") -> {}", t.return_type)
             }
+            VarType::Array(t) => write!(f, "[{}]", t),
+            VarType::Map(k, v) => write!(f, "{{{}: {}}}", k, v),
+            VarType::Optional(t) => write!(f, "{}?", t),
+            VarType::Range => write!(f, "range"),
         }
     }
 }
@@ -275,6 +539,7 @@ impl From<&Token> for VarType {
             "float" => VarType::Float,
             "str" => VarType::Str,
             "bool" => VarType::Bool,
+            "char" => VarType::Char,
             "null" => VarType::Null,
             "void" => VarType::Void,
             other => VarType::Struct(other.into()),
@@ -314,6 +579,11 @@ impl From<&VarTypeDecl> for VarType {
 
                 VarType::new_fn(args_type, return_type)
             }
+            VarTypeDecl::Array { typ, .. } => VarType::Array(Box::new((&**typ).into())),
+            VarTypeDecl::Map { key, value, .. } => {
+                VarType::Map(Box::new((&**key).into()), Box::new((&**value).into()))
+            }
+            VarTypeDecl::Optional { typ, .. } => VarType::Optional(Box::new((&**typ).into())),
         }
     }
 }
@@ -333,30 +603,66 @@ impl From<&EcoString> for VarType {
     }
 }
 
-#[derive(Default)]
+// Read-only view of a resolved structure, members are in declaration order
+#[derive(Debug, PartialEq, Clone)]
+pub struct StructLayout {
+    pub name: EcoString,
+    pub fields: Vec<(EcoString, VarType)>,
+    pub methods: Vec<(EcoString, FnType)>,
+}
+
+#[derive(Default, Clone)]
 struct Scope {
     variables: HashMap<EcoString, bool>,
     var_types: HashMap<EcoString, VarType>,
+    // More precise types of outer variables inside a branch (ex: not
+    // null). Only used when reading them, assignments use the declared type
+    narrowed: HashMap<EcoString, VarType>,
+    // Kind of the declarations that aren't variables, they can't be assigned
+    callables: HashMap<EcoString, EcoString>,
     types_def: HashMap<EcoString, StructType>,
+    enums_def: HashMap<EcoString, EnumType>,
+    // Only declared in global scope
+    aliases: HashMap<EcoString, VarType>,
 }
 
 #[derive(Default)]
 pub struct StaticAnalyzer {
     globals: Scope,
+    // Globals with only the natives, built on the first resolution and
+    // restored on reset
+    native_globals: Option<Scope>,
     scopes: Vec<Scope>,
     locals: HashMap<Loc, usize>,
     fn_ctx: FnCtx,
     current_struct: Option<EcoString>,
     warnings: Vec<Warning>,
+    warnings_levels: HashMap<StaticAnalyzerWarning, WarningLevel>,
+    // Native functions accepting several types of arguments, like 'len'
+    natives_overloads: HashMap<EcoString, Vec<FnType>>,
+    // Name locations of the top level declarations made before resolving
+    hoisted: HashSet<Loc>,
+    // Strict numeric typing, int values aren't implicitly widened to float
+    no_numeric_widening: bool,
+    // Inferred type of each expression, only recorded for tooling
+    expr_types: Option<HashMap<Loc, VarType>>,
+    // Int values used as float array elements, map keys or map values. The
+    // runtime containers don't know their types, the interpreter casts them
+    widened: HashSet<Loc>,
 }
 
 // If we can’t find it in the stack of local scopes, we assume it must be global
 impl StaticAnalyzer {
     pub fn resolve(&mut self, stmts: &[Stmt]) -> Result<HashMap<Loc, usize>, Vec<RizonResResolv>> {
-        self.set_globals();
+        if self.native_globals.is_none() {
+            self.set_globals();
+            self.native_globals = Some(self.globals.clone());
+        }
 
         let mut errors: Vec<RizonResResolv> = vec![];
 
+        self.hoist_declarations(stmts);
+
         for s in stmts {
             match self.resolve_stmt(s) {
                 Ok(_) => continue,
@@ -364,24 +670,198 @@ impl StaticAnalyzer {
             }
         }
 
-        if !errors.is_empty() || !self.warnings.is_empty() {
-            self.warnings.iter().for_each(|w| errors.push(RizonResult::new(
-                StaticAnalyzerErr::Warning((*w).clone()),
-                Some(w.loc.clone()),
-            )));
+        let (denied, warnings): (Vec<Warning>, Vec<Warning>) = std::mem::take(&mut self.warnings)
+            .into_iter()
+            .partition(|w| self.warning_level(w.kind) == WarningLevel::Deny);
 
+        self.warnings = warnings;
+
+        denied.into_iter().for_each(|w| {
+            let loc = w.loc.clone();
+            errors.push(RizonResult::new(StaticAnalyzerErr::DeniedWarning(w), Some(loc)))
+        });
+
+        if !errors.is_empty() {
             return Err(errors);
         }
 
         Ok(self.locals.clone())
     }
 
+    // Clears the state of the previous program to resolve a new one with
+    // the same analyzer. Only the native globals and the settings are kept
+    pub fn reset(&mut self) {
+        self.globals = self.native_globals.clone().unwrap_or_default();
+        self.scopes.clear();
+        self.locals.clear();
+        self.fn_ctx = FnCtx::default();
+        self.current_struct = None;
+        self.warnings.clear();
+        self.hoisted.clear();
+        self.widened.clear();
+
+        if let Some(types) = &mut self.expr_types {
+            types.clear();
+        }
+    }
+
+    // Top level structures and functions can be used before their declaration.
+    // Their names and types are declared here, their bodies are resolved in
+    // order. Declarations that fail are left to report their error then
+    fn hoist_declarations(&mut self, stmts: &[Stmt]) {
+        self.hoisted.clear();
+
+        // Structures first, function signatures can reference them
+        for stmt in stmts {
+            if let Stmt::Struct(s) = stmt {
+                let Ok((fields, methods)) = self.struct_members_types(&s.fields, &s.methods) else {
+                    continue;
+                };
+
+                if self.declare_name(&s.name.value, &s.name.loc, "structure").is_err() {
+                    continue;
+                }
+
+                self.define_name(&s.name.value);
+
+                let struct_type = StructType::new(s, fields, methods);
+
+                if self.declare_type(struct_type, &s.name.loc).is_ok() {
+                    self.hoisted.insert(s.name.loc.clone());
+                }
+            }
+        }
+
+        for stmt in stmts {
+            if let Stmt::FnDecl(f) = stmt {
+                if self.declare_name(&f.name.value, &f.name.loc, "function").is_err() {
+                    continue;
+                }
+
+                self.define_name(&f.name.value);
+
+                let fn_type = self.resolve_fn_type(f);
+                self.init_var_type(&f.name.value, fn_type);
+                self.hoisted.insert(f.name.loc.clone());
+            }
+        }
+    }
+
+    // Layouts of the top level structures of the last resolved program,
+    // sorted by name. Built-in types have no layout
+    pub fn struct_layouts(&self) -> Vec<StructLayout> {
+        let mut layouts: Vec<StructLayout> = self
+            .globals
+            .types_def
+            .values()
+            .filter(|t| !t.name.is_empty())
+            .map(StructType::layout)
+            .collect();
+
+        layouts.sort_by(|a, b| a.name.cmp(&b.name));
+        layouts
+    }
+
+    pub fn struct_layout(&self, name: &str) -> Option<StructLayout> {
+        self.globals
+            .types_def
+            .get(name)
+            .filter(|t| !t.name.is_empty())
+            .map(StructType::layout)
+    }
+
+    // On by default: 'var x: float = 5' is accepted
+    pub fn set_implicit_numeric_widening(&mut self, enabled: bool) {
+        self.no_numeric_widening = !enabled;
+    }
+
+    // Off by default, see 'expr_types'
+    pub fn set_record_types(&mut self, record: bool) {
+        self.expr_types = record.then(HashMap::new);
+    }
+
+    // Inferred types of the last resolved program, by expression location.
+    // Only available if recorded, an outer expression with the same location
+    // than an inner one overrides it
+    pub fn expr_types(&self) -> Option<&HashMap<Loc, VarType>> {
+        self.expr_types.as_ref()
+    }
+
+    // Same as 'expr_types' but leaves the recording empty, for the callers
+    // keeping the types of several programs
+    pub fn take_expr_types(&mut self) -> Option<HashMap<Loc, VarType>> {
+        self.expr_types.as_mut().map(std::mem::take)
+    }
+
+    // Locations of the int values to cast to float when stored in an array
+    // or a map, to hand over to the interpreter
+    pub fn take_widened(&mut self) -> HashSet<Loc> {
+        std::mem::take(&mut self.widened)
+    }
+
+    pub fn set_warning_level(&mut self, warning: StaticAnalyzerWarning, level: WarningLevel) {
+        self.warnings_levels.insert(warning, level);
+    }
+
+    pub fn warning_level(&self, warning: StaticAnalyzerWarning) -> WarningLevel {
+        self.warnings_levels.get(&warning).copied().unwrap_or(warning.default_level())
+    }
+
+    // Warnings don't prevent the execution, they are collected here until the
+    // caller reports them
+    pub fn take_warnings(&mut self) -> Vec<RizonResResolv> {
+        std::mem::take(&mut self.warnings)
+            .into_iter()
+            .map(|w| {
+                let loc = w.loc.clone();
+                RizonResult::new(StaticAnalyzerErr::Warning(w), Some(loc))
+            })
+            .collect()
+    }
+
+    fn warn(&mut self, kind: StaticAnalyzerWarning, loc: Loc) {
+        if self.warning_level(kind) != WarningLevel::Allow {
+            self.warnings.push(Warning::new(kind, loc));
+        }
+    }
+
     fn set_globals(&mut self) {
         self.globals.variables.insert("true".into(), true);
         self.globals.variables.insert("false".into(), true);
         self.globals.variables.insert("null".into(), true);
         self.globals.variables.insert("clock".into(), true);
         self.globals.variables.insert("input".into(), true);
+        self.globals.variables.insert("read_line".into(), true);
+        self.globals.variables.insert("mod_euclid".into(), true);
+        self.globals.variables.insert("debug_assert".into(), true);
+        self.globals.variables.insert("assert".into(), true);
+        self.globals.variables.insert("dbg".into(), true);
+        self.globals.variables.insert("map".into(), true);
+        self.globals.variables.insert("keys".into(), true);
+        self.globals.variables.insert("to_array".into(), true);
+        self.globals.variables.insert("to_map".into(), true);
+        self.globals.variables.insert("remove".into(), true);
+        self.globals.variables.insert("freeze".into(), true);
+        self.globals.variables.insert("deep_freeze".into(), true);
+        self.globals.variables.insert("len".into(), true);
+
+        for f in ["sqrt", "pow", "abs", "floor", "ceil", "round", "min", "max", "clamp", "max_of", "min_of"] {
+            self.globals.variables.insert(f.into(), true);
+        }
+
+        for f in ["substr", "index_of", "to_upper", "to_lower", "split", "format"] {
+            self.globals.variables.insert(f.into(), true);
+        }
+
+        for f in ["to_str", "to_int", "to_float", "repr"] {
+            self.globals.variables.insert(f.into(), true);
+        }
+
+        for f in self.globals.variables.keys() {
+            if !matches!(f.as_str(), "true" | "false" | "null") {
+                self.globals.callables.insert(f.clone(), "native function".into());
+            }
+        }
 
         self.globals.var_types.insert("true".into(), VarType::Bool);
         self.globals.var_types.insert("false".into(), VarType::Bool);
@@ -392,8 +872,204 @@ impl StaticAnalyzer {
         self.globals
             .var_types
             .insert("input".into(), VarType::new_fn(vec![VarType::Str], VarType::Str));
+        self.globals
+            .var_types
+            .insert("read_line".into(), VarType::new_fn(vec![], VarType::Str));
+        self.globals.var_types.insert(
+            "mod_euclid".into(),
+            VarType::new_fn(vec![VarType::Int, VarType::Int], VarType::Int),
+        );
+        self.globals.var_types.insert(
+            "debug_assert".into(),
+            VarType::new_fn(vec![VarType::Bool], VarType::Void),
+        );
+        self.globals
+            .var_types
+            .insert("assert".into(), VarType::new_fn(vec![VarType::Bool], VarType::Void));
+        // Calls are typed as their argument in 'visit_call_expr'
+        self.globals
+            .var_types
+            .insert("dbg".into(), VarType::new_fn(vec![VarType::Any], VarType::Any));
+        // Calls are typed from the callback in 'visit_call_expr'
+        self.globals.var_types.insert(
+            "map".into(),
+            VarType::new_fn(
+                vec![VarType::Array(Box::new(VarType::Any)), VarType::Any],
+                VarType::Array(Box::new(VarType::Any)),
+            ),
+        );
+        // The keys type is inferred from the map argument
+        self.globals.var_types.insert(
+            "keys".into(),
+            VarType::new_fn(
+                vec![VarType::Map(Box::new(VarType::Any), Box::new(VarType::Any))],
+                VarType::Array(Box::new(VarType::Any)),
+            ),
+        );
+        // Calls are typed from the collection in 'visit_call_expr'
+        self.globals.var_types.insert(
+            "to_array".into(),
+            VarType::new_fn(
+                vec![VarType::Map(Box::new(VarType::Any), Box::new(VarType::Any))],
+                VarType::Array(Box::new(VarType::Array(Box::new(VarType::Any)))),
+            ),
+        );
+        self.globals.var_types.insert(
+            "to_map".into(),
+            VarType::new_fn(
+                vec![VarType::Array(Box::new(VarType::Array(Box::new(VarType::Any))))],
+                VarType::Map(Box::new(VarType::Any), Box::new(VarType::Any)),
+            ),
+        );
+        // Return the type of their argument
+        for f in ["freeze", "deep_freeze"] {
+            self.globals
+                .var_types
+                .insert(f.into(), VarType::new_fn(vec![VarType::Any], VarType::Any));
+        }
+        self.globals.var_types.insert(
+            "remove".into(),
+            VarType::new_fn(
+                vec![VarType::Map(Box::new(VarType::Any), Box::new(VarType::Any)), VarType::Any],
+                VarType::Void,
+            ),
+        );
+        self.globals
+            .var_types
+            .insert("len".into(), VarType::new_fn(vec![VarType::Any], VarType::Int));
+
+        self.globals
+            .var_types
+            .insert("sqrt".into(), VarType::new_fn(vec![VarType::Float], VarType::Float));
+        self.globals.var_types.insert(
+            "pow".into(),
+            VarType::new_fn(vec![VarType::Float, VarType::Float], VarType::Float),
+        );
+        self.globals
+            .var_types
+            .insert("abs".into(), VarType::new_fn(vec![VarType::Any], VarType::Any));
+
+        for f in ["floor", "ceil", "round"] {
+            self.globals
+                .var_types
+                .insert(f.into(), VarType::new_fn(vec![VarType::Float], VarType::Int));
+        }
+
+        for f in ["min", "max"] {
+            self.globals
+                .var_types
+                .insert(f.into(), VarType::new_fn(vec![VarType::Any, VarType::Any], VarType::Any));
+        }
+        self.globals.var_types.insert(
+            "clamp".into(),
+            VarType::new_fn(vec![VarType::Any, VarType::Any, VarType::Any], VarType::Any),
+        );
+
+        for f in ["max_of", "min_of"] {
+            self.globals.var_types.insert(
+                f.into(),
+                VarType::new_fn(vec![VarType::Array(Box::new(VarType::Any))], VarType::Any),
+            );
+        }
+
+        self.globals.var_types.insert(
+            "substr".into(),
+            VarType::new_fn(vec![VarType::Str, VarType::Int, VarType::Int], VarType::Str),
+        );
+        self.globals.var_types.insert(
+            "index_of".into(),
+            VarType::new_fn(vec![VarType::Str, VarType::Str], VarType::Int),
+        );
+
+        for f in ["to_upper", "to_lower"] {
+            self.globals
+                .var_types
+                .insert(f.into(), VarType::new_fn(vec![VarType::Str], VarType::Str));
+        }
+
+        self.globals.var_types.insert(
+            "split".into(),
+            VarType::new_fn(
+                vec![VarType::Str, VarType::Str],
+                VarType::Array(Box::new(VarType::Str)),
+            ),
+        );
+
+        // Variadic, calls are checked in 'visit_call_expr'
+        self.globals
+            .var_types
+            .insert("format".into(), VarType::new_fn(vec![VarType::Str], VarType::Str));
 
-        for t in ["any", "int", "float", "str", "bool", "void"] {
+        self.globals
+            .var_types
+            .insert("to_str".into(), VarType::new_fn(vec![VarType::Any], VarType::Str));
+        self.globals
+            .var_types
+            .insert("repr".into(), VarType::new_fn(vec![VarType::Any], VarType::Str));
+        self.globals
+            .var_types
+            .insert("to_int".into(), VarType::new_fn(vec![VarType::Any], VarType::Int));
+        self.globals
+            .var_types
+            .insert("to_float".into(), VarType::new_fn(vec![VarType::Any], VarType::Float));
+
+        self.natives_overloads.insert(
+            "assert".into(),
+            vec![
+                FnType::new(vec![VarType::Bool], VarType::Void),
+                FnType::new(vec![VarType::Bool, VarType::Str], VarType::Void),
+                // Lazy message, only built if the assertion fails
+                FnType::new(vec![VarType::Bool, VarType::new_fn(vec![], VarType::Str)], VarType::Void),
+            ],
+        );
+        // The element type is returned, only numeric arrays are ordered
+        for f in ["max_of", "min_of"] {
+            self.natives_overloads.insert(
+                f.into(),
+                vec![
+                    FnType::new(vec![VarType::Array(Box::new(VarType::Int))], VarType::Int),
+                    FnType::new(vec![VarType::Array(Box::new(VarType::Float))], VarType::Float),
+                ],
+            );
+        }
+        // Ints are widened to float like in binary operations
+        for f in ["min", "max"] {
+            self.natives_overloads.insert(
+                f.into(),
+                vec![
+                    FnType::new(vec![VarType::Int, VarType::Int], VarType::Int),
+                    FnType::new(vec![VarType::Float, VarType::Float], VarType::Float),
+                ],
+            );
+        }
+        self.natives_overloads.insert(
+            "clamp".into(),
+            vec![
+                FnType::new(vec![VarType::Int, VarType::Int, VarType::Int], VarType::Int),
+                FnType::new(vec![VarType::Float, VarType::Float, VarType::Float], VarType::Float),
+            ],
+        );
+        self.natives_overloads.insert(
+            "abs".into(),
+            vec![
+                FnType::new(vec![VarType::Int], VarType::Int),
+                FnType::new(vec![VarType::Float], VarType::Float),
+            ],
+        );
+        self.natives_overloads.insert(
+            "len".into(),
+            vec![
+                FnType::new(vec![VarType::Str], VarType::Int),
+                FnType::new(vec![VarType::Array(Box::new(VarType::Any))], VarType::Int),
+                FnType::new(
+                    vec![VarType::Map(Box::new(VarType::Any), Box::new(VarType::Any))],
+                    VarType::Int,
+                ),
+                FnType::new(vec![VarType::Range], VarType::Int),
+            ],
+        );
+
+        for t in ["any", "int", "float", "str", "bool", "char", "void"] {
             self.globals
                 .types_def
                 .insert(t.into(), StructType::default());
@@ -405,7 +1081,13 @@ impl StaticAnalyzer {
     }
 
     fn resolve_expr(&mut self, expr: &Expr) -> StaticAnalyzerExprRes {
-        expr.accept(self)
+        let typ = expr.accept(self)?;
+
+        if let Some(types) = &mut self.expr_types {
+            types.insert(expr.get_loc(), typ.clone());
+        }
+
+        Ok(typ)
     }
 
     fn resolve_local(&mut self, loc: &Loc, name: &EcoString) -> Result<(), RizonResResolv> {
@@ -429,35 +1111,35 @@ impl StaticAnalyzer {
     }
 
     fn declare_name(&mut self, name: &EcoString, loc: &Loc, decl_type: &str) -> Result<(), RizonResResolv> {
-        if self.scopes.is_empty() {
-            if self.globals.variables.contains_key(name) {
-                return Err(RizonResult::new(
-                    StaticAnalyzerErr::AlreadyDecl(decl_type.into()),
-                    Some(loc.clone()),
-                ));
-            }
-
-            self.globals.variables.insert(name.clone(), false);
-
-            return Ok(());
-        }
+        let scope = self.scopes.last_mut().unwrap_or(&mut self.globals);
 
-        if self.scopes.last().unwrap().variables.contains_key(name) {
+        if scope.variables.contains_key(name) {
             return Err(RizonResult::new(
                 StaticAnalyzerErr::AlreadyDecl(decl_type.into()),
                 Some(loc.clone()),
             ));
         }
 
-        self.scopes
-            .last_mut()
-            .unwrap()
-            .variables
-            .insert(name.clone(), false);
+        scope.variables.insert(name.clone(), false);
+
+        if decl_type != "variable" {
+            scope.callables.insert(name.clone(), decl_type.into());
+        }
 
         Ok(())
     }
 
+    // Kind of the function, structure or enum the name refers to in the
+    // innermost scope declaring it, if it isn't a variable
+    fn callable_kind(&self, name: &EcoString) -> Option<&EcoString> {
+        self.scopes
+            .iter()
+            .rev()
+            .chain(std::iter::once(&self.globals))
+            .find(|s| s.variables.contains_key(name))
+            .and_then(|s| s.callables.get(name))
+    }
+
     fn define_name(&mut self, name: &EcoString) {
         if self.scopes.is_empty() {
             self.globals.variables.insert(name.clone(), true);
@@ -472,71 +1154,206 @@ impl StaticAnalyzer {
             .insert(name.clone(), true);
     }
 
-    fn resolve_fn(&mut self, stmt: &FnDeclStmt, fn_ctx: FnCtx) -> Result<(), RizonResResolv> {
-        if fn_ctx.kind == FnKind::Init && stmt.return_type.is_some() {
-            return Err(RizonResult::new(
-                StaticAnalyzerErr::ConstructorReturnType,
-                Some(stmt.return_type.as_ref().unwrap().get_loc()),
-            ));
-        }
+    // Known annotations with the arguments they accept. '@inline' is a
+    // hint, '@derive(eq)' is the default structural equality of instances
+    // and '@derive(to_string)' is lowered into a method by normalization
+    fn check_annotations(annotations: &[Annotation], on_struct: bool) -> Result<(), RizonResResolv> {
+        for a in annotations {
+            let name = a.name.value.as_str();
+
+            let (for_struct, valid_args): (bool, &[&str]) = match name {
+                "inline" => (false, &[]),
+                "derive" => (true, &["eq", "to_string"]),
+                _ => {
+                    return Err(RizonResult::new(
+                        StaticAnalyzerErr::UnknownAnnotation(name.into()),
+                        Some(a.name.loc.clone()),
+                    ))
+                }
+            };
 
-        let prev_fn_ctx = std::mem::replace(&mut self.fn_ctx, fn_ctx);
+            if for_struct != on_struct {
+                let target = if on_struct { "a structure" } else { "a function" };
 
-        self.begin_scope();
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::MisplacedAnnotation(name.into(), target.into()),
+                    Some(a.loc.clone()),
+                ));
+            }
 
-        for p in stmt.params.iter() {
-            self.declare_name(&p.name.value, &stmt.name.loc, "variable")?;
-            self.define_name(&p.name.value);
-            self.init_var_type(&p.name.value, (&p.typ).into());
+            if let Some(arg) = a.args.iter().find(|arg| !valid_args.contains(&arg.value.as_str())) {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::InvalidAnnotationArg(name.into(), arg.value.to_string()),
+                    Some(arg.loc.clone()),
+                ));
+            }
         }
 
-        let mut return_type = None;
-        let mut end_reached = false;
+        Ok(())
+    }
 
-        for stmt in &stmt.body.stmts {
-            if return_type.is_some() || end_reached {
-                self.warnings.push(Warning::new(StaticAnalyzerWarning::UnreachAfterReturn, stmt.get_loc()));
-            }
+    fn resolve_loop_body(&mut self, body: &Stmt) -> StaticAnalyzerRes {
+        self.fn_ctx.loop_depth += 1;
+        let res = body.accept(self);
+        self.fn_ctx.loop_depth -= 1;
 
-            if let Stmt::Return(r) = stmt {
-                if let Some(v) = &r.value {
-                    return_type = Some(v.accept(self)?);
-                }
-            }
+        res
+    }
 
-            end_reached = end_reached || stmt.accept(self)?;
+    fn resolve_condition(&mut self, condition: &Expr) -> Result<(), RizonResResolv> {
+        let cond_type = self.resolve_expr(condition)?;
+
+        if cond_type != VarType::Bool {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::NonBoolCondition(cond_type.to_string()),
+                Some(condition.get_loc()),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn check_in_loop(&self, keyword: &str, loc: &Loc) -> Result<(), RizonResResolv> {
+        if self.fn_ctx.loop_depth == 0 {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::JumpOutsideLoop(keyword.into()),
+                Some(loc.clone()),
+            ));
         }
 
-        match (return_type, &stmt.return_type) {
-            (None, Some(r)) if !end_reached => {
-                if Into::<VarType>::into(r) != VarType::Void {
+        Ok(())
+    }
+
+    // Loop placeholders are declared here too, typed after by their iterable
+    fn resolve_var_decl(&mut self, stmt: &VarDeclStmt) -> StaticAnalyzerRes {
+        self.declare_name(&stmt.name.value, &stmt.name.loc, "variable")?;
+
+        let mut final_type = match &stmt.typ {
+            Some(t) => {
+                self.check_type_decl_exists(t)?;
+                self.normalize_type(t.into())
+            }
+            None => VarType::Any,
+        };
+
+        if let Some(v) = &stmt.value {
+            let value_type = self.resolve_expr(v)?;
+
+            if final_type != VarType::Any && final_type != value_type {
+                // We allow passing int values to float types
+                if !self.is_castable(&value_type, &final_type) {
                     return Err(RizonResult::new(
-                        StaticAnalyzerErr::NotAllPathReturn(
-                            Into::<VarType>::into(r).to_string(),
-                            stmt.name.value.to_string()
+                        StaticAnalyzerErr::WrongTypeAssign(
+                            value_type.to_string(),
+                            final_type.to_string(),
                         ),
-                        Some(r.get_loc()),
+                        Some(v.get_loc()),
                     ));
                 }
+            } else if stmt.typ.is_none() {
+                // Only inferred without declared type, 'any' variables stay 'any'
+                final_type = value_type;
             }
-            _ => {}
         }
 
+        self.define_name(&stmt.name.value);
+        self.init_var_type(&stmt.name.value, final_type);
+
+        Ok(false)
+    }
+
+    fn resolve_fn(&mut self, stmt: &FnDeclStmt, fn_ctx: FnCtx) -> Result<(), RizonResResolv> {
+        StaticAnalyzer::check_annotations(&stmt.annotations, false)?;
+
+        if let (FnKind::Init, Some(r)) = (fn_ctx.kind, &stmt.return_type) {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::ConstructorReturnType,
+                Some(r.get_loc()),
+            ));
+        }
+
+        let prev_fn_ctx = std::mem::replace(&mut self.fn_ctx, fn_ctx);
+
+        // The enclosing context is restored even if the body has an error
+        self.begin_scope();
+        let res = self.resolve_fn_body(stmt);
         self.end_scope();
 
         self.fn_ctx = prev_fn_ctx;
 
-        Ok(())
+        res
+    }
+
+    fn resolve_fn_body(&mut self, stmt: &FnDeclStmt) -> Result<(), RizonResResolv> {
+        for p in stmt.params.iter() {
+            self.declare_name(&p.name.value, &stmt.name.loc, "variable")?;
+            self.define_name(&p.name.value);
+            let param_type = self.normalize_type((&p.typ).into());
+            self.init_var_type(&p.name.value, param_type);
+        }
+
+        // Each 'return' is checked against the declared type of the function
+        // in 'visit_return_stmt', only the missing ones are left to check
+        let mut end_reached = false;
+
+        for stmt in &stmt.body.stmts {
+            if end_reached {
+                self.warn(StaticAnalyzerWarning::UnreachAfterReturn, stmt.get_loc());
+            }
+
+            end_reached = end_reached || stmt.accept(self)?;
+        }
+
+        match &stmt.return_type {
+            Some(r) if !end_reached && Into::<VarType>::into(r) != VarType::Void => {
+                Err(RizonResult::new(
+                    StaticAnalyzerErr::NotAllPathReturn(
+                        Into::<VarType>::into(r).to_string(),
+                        stmt.name.value.to_string()
+                    ),
+                    Some(r.get_loc()),
+                ))
+            }
+            _ => Ok(()),
+        }
     }
 
-    fn resolve_fn_type(stmt: &FnDeclStmt) -> VarType {
+    fn resolve_fn_type(&self, stmt: &FnDeclStmt) -> VarType {
         let args_type: Vec<VarType> = stmt.params.iter().map(|p| (&p.typ).into()).collect();
         let return_type: VarType = (&stmt.return_type).into();
 
-        VarType::Fn(Box::new(FnType {
+        self.normalize_type(VarType::Fn(Box::new(FnType {
             args_type,
             return_type,
-        }))
+            param_names: stmt.params.iter().map(|p| p.name.value.clone()).collect(),
+        })))
+    }
+
+    // User defined type names are parsed as structures by default. We check
+    // here if the name refers to an alias or an enum instead
+    fn normalize_type(&self, var_type: VarType) -> VarType {
+        match var_type {
+            VarType::Struct(name) if self.globals.aliases.contains_key(&name) => {
+                self.globals.aliases[&name].clone()
+            }
+            VarType::Struct(name) if self.get_enum_def(&name).is_some() => VarType::Enum(name),
+            VarType::Fn(f) => VarType::Fn(Box::new(FnType {
+                args_type: f.args_type.into_iter().map(|a| self.normalize_type(a)).collect(),
+                return_type: self.normalize_type(f.return_type),
+                param_names: f.param_names,
+            })),
+            VarType::Array(t) => VarType::Array(Box::new(self.normalize_type(*t))),
+            VarType::Map(k, v) => VarType::Map(
+                Box::new(self.normalize_type(*k)),
+                Box::new(self.normalize_type(*v)),
+            ),
+            VarType::Optional(t) => match self.normalize_type(*t) {
+                // An alias to a nullable type can't be nullable twice
+                t @ VarType::Optional(_) => t,
+                t => VarType::Optional(Box::new(t)),
+            },
+            t => t,
+        }
     }
 
     fn declare_type(&mut self, var_type: StructType, loc: &Loc) -> Result<(), RizonResResolv> {
@@ -577,6 +1394,21 @@ impl StaticAnalyzer {
         Ok(())
     }
 
+    fn declare_enum(&mut self, enum_type: EnumType, loc: &Loc) -> Result<(), RizonResResolv> {
+        let scope = self.scopes.last_mut().unwrap_or(&mut self.globals);
+
+        if scope.enums_def.contains_key(&enum_type.name) {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::AlreadyDecl("type".into()),
+                Some(loc.clone()),
+            ));
+        }
+
+        scope.enums_def.insert(enum_type.name.clone(), enum_type);
+
+        Ok(())
+    }
+
     fn init_var_type(&mut self, var_name: &EcoString, var_type: VarType) {
         let target = if self.scopes.is_empty() {
             &mut self.globals.var_types
@@ -587,6 +1419,21 @@ impl StaticAnalyzer {
         target.insert(var_name.clone(), var_type);
     }
 
+    // After an assignment, the variable is only known to be of the
+    // narrowed type if the assigned value is
+    fn forget_narrowing(&mut self, var_name: &EcoString, value_type: &VarType) {
+        for scope in self.scopes.iter_mut().rev() {
+            // Narrowing is never outside of the declaring scope
+            if scope.var_types.contains_key(var_name) {
+                break;
+            }
+
+            if scope.narrowed.get(var_name).is_some_and(|t| t != value_type) {
+                scope.narrowed.remove(var_name);
+            }
+        }
+    }
+
     fn update_var_type(&mut self, var_name: &EcoString, var_type: VarType, loc: &Loc) {
         if let Some(depth) = self.locals.get(loc) {
             if let Some(scope) = self.scopes.iter_mut().rev().nth(*depth) {
@@ -599,7 +1446,10 @@ impl StaticAnalyzer {
     }
 
     fn check_type_exists(&self, type_name: &EcoString, loc: &Loc) -> Result<(), RizonResResolv> {
-        if self.globals.types_def.contains_key(type_name) {
+        if self.globals.types_def.contains_key(type_name)
+            || self.globals.enums_def.contains_key(type_name)
+            || self.globals.aliases.contains_key(type_name)
+        {
             return Ok(());
         }
 
@@ -611,7 +1461,7 @@ impl StaticAnalyzer {
         }
 
         for scope in self.scopes.iter().rev() {
-            if scope.types_def.contains_key(type_name) {
+            if scope.types_def.contains_key(type_name) || scope.enums_def.contains_key(type_name) {
                 return Ok(());
             }
         }
@@ -622,16 +1472,85 @@ impl StaticAnalyzer {
         ))
     }
 
+    fn check_type_decl_exists(&self, type_decl: &VarTypeDecl) -> Result<(), RizonResResolv> {
+        match type_decl {
+            VarTypeDecl::Identifier(i) => self.check_type_exists(&i.value, &i.loc),
+            VarTypeDecl::Fn {
+                param_types,
+                return_type,
+                ..
+            } => {
+                if let Some(r) = return_type {
+                    self.check_type_decl_exists(r)?;
+                }
+
+                param_types
+                    .iter()
+                    .try_for_each(|p| self.check_type_decl_exists(p))
+            }
+            VarTypeDecl::Array { typ, .. } | VarTypeDecl::Optional { typ, .. } => {
+                self.check_type_decl_exists(typ)
+            }
+            VarTypeDecl::Map { key, value, .. } => {
+                self.check_type_decl_exists(key)?;
+                self.check_type_decl_exists(value)
+            }
+        }
+    }
+
+    fn type_decl_references(type_decl: &VarTypeDecl, type_name: &EcoString) -> bool {
+        match type_decl {
+            VarTypeDecl::Identifier(i) => &i.value == type_name,
+            VarTypeDecl::Fn {
+                param_types,
+                return_type,
+                ..
+            } => {
+                param_types
+                    .iter()
+                    .any(|p| StaticAnalyzer::type_decl_references(p, type_name))
+                    || return_type
+                        .as_ref()
+                        .is_some_and(|r| StaticAnalyzer::type_decl_references(r, type_name))
+            }
+            VarTypeDecl::Array { typ, .. } | VarTypeDecl::Optional { typ, .. } => {
+                StaticAnalyzer::type_decl_references(typ, type_name)
+            }
+            VarTypeDecl::Map { key, value, .. } => {
+                StaticAnalyzer::type_decl_references(key, type_name)
+                    || StaticAnalyzer::type_decl_references(value, type_name)
+            }
+        }
+    }
+
     fn get_var_type(&self, var_name: &EcoString, loc: &Loc) -> StaticAnalyzerExprRes {
+        self.find_var_type(var_name, loc, true)
+    }
+
+    // Type the variable was declared with, ignoring narrowing
+    fn get_declared_var_type(&self, var_name: &EcoString, loc: &Loc) -> StaticAnalyzerExprRes {
+        self.find_var_type(var_name, loc, false)
+    }
+
+    fn find_var_type(&self, var_name: &EcoString, loc: &Loc, narrowed: bool) -> StaticAnalyzerExprRes {
         for scope in self.scopes.iter().rev() {
             if let Some(t) = scope.var_types.get(var_name) {
                 return Ok(t.clone());
             }
 
+            if let Some(t) = scope.narrowed.get(var_name).filter(|_| narrowed) {
+                return Ok(t.clone());
+            }
+
             // Case where we call the type directly like: var f = Foo()
             if let Some(t) = scope.types_def.get(var_name) {
                 return Ok((&t.name).into());
             }
+
+            // Case where we access a variant like: var c = Color.Red
+            if let Some(t) = scope.enums_def.get(var_name) {
+                return Ok(VarType::Enum(t.name.clone()));
+            }
         }
 
         if let Some(t) = self.globals.var_types.get(var_name) {
@@ -642,6 +1561,10 @@ impl StaticAnalyzer {
             return Ok((&t.name).into());
         }
 
+        if let Some(t) = self.globals.enums_def.get(var_name) {
+            return Ok(VarType::Enum(t.name.clone()));
+        }
+
         Err(RizonResult::new(StaticAnalyzerErr::VarNonType, Some(loc.clone())))
     }
 
@@ -662,10 +1585,19 @@ impl StaticAnalyzer {
         ))
     }
 
+    fn get_enum_def(&self, enum_name: &EcoString) -> Option<&EnumType> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|s| s.enums_def.get(enum_name))
+            .or_else(|| self.globals.enums_def.get(enum_name))
+    }
+
     fn struct_members_types(
+        &self,
         fields: &[VarDeclStmt],
         methods: &[FnDeclStmt],
-    ) -> Result<(HashMap<EcoString, VarType>, HashMap<EcoString, VarType>), RizonResResolv> {
+    ) -> Result<(MembersTypes, MembersTypes), RizonResResolv> {
         let mut fields_types: HashMap<EcoString, VarType> = HashMap::new();
         let mut methods_types: HashMap<EcoString, VarType> = HashMap::new();
         let mut has_init = false;
@@ -678,7 +1610,7 @@ impl StaticAnalyzer {
                 ));
             }
 
-            let field_type = field.typ.as_ref().map_or(VarType::Any, |t| t.into());
+            let field_type = field.typ.as_ref().map_or(VarType::Any, |t| self.normalize_type(t.into()));
 
             fields_types.insert(field.name.value.clone(), field_type);
         }
@@ -695,167 +1627,680 @@ impl StaticAnalyzer {
                 has_init = true;
             }
 
-            let fn_ctx = StaticAnalyzer::resolve_fn_type(method);
+            let fn_ctx = self.resolve_fn_type(method);
             methods_types.insert(method.name.value.clone(), fn_ctx);
         }
 
         if !has_init {
             methods_types.insert(
                 EcoString::from("init"),
-                VarType::Fn(Box::new(FnType::default())),
+                VarType::Fn(Box::default()),
             );
         }
 
         Ok((fields_types, methods_types))
     }
 
-    fn is_castable(current_type: &VarType, cast_to: &VarType) -> bool {
-        match (current_type, cast_to) {
-            (VarType::Int, VarType::Float) => true,
-            _ => false,
-        }
+    fn required_fields(fields: &[VarDeclStmt], fields_types: &MembersTypes) -> Vec<EcoString> {
+        fields
+            .iter()
+            .filter(|f| {
+                f.value.is_none()
+                    && !matches!(fields_types.get(&f.name.value), Some(VarType::Optional(_) | VarType::Any))
+            })
+            .map(|f| f.name.value.clone())
+            .collect()
     }
 
-    fn begin_scope(&mut self) {
-        self.scopes.push(Scope::default());
-    }
+    // Fields assigned with 'self.field = ...' on every path through the
+    // statements. Loop bodies may not run and code after a 'return' is
+    // never reached
+    fn assigned_fields(stmts: &[Stmt]) -> HashSet<EcoString> {
+        let mut assigned = HashSet::new();
+
+        for stmt in stmts {
+            match stmt {
+                Stmt::Expr(s) => StaticAnalyzer::self_assignments(&s.expr, &mut assigned),
+                Stmt::Block(b) => assigned.extend(StaticAnalyzer::assigned_fields(&b.stmts)),
+                Stmt::If(s) => {
+                    let then_fields = s.then_branch.as_ref().map(|b| StaticAnalyzer::assigned_fields(&b.stmts));
+                    let else_fields = s.else_branch.as_ref().map(|b| StaticAnalyzer::assigned_fields(&b.stmts));
+
+                    if let (Some(t), Some(e)) = (then_fields, else_fields) {
+                        assigned.extend(t.intersection(&e).cloned());
+                    }
+                }
+                Stmt::Return(_) => break,
+                _ => {}
+            }
+        }
 
-    fn end_scope(&mut self) {
-        self.scopes.pop();
+        assigned
     }
-}
 
-impl VisitStmt<bool, StaticAnalyzerErr> for StaticAnalyzer {
-    fn visit_expr_stmt(&mut self, stmt: &ExprStmt) -> StaticAnalyzerRes {
-        self.resolve_expr(&stmt.expr)?;
+    fn self_assignments(expr: &Expr, assigned: &mut HashSet<EcoString>) {
+        // Chained assignments: self.x = self.y = 0
+        if let Expr::Set(s) = expr {
+            if let Expr::Selff(_) = &*s.object {
+                assigned.insert(s.name.value.clone());
+            }
 
-        Ok(false)
+            StaticAnalyzer::self_assignments(&s.value, assigned);
+        }
     }
 
-    fn visit_print_stmt(&mut self, stmt: &PrintStmt) -> StaticAnalyzerRes {
-        match self.resolve_expr(&stmt.expr) {
-            Ok(_) => Ok(false),
-            Err(e) => Err(e),
+    // Int to float widening can be disabled, requiring explicit casts
+    fn is_castable(&self, current_type: &VarType, cast_to: &VarType) -> bool {
+        match (current_type, cast_to) {
+            (VarType::Int, VarType::Float) => !self.no_numeric_widening,
+            // Nullable types accept null and their underlying type
+            (VarType::Null, VarType::Optional(_)) => true,
+            (VarType::Optional(t1), VarType::Optional(t2)) => {
+                t1 == t2 || self.is_castable(t1, t2)
+            }
+            (t1, VarType::Optional(t2)) => {
+                t1 == &**t2 || self.is_castable(t1, t2)
+            }
+            // Empty literals like [] or {} have 'any' as inner types
+            (VarType::Array(t1), VarType::Array(t2)) => {
+                **t1 == VarType::Any || **t2 == VarType::Any
+            }
+            (VarType::Map(k1, v1), VarType::Map(k2, v2)) => {
+                (**k1 == VarType::Any || **k2 == VarType::Any)
+                    && (**v1 == VarType::Any || **v2 == VarType::Any)
+            }
+            _ => false,
         }
     }
 
-    fn visit_var_decl_stmt(&mut self, stmt: &VarDeclStmt) -> StaticAnalyzerRes {
-        self.declare_name(&stmt.name.value, &stmt.name.loc, "variable")?;
+    fn record_widening(&mut self, from: &VarType, to: &VarType, loc: Loc) {
+        let to_float = match to {
+            VarType::Optional(t) => **t == VarType::Float,
+            t => *t == VarType::Float,
+        };
 
-        let mut final_type = match &stmt.typ {
-            Some(VarTypeDecl::Identifier(i)) => {
-                self.check_type_exists(&i.value, &i.loc)?;
-                i.into()
-            }
-            t @ Some(VarTypeDecl::Fn {
-                param_types,
-                return_type,
-                ..
-            }) => {
-                if let Some(r) = return_type {
-                    if let VarTypeDecl::Identifier(i) = &**r {
-                        self.check_type_exists(&i.value, &i.loc)?;
-                    }
-                }
+        if *from == VarType::Int && to_float {
+            self.widened.insert(loc);
+        }
+    }
 
-                param_types
-                    .iter()
-                    .try_for_each(|p| self.check_type_exists(&p.value, &p.loc))?;
+    // Returns the type of the indexed values
+    fn index_type(&mut self, object: &Expr, index: &Expr, loc: &Loc) -> StaticAnalyzerExprRes {
+        let obj_type = self.resolve_expr(object)?;
+        let index_type = self.resolve_expr(index)?;
 
-                t.into()
+        let (expected_index, value_type) = match obj_type {
+            VarType::Array(t) => (VarType::Int, *t),
+            VarType::Map(k, v) => (*k, *v),
+            VarType::Any => return Ok(VarType::Any),
+            t => {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::NonIndexable(t.to_string()),
+                    Some(loc.clone()),
+                ))
             }
-            None => VarType::Any,
         };
 
-        if let Some(v) = &stmt.value {
-            let value_type = self.resolve_expr(v)?;
+        if expected_index != VarType::Any
+            && index_type != expected_index
+            && !self.is_castable(&index_type, &expected_index)
+        {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::WrongIndexType(expected_index.to_string(), index_type.to_string()),
+                Some(index.get_loc()),
+            ));
+        }
 
-            if final_type != VarType::Any && final_type != value_type {
-                // We allow passing int values to float types
-                if !StaticAnalyzer::is_castable(&value_type, &final_type) {
-                    return Err(RizonResult::new(
-                        StaticAnalyzerErr::WrongTypeAssign(
-                            value_type.to_string(),
-                            final_type.to_string(),
-                        ),
-                        Some(v.get_loc()),
-                    ));
-                }
-            } else {
-                final_type = value_type;
+        self.record_widening(&index_type, &expected_index, index.get_loc());
+
+        // Known only for literals, other cases are checked at runtime: [1, 2][5]
+        if let (Expr::ArrayLiteral(a), Some(i)) = (object, StaticAnalyzer::int_literal_value(index)) {
+            if usize::try_from(i).map_or(true, |i| i >= a.values.len()) {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::IndexOutOfRange(i, a.values.len()),
+                    Some(index.get_loc()),
+                ));
             }
         }
 
-        self.define_name(&stmt.name.value);
-        self.init_var_type(&stmt.name.value, final_type);
-
-        Ok(false)
+        Ok(value_type)
     }
 
-    fn visit_block_stmt(&mut self, stmt: &BlockStmt) -> StaticAnalyzerRes {
-        self.begin_scope();
-
-        let mut end_reached = false;
-        for stmt in &stmt.stmts {
-            if end_reached {
-                self.warnings.push(Warning::new(StaticAnalyzerWarning::UnreachAfterReturn, stmt.get_loc()));
+    fn int_literal_value(expr: &Expr) -> Option<i64> {
+        match expr {
+            Expr::IntLiteral(i) => Some(i.value),
+            Expr::Unary(u) if u.operator.kind == TokenKind::Minus => {
+                StaticAnalyzer::int_literal_value(&u.right).and_then(i64::checked_neg)
             }
-
-            end_reached = end_reached || stmt.accept(self)?;
+            Expr::Grouping(g) => StaticAnalyzer::int_literal_value(&g.expr),
+            _ => None,
         }
+    }
 
-        self.end_scope();
+    // Name of the called native function if it isn't shadowed
+    fn get_native_name<'a>(&self, callee: &'a Expr) -> Option<&'a EcoString> {
+        let Expr::Identifier(ident) = callee else {
+            return None;
+        };
 
-        Ok(end_reached)
+        if self.scopes.iter().any(|s| s.variables.contains_key(&ident.name)) {
+            return None;
+        }
+
+        Some(&ident.name)
     }
 
-    fn visit_if_stmt(&mut self, stmt: &IfStmt) -> StaticAnalyzerRes {
-        stmt.condition.accept(self)?;
+    // Overloads of the called native function if it isn't shadowed
+    fn get_native_overloads(&self, callee: &Expr) -> Option<&Vec<FnType>> {
+        self.natives_overloads.get(self.get_native_name(callee)?)
+    }
 
-        let complete_then = if let Some(t) = &stmt.then_branch {
-            t.accept(self)?
-        } else {
-            false
+    // Generics-lite: in the return type, 'any' is the type it takes in the
+    // first argument. fn first(xs: [any]) -> any returns an int for an [int]
+    fn call_return_type(callee_type: &VarType, call_args: &[VarType]) -> VarType {
+        let VarType::Fn(f) = callee_type else {
+            return callee_type.clone().into_fn_return_type();
         };
 
-        let complete_else = if let Some(e) = &stmt.else_branch {
-            e.accept(self)?
-        } else {
-            false
+        let binding = match (f.args_type.first(), call_args.first()) {
+            (Some(decl @ VarType::Fn(_)), Some(arg)) => StaticAnalyzer::bind_any(decl, arg),
+            (Some(decl), Some(arg)) => StaticAnalyzer::bind_any(decl, &arg.clone().into_fn_return_type()),
+            _ => None,
         };
 
-        Ok(complete_then && complete_else)
+        match binding {
+            Some(t) => StaticAnalyzer::replace_any(&f.return_type, &t),
+            None => f.return_type.clone(),
+        }
     }
 
-    fn visit_while_stmt(&mut self, stmt: &WhileStmt) -> StaticAnalyzerRes {
-        stmt.condition.accept(self)?;
-        stmt.body.accept(self)?;
-
-        Ok(false)
+    // Type matching 'any' in the declared type
+    fn bind_any(decl: &VarType, arg: &VarType) -> Option<VarType> {
+        match (decl, arg) {
+            (VarType::Any, VarType::Any) => None,
+            (VarType::Any, t) => Some(t.clone()),
+            (VarType::Array(d), VarType::Array(a)) | (VarType::Optional(d), VarType::Optional(a)) => {
+                StaticAnalyzer::bind_any(d, a)
+            }
+            (VarType::Map(dk, dv), VarType::Map(ak, av)) => {
+                StaticAnalyzer::bind_any(dk, ak).or_else(|| StaticAnalyzer::bind_any(dv, av))
+            }
+            _ => None,
+        }
     }
 
-    fn visit_for_stmt(&mut self, stmt: &ForStmt) -> StaticAnalyzerRes {
-        self.begin_scope();
-        self.visit_var_decl_stmt(&stmt.placeholder)?;
-        self.init_var_type(&stmt.placeholder.name.value, VarType::Int);
-        stmt.body.accept(self)?;
-        self.end_scope();
-
-        Ok(false)
+    fn replace_any(typ: &VarType, by: &VarType) -> VarType {
+        match typ {
+            VarType::Any => by.clone(),
+            VarType::Array(t) => VarType::Array(Box::new(StaticAnalyzer::replace_any(t, by))),
+            VarType::Map(k, v) => VarType::Map(
+                Box::new(StaticAnalyzer::replace_any(k, by)),
+                Box::new(StaticAnalyzer::replace_any(v, by)),
+            ),
+            VarType::Optional(t) => VarType::Optional(Box::new(StaticAnalyzer::replace_any(t, by))),
+            t => t.clone(),
+        }
     }
 
-    fn visit_fn_decl_stmt(&mut self, stmt: &FnDeclStmt) -> StaticAnalyzerRes {
-        self.declare_name(&stmt.name.value, &stmt.name.loc, "function")?;
-        self.define_name(&stmt.name.value);
-
-        let return_type = StaticAnalyzer::resolve_fn_type(stmt);
-        self.init_var_type(&stmt.name.value, return_type.clone());
+    // map(values: [T], f: fn(T) -> U) -> [U]
+    fn resolve_map_call(&self, call_args: &[VarType], loc: &Loc) -> StaticAnalyzerExprRes {
+        let [values, callback] = call_args else {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::WrongArgsNb(2, call_args.len()),
+                Some(loc.clone()),
+            ));
+        };
 
-        self.resolve_fn(
+        let VarType::Array(elem) = values else {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::WrongArgsType("array".into(), values.to_string()),
+                Some(loc.clone()),
+            ));
+        };
+
+        match callback {
+            VarType::Fn(f)
+                if f.args_type.len() == 1
+                    && (f.args_type[0] == **elem
+                        || f.args_type[0] == VarType::Any
+                        || self.is_castable(elem, &f.args_type[0])) =>
+            {
+                Ok(VarType::Array(Box::new(f.return_type.clone())))
+            }
+            _ => Err(RizonResult::new(
+                StaticAnalyzerErr::WrongArgsType(format!("fn({}) -> any", elem), callback.to_string()),
+                Some(loc.clone()),
+            )),
+        }
+    }
+
+    // Without tuples, entries are [key, value] arrays. They are typed precisely
+    // only when keys and values share the same type
+    fn resolve_to_array_call(call_args: &[VarType], loc: &Loc) -> StaticAnalyzerExprRes {
+        let [map] = call_args else {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::WrongArgsNb(1, call_args.len()),
+                Some(loc.clone()),
+            ));
+        };
+
+        let VarType::Map(k, v) = map else {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::WrongArgsType("map".into(), map.to_string()),
+                Some(loc.clone()),
+            ));
+        };
+
+        let entry = if k == v { (**k).clone() } else { VarType::Any };
+
+        Ok(VarType::Array(Box::new(VarType::Array(Box::new(entry)))))
+    }
+
+    fn resolve_to_map_call(call_args: &[VarType], loc: &Loc) -> StaticAnalyzerExprRes {
+        let [pairs] = call_args else {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::WrongArgsNb(1, call_args.len()),
+                Some(loc.clone()),
+            ));
+        };
+
+        match pairs {
+            VarType::Array(entry) => match &**entry {
+                VarType::Array(t) => Ok(VarType::Map(t.clone(), t.clone())),
+                _ => Err(RizonResult::new(
+                    StaticAnalyzerErr::WrongArgsType("[[any]]".into(), pairs.to_string()),
+                    Some(loc.clone()),
+                )),
+            },
+            _ => Err(RizonResult::new(
+                StaticAnalyzerErr::WrongArgsType("[[any]]".into(), pairs.to_string()),
+                Some(loc.clone()),
+            )),
+        }
+    }
+
+    // format(fmt: str, ...) -> str, when the format is a literal its
+    // placeholders are counted against the other arguments
+    fn resolve_format_call(expr: &CallExpr, call_args: &[VarType]) -> StaticAnalyzerExprRes {
+        let Some(fmt) = call_args.first() else {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::WrongArgsNb(1, 0),
+                Some(expr.loc.clone()),
+            ));
+        };
+
+        if fmt != &VarType::Str {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::WrongArgsType("str".into(), fmt.to_string()),
+                Some(expr.args[0].get_loc()),
+            ));
+        }
+
+        if let Expr::StrLiteral(s) = &expr.args[0] {
+            let placeholders = format_segments(&s.value).len() - 1;
+
+            if placeholders != call_args.len() - 1 {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::FormatArgMismatch(placeholders, call_args.len() - 1),
+                    Some(expr.loc.clone()),
+                ));
+            }
+        }
+
+        Ok(VarType::Str)
+    }
+
+    // Reorders the types of the arguments to match the parameters, named
+    // arguments can only follow the positional ones
+    fn order_named_args(fn_ctx: &FnType, expr: &CallExpr, call_args: Vec<VarType>) -> Result<Vec<VarType>, RizonResResolv> {
+        if fn_ctx.param_names.is_empty() {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::NamedArgsNotAllowed,
+                Some(expr.loc.clone()),
+            ));
+        }
+
+        let mut ordered: Vec<Option<VarType>> = vec![None; fn_ctx.param_names.len().max(call_args.len())];
+
+        for (i, (arg, name)) in call_args.into_iter().zip(&expr.arg_names).enumerate() {
+            let idx = match name {
+                Some(name) => fn_ctx
+                    .param_names
+                    .iter()
+                    .position(|p| p == &name.value)
+                    .ok_or_else(|| {
+                        RizonResult::new(
+                            StaticAnalyzerErr::UnknownArgName(name.value.to_string()),
+                            Some(name.loc.clone()),
+                        )
+                    })?,
+                None => i,
+            };
+
+            if ordered[idx].is_some() {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::DuplicateArgName(fn_ctx.param_names[idx].to_string()),
+                    Some(name.as_ref().map_or(expr.loc.clone(), |n| n.loc.clone())),
+                ));
+            }
+
+            ordered[idx] = Some(arg);
+        }
+
+        // Missing arguments are reported by the arguments count check
+        Ok(ordered.into_iter().flatten().collect())
+    }
+
+    // Returns the return type of the first overload matching the arguments
+    fn resolve_overload(&self, overloads: &[FnType], call_args: &[VarType], loc: &Loc) -> StaticAnalyzerExprRes {
+        let matches = |f: &FnType| {
+            f.args_type.len() == call_args.len()
+                && f.args_type.iter().zip(call_args).all(|(decl, arg)| {
+                    let arg = arg.clone().into_fn_return_type();
+                    &arg == decl || self.is_castable(&arg, decl)
+                })
+        };
+
+        if let Some(f) = overloads.iter().find(|f| matches(f)) {
+            return Ok(f.return_type.clone());
+        }
+
+        if let Some(f) = overloads.iter().find(|f| f.args_type.len() != call_args.len()) {
+            if overloads.iter().all(|o| o.args_type.len() == f.args_type.len()) {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::WrongArgsNb(f.args_type.len(), call_args.len()),
+                    Some(loc.clone()),
+                ));
+            }
+        }
+
+        let expected: Vec<String> = overloads
+            .iter()
+            .map(|f| f.args_type.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", "))
+            .collect();
+        let found: Vec<String> = call_args.iter().map(|a| a.to_string()).collect();
+
+        Err(RizonResult::new(
+            StaticAnalyzerErr::WrongArgsType(expected.join("' or '"), found.join(", ")),
+            Some(loc.clone()),
+        ))
+    }
+
+    // Type of an ordering comparison: <, >, <= or >=
+    fn resolve_ordering(
+        &mut self,
+        lhs_type: &VarType,
+        rhs_type: &VarType,
+        operator: &Token,
+        loc: Loc,
+    ) -> StaticAnalyzerExprRes {
+        match (lhs_type, rhs_type) {
+            (VarType::Int, VarType::Int)
+            | (VarType::Float, VarType::Float)
+            | (VarType::Char, VarType::Char)
+            | (VarType::Str, VarType::Str) => Ok(VarType::Bool),
+            (VarType::Int, VarType::Float) | (VarType::Float, VarType::Int) => {
+                self.warn(StaticAnalyzerWarning::CompIntFloat, loc);
+
+                Ok(VarType::Bool)
+            }
+            _ => Err(RizonResult::new(
+                StaticAnalyzerErr::InvalidOp(operator.value.to_string(), lhs_type.to_string(), rhs_type.to_string()),
+                Some(loc),
+            )),
+        }
+    }
+
+    // Value of integer expressions known at compile time, made of literals
+    // and arithmetic operators. Errors if it isn't constant, divides by zero
+    // or overflows
+    pub fn const_eval(expr: &Expr) -> Result<i64, RizonResResolv> {
+        let err = |kind: StaticAnalyzerErr| Err(RizonResult::new(kind, Some(expr.get_loc())));
+
+        match expr {
+            Expr::IntLiteral(i) => Ok(i.value),
+            Expr::Grouping(g) => StaticAnalyzer::const_eval(&g.expr),
+            Expr::Unary(u) if u.operator.kind == TokenKind::Minus => {
+                match StaticAnalyzer::const_eval(&u.right)?.checked_neg() {
+                    Some(v) => Ok(v),
+                    None => err(StaticAnalyzerErr::ConstOverflow),
+                }
+            }
+            Expr::Binary(b) => {
+                let (l, r) = (StaticAnalyzer::const_eval(&b.left)?, StaticAnalyzer::const_eval(&b.right)?);
+
+                let res = match b.operator.kind {
+                    TokenKind::Plus => l.checked_add(r),
+                    TokenKind::Minus => l.checked_sub(r),
+                    TokenKind::Star => l.checked_mul(r),
+                    TokenKind::Slash if r == 0 => return err(StaticAnalyzerErr::ConstDivisionByZero),
+                    TokenKind::Slash => l.checked_div(r),
+                    // Negative exponents give a float
+                    TokenKind::StarStar if r < 0 => return err(StaticAnalyzerErr::NonConstVariantValue),
+                    TokenKind::StarStar => u32::try_from(r).ok().and_then(|e| l.checked_pow(e)),
+                    _ => return err(StaticAnalyzerErr::NonConstVariantValue),
+                };
+
+                match res {
+                    Some(v) => Ok(v),
+                    None => err(StaticAnalyzerErr::ConstOverflow),
+                }
+            }
+            _ => err(StaticAnalyzerErr::NonConstVariantValue),
+        }
+    }
+
+    // Returns the variable compared to null and if the comparison
+    // is '!=', in conditions like: a != null
+    fn null_check(cond: &Expr) -> Option<(&IdentifierExpr, bool)> {
+        let Expr::Binary(b) = cond else {
+            return None;
+        };
+
+        let is_not_null = match b.operator.kind {
+            TokenKind::BangEqual => true,
+            TokenKind::EqualEqual => false,
+            _ => return None,
+        };
+
+        match (&*b.left, &*b.right) {
+            (Expr::Identifier(v), Expr::Identifier(n)) | (Expr::Identifier(n), Expr::Identifier(v))
+                if n.name == "null" && v.name != "null" =>
+            {
+                Some((v, is_not_null))
+            }
+            _ => None,
+        }
+    }
+
+    // Resolves a block, with variables which type is known to be
+    // more precise inside it (ex: not null)
+    fn resolve_block(&mut self, stmt: &BlockStmt, narrowed: Vec<(EcoString, VarType)>) -> StaticAnalyzerRes {
+        self.begin_scope();
+
+        let scope = self.scopes.last_mut().unwrap();
+        scope.narrowed.extend(narrowed);
+
+        // Only the first statement after a 'break' or 'continue' is reported
+        let after_jump = stmt
+            .stmts
+            .iter()
+            .position(|s| matches!(s, Stmt::Break(_) | Stmt::Continue(_)))
+            .map(|i| i + 1);
+
+        let mut end_reached = false;
+        for (i, stmt) in stmt.stmts.iter().enumerate() {
+            if end_reached {
+                self.warn(StaticAnalyzerWarning::UnreachAfterReturn, stmt.get_loc());
+            } else if after_jump == Some(i) {
+                self.warn(StaticAnalyzerWarning::UnreachAfterBreak, stmt.get_loc());
+            }
+
+            end_reached = end_reached || stmt.accept(self)?;
+        }
+
+        self.end_scope();
+
+        Ok(end_reached)
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+}
+
+impl VisitStmt<bool, StaticAnalyzerErr> for StaticAnalyzer {
+    fn visit_expr_stmt(&mut self, stmt: &ExprStmt) -> StaticAnalyzerRes {
+        self.resolve_expr(&stmt.expr)?;
+
+        Ok(false)
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &PrintStmt) -> StaticAnalyzerRes {
+        for e in &stmt.exprs {
+            self.resolve_expr(e)?;
+        }
+
+        Ok(false)
+    }
+
+    fn visit_var_decl_stmt(&mut self, stmt: &VarDeclStmt) -> StaticAnalyzerRes {
+        if stmt.typ.is_none() && stmt.value.is_none() {
+            self.warn(StaticAnalyzerWarning::ImplicitAny, stmt.name.loc.clone());
+        }
+
+        self.resolve_var_decl(stmt)
+    }
+
+    fn visit_block_stmt(&mut self, stmt: &BlockStmt) -> StaticAnalyzerRes {
+        self.resolve_block(stmt, vec![])
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &IfStmt) -> StaticAnalyzerRes {
+        self.resolve_condition(&stmt.condition)?;
+
+        // Nullable variables checked against null are not nullable in
+        // the corresponding branch
+        let mut then_narrowed = vec![];
+        let mut else_narrowed = vec![];
+
+        if let Some((var, is_not_null)) = StaticAnalyzer::null_check(&stmt.condition) {
+            if let VarType::Optional(t) = self.get_var_type(&var.name, &var.loc)? {
+                let narrowed = vec![(var.name.clone(), *t)];
+
+                if is_not_null {
+                    then_narrowed = narrowed;
+                } else {
+                    else_narrowed = narrowed;
+                }
+            }
+        }
+
+        // Same for 'any' and nullable variables checked with 'is'
+        if let Expr::Is(IsExpr { left, typ, .. }) = &stmt.condition {
+            if let Expr::Identifier(var) = &**left {
+                let checked = self.normalize_type(typ.into());
+
+                match self.get_var_type(&var.name, &var.loc)? {
+                    VarType::Any if checked != VarType::Null => {
+                        then_narrowed = vec![(var.name.clone(), checked)];
+                    }
+                    VarType::Optional(t) if checked == VarType::Null => {
+                        else_narrowed = vec![(var.name.clone(), *t)];
+                    }
+                    VarType::Optional(t) if *t == checked => {
+                        then_narrowed = vec![(var.name.clone(), *t)];
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let complete_then = if let Some(t) = &stmt.then_branch {
+            self.resolve_block(t, then_narrowed)?
+        } else {
+            false
+        };
+
+        let complete_else = if let Some(e) = &stmt.else_branch {
+            self.resolve_block(e, else_narrowed)?
+        } else {
+            false
+        };
+
+        Ok(complete_then && complete_else)
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &WhileStmt) -> StaticAnalyzerRes {
+        self.resolve_condition(&stmt.condition)?;
+        self.resolve_loop_body(&stmt.body)?;
+
+        Ok(false)
+    }
+
+    fn visit_do_while_stmt(&mut self, stmt: &DoWhileStmt) -> StaticAnalyzerRes {
+        self.resolve_loop_body(&stmt.body)?;
+        self.resolve_condition(&stmt.condition)?;
+
+        Ok(false)
+    }
+
+    fn visit_for_stmt(&mut self, stmt: &ForStmt) -> StaticAnalyzerRes {
+        let placeholder_type = match self.resolve_expr(&stmt.iterable)? {
+            VarType::Range => VarType::Int,
+            VarType::Array(t) => *t,
+            VarType::Map(k, _) => *k,
+            t => {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::NonIterable(t.to_string()),
+                    Some(stmt.iterable.get_loc()),
+                ))
+            }
+        };
+
+        self.begin_scope();
+        self.resolve_var_decl(&stmt.placeholder)?;
+        self.init_var_type(&stmt.placeholder.name.value, placeholder_type);
+        let res = self.resolve_loop_body(&stmt.body);
+        self.end_scope();
+        res?;
+
+        Ok(false)
+    }
+
+    fn visit_break_stmt(&mut self, stmt: &BreakStmt) -> StaticAnalyzerRes {
+        self.check_in_loop("break", &stmt.loc)?;
+
+        Ok(false)
+    }
+
+    fn visit_continue_stmt(&mut self, stmt: &ContinueStmt) -> StaticAnalyzerRes {
+        self.check_in_loop("continue", &stmt.loc)?;
+
+        Ok(false)
+    }
+
+    fn visit_fn_decl_stmt(&mut self, stmt: &FnDeclStmt) -> StaticAnalyzerRes {
+        if !self.hoisted.contains(&stmt.name.loc) {
+            self.declare_name(&stmt.name.value, &stmt.name.loc, "function")?;
+            self.define_name(&stmt.name.value);
+        }
+
+        // Declared again when hoisted, aliases and enums declared since
+        // change how its signature is normalized
+        let return_type = self.resolve_fn_type(stmt);
+        self.init_var_type(&stmt.name.value, return_type.clone());
+
+        self.resolve_fn(
             stmt,
             FnCtx {
                 kind: FnKind::Function,
                 return_type: return_type.into_fn_return_type(),
+                loop_depth: 0,
             },
         )?;
 
@@ -876,11 +2321,19 @@ impl VisitStmt<bool, StaticAnalyzerErr> for StaticAnalyzer {
                     Some(stmt.loc.clone()),
                 ))
             }
+            _ if stmt.value.is_none() && self.fn_ctx.return_type != VarType::Void => {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::MissingReturnValue(self.fn_ctx.return_type.to_string()),
+                    Some(stmt.loc.clone()),
+                ))
+            }
             _ => {
                 if let Some(v) = &stmt.value {
                     let return_type = self.resolve_expr(v)?;
 
-                    if return_type != self.fn_ctx.return_type {
+                    if return_type != self.fn_ctx.return_type
+                        && !self.is_castable(&return_type, &self.fn_ctx.return_type)
+                    {
                         return Err(RizonResult::new(
                             StaticAnalyzerErr::WrongReturnType(
                                 self.fn_ctx.return_type.to_string(),
@@ -897,20 +2350,45 @@ impl VisitStmt<bool, StaticAnalyzerErr> for StaticAnalyzer {
     }
 
     fn visit_struct_stmt(&mut self, stmt: &StructStmt) -> StaticAnalyzerRes {
+        StaticAnalyzer::check_annotations(&stmt.annotations, true)?;
+
         self.current_struct = Some(stmt.name.value.clone());
 
-        self.declare_name(&stmt.name.value, &stmt.name.loc, "structure")?;
-        self.define_name(&stmt.name.value);
+        let hoisted = self.hoisted.contains(&stmt.name.loc);
 
-        let (fields, methods) = StaticAnalyzer::struct_members_types(&stmt.fields, &stmt.methods)?;
+        if !hoisted {
+            self.declare_name(&stmt.name.value, &stmt.name.loc, "structure")?;
+            self.define_name(&stmt.name.value);
+        }
 
-        let struct_type = StructType {
-            name: stmt.name.value.clone(),
-            fields,
-            methods,
-        };
+        let (fields, methods) = self.struct_members_types(&stmt.fields, &stmt.methods)?;
+
+        let struct_type = StructType::new(stmt, fields, methods);
+        let required_fields = struct_type.required_fields.clone();
+        let init = stmt.methods.iter().find(|m| m.name.value == "init");
+
+        // Defaults are evaluated in the scope of the declaration
+        for field in &stmt.fields {
+            let Some(value) = &field.value else {
+                continue;
+            };
+
+            let value_type = self.resolve_expr(value)?;
+            let field_type = &struct_type.fields[&field.name.value];
+
+            if field_type != &value_type && !self.is_castable(&value_type, field_type) {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::WrongTypeAssign(value_type.to_string(), field_type.to_string()),
+                    Some(value.get_loc()),
+                ));
+            }
+        }
 
-        self.declare_type(struct_type, &stmt.name.loc)?;
+        if hoisted {
+            self.globals.types_def.insert(struct_type.name.clone(), struct_type);
+        } else {
+            self.declare_type(struct_type, &stmt.name.loc)?;
+        }
 
         self.begin_scope();
         self.scopes
@@ -930,7 +2408,8 @@ impl VisitStmt<bool, StaticAnalyzerErr> for StaticAnalyzer {
                 m,
                 FnCtx {
                     kind,
-                    return_type: (&m.return_type).into(),
+                    return_type: self.normalize_type((&m.return_type).into()),
+                    loop_depth: 0,
                 },
             )
         })?;
@@ -938,8 +2417,174 @@ impl VisitStmt<bool, StaticAnalyzerErr> for StaticAnalyzer {
         self.end_scope();
         self.current_struct = None;
 
+        // Without 'init', fields are assigned after the instance creation
+        if let Some(init) = init {
+            let assigned = StaticAnalyzer::assigned_fields(&init.body.stmts);
+
+            if let Some(field) = required_fields.iter().find(|f| !assigned.contains(*f)) {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::UninitializedField(stmt.name.value.to_string(), field.to_string()),
+                    Some(init.name.loc.clone()),
+                ));
+            }
+        }
+
         Ok(false)
     }
+
+    fn visit_enum_decl_stmt(&mut self, stmt: &EnumDeclStmt) -> StaticAnalyzerRes {
+        self.declare_name(&stmt.name.value, &stmt.name.loc, "enum")?;
+        self.define_name(&stmt.name.value);
+
+        let mut variants: Vec<EcoString> = vec![];
+        let mut values: Vec<i64> = vec![];
+
+        for variant in &stmt.variants {
+            let name = &variant.name;
+
+            if variants.contains(&name.value) {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::AlreadyDecl("variant".into()),
+                    Some(name.loc.clone()),
+                ));
+            }
+
+            // Like in C, variants without value follow the previous one
+            let value = match &variant.value {
+                Some(e) => StaticAnalyzer::const_eval(e)?,
+                None => match values.last() {
+                    Some(prev) => prev.checked_add(1).ok_or_else(|| {
+                        RizonResult::new(StaticAnalyzerErr::ConstOverflow, Some(name.loc.clone()))
+                    })?,
+                    None => 0,
+                },
+            };
+
+            if let Some(i) = values.iter().position(|v| *v == value) {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::DuplicateVariantValue(value, variants[i].to_string()),
+                    Some(variant.value.as_ref().map_or(name.loc.clone(), |e| e.get_loc())),
+                ));
+            }
+
+            variants.push(name.value.clone());
+            values.push(value);
+        }
+
+        let enum_type = EnumType {
+            name: stmt.name.value.clone(),
+            variants,
+        };
+
+        self.declare_enum(enum_type, &stmt.name.loc)?;
+
+        Ok(false)
+    }
+
+    fn visit_type_alias_stmt(&mut self, stmt: &TypeAliasStmt) -> StaticAnalyzerRes {
+        let name = &stmt.name.value;
+
+        if StaticAnalyzer::type_decl_references(&stmt.typ, name) {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::RecursiveTypeAlias(name.to_string()),
+                Some(stmt.typ.get_loc()),
+            ));
+        }
+
+        if self.check_type_exists(name, &stmt.name.loc).is_ok() {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::AlreadyDecl("type".into()),
+                Some(stmt.name.loc.clone()),
+            ));
+        }
+
+        self.check_type_decl_exists(&stmt.typ)?;
+
+        let alias_type = self.normalize_type((&stmt.typ).into());
+        self.globals.aliases.insert(name.clone(), alias_type);
+
+        Ok(false)
+    }
+
+    fn visit_match_stmt(&mut self, stmt: &MatchStmt) -> StaticAnalyzerRes {
+        let value_type = self.resolve_expr(&stmt.expr)?;
+
+        let mut has_wildcard = false;
+        let mut covered: Vec<EcoString> = vec![];
+        let mut end_reached = true;
+
+        for arm in &stmt.arms {
+            match &arm.pattern {
+                MatchPattern::Wildcard(_) => has_wildcard = true,
+                MatchPattern::Value(p) => {
+                    let pattern_type = self.resolve_expr(p)?;
+
+                    if value_type != VarType::Any
+                        && pattern_type != value_type
+                        && !self.is_castable(&pattern_type, &value_type)
+                    {
+                        return Err(RizonResult::new(
+                            StaticAnalyzerErr::WrongMatchPatternType(
+                                pattern_type.to_string(),
+                                value_type.to_string(),
+                            ),
+                            Some(p.get_loc()),
+                        ));
+                    }
+
+                    // Used to check exhaustiveness over enums and bools
+                    match p {
+                        Expr::Get(g) => {
+                            if !matches!(pattern_type, VarType::Enum(_)) {
+                                return Err(RizonResult::new(
+                                    StaticAnalyzerErr::NonVariantMatchPattern(
+                                        pattern_type.to_string(),
+                                    ),
+                                    Some(p.get_loc()),
+                                ));
+                            }
+
+                            covered.push(g.name.value.clone());
+                        }
+                        Expr::Identifier(i) => covered.push(i.name.clone()),
+                        _ => {}
+                    }
+                }
+            }
+
+            end_reached &= arm.body.accept(self)?;
+        }
+
+        if !has_wildcard {
+            let missing: Vec<String> = match &value_type {
+                VarType::Enum(e) => self
+                    .get_enum_def(e)
+                    .map(|def| {
+                        def.variants
+                            .iter()
+                            .filter(|v| !covered.contains(v))
+                            .map(|v| format!("{}.{}", e, v))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                VarType::Bool => ["true", "false"]
+                    .into_iter()
+                    .filter(|b| !covered.contains(&EcoString::from(*b)))
+                    .map(String::from)
+                    .collect(),
+                _ => vec!["'_'".into()],
+            };
+
+            if !missing.is_empty() {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::NonExhaustiveMatch(missing.join(", ")),
+                    Some(stmt.expr.get_loc()),
+                ));
+            }
+        }
+
+        Ok(end_reached && !stmt.arms.is_empty())
+    }
 }
 
 impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
@@ -947,6 +2592,14 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
         let lhs_type = self.resolve_expr(&expr.left)?.into_fn_return_type();
         let rhs_type = self.resolve_expr(&expr.right)?.into_fn_return_type();
 
+        // Only integer operations fail, floats give 'inf' or 'nan'
+        if matches!(expr.operator.kind, TokenKind::Slash | TokenKind::TildeSlash | TokenKind::Modulo)
+            && lhs_type == VarType::Int
+            && StaticAnalyzer::int_literal_value(&expr.right) == Some(0)
+        {
+            self.warn(StaticAnalyzerWarning::DivisionByZero, expr.right.get_loc());
+        }
+
         let invalid_op_error = |op: &str| {
             RizonResult::new(
                 StaticAnalyzerErr::InvalidOp(op.into(), lhs_type.to_string(), rhs_type.to_string()),
@@ -970,6 +2623,11 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
                     _ => Err(invalid_op_error(expr.operator.value.as_str())),
                 }
             }
+            // Integer division, '/' gives a float with a float operand
+            TokenKind::TildeSlash => match (&lhs_type, &rhs_type) {
+                (VarType::Int, VarType::Int) => Ok(VarType::Int),
+                _ => Err(invalid_op_error("~/")),
+            },
             TokenKind::Star => match (&lhs_type, &rhs_type) {
                 (VarType::Int, VarType::Int) => Ok(VarType::Int),
                 (VarType::Int, VarType::Float)
@@ -977,31 +2635,53 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
                 (VarType::Int, VarType::Str) | (VarType::Str, VarType::Int) => Ok(VarType::Str),
                 _ => Err(invalid_op_error("*")),
             },
+            TokenKind::StarStar => match (&lhs_type, &rhs_type) {
+                // Negative exponents give a float. If the exponent is only known
+                // at runtime, the result is always a float
+                (VarType::Int, VarType::Int) => match StaticAnalyzer::const_eval(&expr.right) {
+                    Ok(e) if e >= 0 => Ok(VarType::Int),
+                    Ok(_) => Ok(VarType::Float),
+                    Err(_) => {
+                        self.widened.insert(expr.get_loc());
+                        Ok(VarType::Float)
+                    }
+                },
+                (VarType::Int | VarType::Float, VarType::Int | VarType::Float) => Ok(VarType::Float),
+                _ => Err(invalid_op_error("**")),
+            },
             TokenKind::Less
             | TokenKind::Greater
             | TokenKind::LessEqual
-            | TokenKind::GreaterEqual => match (&lhs_type, &rhs_type) {
-                (VarType::Int, VarType::Int) | (VarType::Float, VarType::Float) => {
-                    Ok(VarType::Bool)
-                }
-                (VarType::Int, VarType::Float) | (VarType::Float, VarType::Int) => {
-                    self.warnings.push(Warning::new(StaticAnalyzerWarning::CompIntFloat, expr.get_loc()));
-
-                    Ok(VarType::Bool)
-                }
-                _ => Err(invalid_op_error(expr.operator.value.as_str())),
-            },
+            | TokenKind::GreaterEqual => {
+                self.resolve_ordering(&lhs_type, &rhs_type, &expr.operator, expr.get_loc())
+            }
             TokenKind::EqualEqual | TokenKind::BangEqual => match (&lhs_type, &rhs_type) {
                 (VarType::Int, VarType::Int)
                 | (VarType::Float, VarType::Float)
                 | (VarType::Str, VarType::Str)
-                | (VarType::Bool, VarType::Bool) => Ok(VarType::Bool),
+                | (VarType::Bool, VarType::Bool)
+                | (VarType::Char, VarType::Char) => Ok(VarType::Bool),
                 (VarType::Int, VarType::Float) | (VarType::Float, VarType::Int) => {
-                    self.warnings.push(Warning::new(StaticAnalyzerWarning::CompIntFloat, expr.get_loc()));
+                    self.warn(StaticAnalyzerWarning::CompIntFloat, expr.get_loc());
 
                     Ok(VarType::Bool)
                 }
-                (VarType::Struct(_), VarType::Struct(_)) => Ok(VarType::Bool),
+                (VarType::Struct(s1), VarType::Struct(s2)) if s1 == s2 => Ok(VarType::Bool),
+                // Empty literals have 'any' as inner types: [] == [1]
+                (VarType::Array(_), VarType::Array(_)) | (VarType::Map(..), VarType::Map(..))
+                    if lhs_type == rhs_type
+                        || self.is_castable(&lhs_type, &rhs_type)
+                        || self.is_castable(&rhs_type, &lhs_type) =>
+                {
+                    Ok(VarType::Bool)
+                }
+                (VarType::Enum(e1), VarType::Enum(e2)) if e1 == e2 => Ok(VarType::Bool),
+                (VarType::Optional(_), VarType::Null)
+                | (VarType::Null, VarType::Optional(_))
+                | (VarType::Null, VarType::Null) => Ok(VarType::Bool),
+                (VarType::Optional(t1), t2) | (t2, VarType::Optional(t1)) if **t1 == *t2 => {
+                    Ok(VarType::Bool)
+                }
                 _ => Err(invalid_op_error(expr.operator.value.as_str())),
             },
             _ => Err(RizonResult::new(
@@ -1027,6 +2707,10 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
         Ok(VarType::Str)
     }
 
+    fn visit_char_literal_expr(&mut self, _: &CharLiteralExpr) -> StaticAnalyzerExprRes {
+        Ok(VarType::Char)
+    }
+
     fn visit_identifier_expr(&mut self, expr: &IdentifierExpr) -> StaticAnalyzerExprRes {
         if !self.scopes.is_empty()
             && self.scopes.last().unwrap().variables.get(&expr.name) == Some(&false)
@@ -1045,21 +2729,17 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
         let val_type = self.resolve_expr(&expr.right)?;
 
         match expr.operator.kind {
-            TokenKind::Minus => {
-                if val_type != VarType::Int && val_type != VarType::Float {
-                    return Err(RizonResult::new(
-                        StaticAnalyzerErr::NonNumMinusUnary,
-                        Some(expr.right.get_loc().clone()),
-                    ));
-                }
+            TokenKind::Minus if val_type != VarType::Int && val_type != VarType::Float => {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::NonNumMinusUnary,
+                    Some(expr.right.get_loc().clone()),
+                ));
             }
-            TokenKind::Bang => {
-                if val_type != VarType::Bool {
-                    return Err(RizonResult::new(
-                        StaticAnalyzerErr::NonBoolBangUnary,
-                        Some(expr.right.get_loc().clone()),
-                    ));
-                }
+            TokenKind::Bang if val_type != VarType::Bool => {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::NonBoolBangUnary,
+                    Some(expr.right.get_loc().clone()),
+                ));
             }
             _ => {}
         }
@@ -1070,14 +2750,21 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
     fn visit_assign_expr(&mut self, expr: &AssignExpr) -> StaticAnalyzerExprRes {
         self.resolve_local(&expr.loc, &expr.name)?;
 
-        self.resolve_expr(&expr.value)?;
-        let lhs_type = self.get_var_type(&expr.name, &expr.loc)?;
-        let value_type = expr.value.accept(self)?;
+        if let Some(kind) = self.callable_kind(&expr.name) {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::AssignToCallable(expr.name.to_string(), kind.to_string()),
+                Some(expr.loc.clone()),
+            ));
+        }
+
+        // Resolved only once, it can be another assignment: a = b = 5
+        let value_type = self.resolve_expr(&expr.value)?;
+        let lhs_type = self.get_declared_var_type(&expr.name, &expr.loc)?;
 
         if lhs_type != value_type {
             if lhs_type == VarType::Any {
-                self.update_var_type(&expr.name, value_type, &expr.loc);
-            } else if !StaticAnalyzer::is_castable(&value_type, &lhs_type) {
+                self.update_var_type(&expr.name, value_type.clone(), &expr.loc);
+            } else if !self.is_castable(&value_type, &lhs_type) {
                 return Err(RizonResult::new(
                     StaticAnalyzerErr::WrongTypeAssign(value_type.to_string(), lhs_type.to_string()),
                     Some(expr.value.get_loc()),
@@ -1085,32 +2772,96 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
             }
         }
 
+        self.forget_narrowing(&expr.name, &value_type);
+
         Ok(lhs_type)
     }
 
     fn visit_logical_expr(&mut self, expr: &LogicalExpr) -> StaticAnalyzerExprRes {
-        let rhs_type = self.resolve_expr(&expr.right)?;
-        let lhs_type = self.resolve_expr(&expr.left)?;
+        // Null coalescing: a ?? b
+        if expr.operator.kind == TokenKind::QuestionQuestion {
+            let rhs_type = self.resolve_expr(&expr.right)?;
+            let lhs_type = self.resolve_expr(&expr.left)?;
+
+            return match lhs_type {
+                VarType::Optional(t) if *t == rhs_type || self.is_castable(&rhs_type, &t) => {
+                    Ok(*t)
+                }
+                VarType::Null => Ok(rhs_type),
+                t if t == rhs_type => Ok(t),
+                t => Err(RizonResult::new(
+                    StaticAnalyzerErr::InvalidOp("??".into(), t.to_string(), rhs_type.to_string()),
+                    Some(expr.loc.clone()),
+                )),
+            };
+        }
 
-        if lhs_type != rhs_type {
-            return Err(RizonResult::new(
-                StaticAnalyzerErr::WrongTypeLogical(lhs_type.to_string(), lhs_type.to_string()),
-                Some(expr.loc.clone()),
-            ));
+        self.resolve_condition(&expr.left)?;
+        self.resolve_condition(&expr.right)?;
+
+        Ok(VarType::Bool)
+    }
+
+    fn visit_chained_compare_expr(&mut self, expr: &ChainedCompareExpr) -> StaticAnalyzerExprRes {
+        let mut lhs_type = self.resolve_expr(&expr.operands[0])?.into_fn_return_type();
+
+        // Each link is checked as a comparison of its two operands
+        for (i, operator) in expr.operators.iter().enumerate() {
+            let (lhs, rhs) = (&expr.operands[i], &expr.operands[i + 1]);
+            let rhs_type = self.resolve_expr(rhs)?.into_fn_return_type();
+
+            let loc = Loc::span(&lhs.get_loc(), &rhs.get_loc());
+            self.resolve_ordering(&lhs_type, &rhs_type, operator, loc)?;
+
+            lhs_type = rhs_type;
         }
 
-        Ok(rhs_type)
+        Ok(VarType::Bool)
     }
 
     fn visit_call_expr(&mut self, expr: &CallExpr) -> StaticAnalyzerExprRes {
         let callee_type = self.resolve_expr(&expr.callee)?;
 
-        let call_args: Vec<VarType> = expr
+        let mut call_args: Vec<VarType> = expr
             .args
             .iter()
             .map(|a| a.accept(self))
             .collect::<Result<_, _>>()?;
 
+        let has_named_args = expr.arg_names.iter().any(|n| n.is_some());
+
+        let is_native = matches!(self.get_native_name(&expr.callee).map(|n| n.as_str()), Some("dbg" | "map" | "to_array" | "to_map" | "format"))
+            || self.get_native_overloads(&expr.callee).is_some();
+
+        if has_named_args && is_native {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::NamedArgsNotAllowed,
+                Some(expr.loc.clone()),
+            ));
+        }
+
+        match self.get_native_name(&expr.callee).map(|n| n.as_str()) {
+            // 'dbg' is transparent, typed as its argument
+            Some("dbg") => {
+                return match <[VarType; 1]>::try_from(call_args) {
+                    Ok([arg]) => Ok(arg),
+                    Err(args) => Err(RizonResult::new(
+                        StaticAnalyzerErr::WrongArgsNb(1, args.len()),
+                        Some(expr.loc.clone()),
+                    )),
+                };
+            }
+            Some("map") => return self.resolve_map_call(&call_args, &expr.loc),
+            Some("to_array") => return StaticAnalyzer::resolve_to_array_call(&call_args, &expr.loc),
+            Some("to_map") => return StaticAnalyzer::resolve_to_map_call(&call_args, &expr.loc),
+            Some("format") => return StaticAnalyzer::resolve_format_call(expr, &call_args),
+            _ => {}
+        }
+
+        if let Some(overloads) = self.get_native_overloads(&expr.callee) {
+            return self.resolve_overload(overloads, &call_args, &expr.loc);
+        }
+
         let fn_ctx = match &callee_type {
             VarType::Struct(s) => {
                 let typedef = self.get_type_def(s, &expr.loc)?;
@@ -1136,13 +2887,21 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
             }
             VarType::Fn(f) => f,
             _ => {
-                return Err(RizonResult::new(
-                    StaticAnalyzerErr::NonFnCall,
-                    Some(expr.callee.get_loc()),
-                ))
+                let err = match &*expr.callee {
+                    Expr::Identifier(i) if self.get_type_def(&i.name, &i.loc).is_ok() => {
+                        StaticAnalyzerErr::ShadowedTypeCall(i.name.to_string())
+                    }
+                    _ => StaticAnalyzerErr::NonFnCall,
+                };
+
+                return Err(RizonResult::new(err, Some(expr.callee.get_loc())));
             }
         };
 
+        if has_named_args {
+            call_args = StaticAnalyzer::order_named_args(fn_ctx, expr, call_args)?;
+        }
+
         if fn_ctx.args_type.len() != expr.args.len() {
             return Err(RizonResult::new(
                 StaticAnalyzerErr::WrongArgsNb(fn_ctx.args_type.len(), expr.args.len()),
@@ -1150,13 +2909,19 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
             ));
         }
 
+        let return_type = StaticAnalyzer::call_return_type(&callee_type, &call_args);
+
         for (mut call_arg, arg_decl) in call_args.into_iter().zip(&fn_ctx.args_type) {
             // If we dont wait for a function as arg, we collapse it to the return value
             if !matches!(arg_decl, VarType::Fn(_)) {
                 call_arg = call_arg.into_fn_return_type();
             }
 
-            if &call_arg != arg_decl && !StaticAnalyzer::is_castable(&call_arg, arg_decl) {
+            // Parameters of type 'any' accept all values
+            if arg_decl != &VarType::Any
+                && &call_arg != arg_decl
+                && !self.is_castable(&call_arg, arg_decl)
+            {
                 return Err(RizonResult::new(
                     StaticAnalyzerErr::WrongArgsType(arg_decl.to_string(), call_arg.to_string()),
                     Some(expr.loc.clone()),
@@ -1169,7 +2934,7 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
             Ok(())
         })?;
 
-        Ok(callee_type.into_fn_return_type())
+        Ok(return_type)
     }
 
     fn visit_get_expr(&mut self, expr: &GetExpr) -> StaticAnalyzerExprRes {
@@ -1189,6 +2954,19 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
             return type_info.get_member_type(&expr.name);
         }
 
+        if let VarType::Enum(e) = &obj_type {
+            if let Some(enum_info) = self.get_enum_def(e) {
+                return enum_info.get_variant_type(&expr.name);
+            }
+        }
+
+        if let VarType::Optional(_) = &obj_type {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::NullableFieldAccess(obj_type.to_string()),
+                Some(expr.loc.clone()),
+            ));
+        }
+
         Err(RizonResult::new(
             StaticAnalyzerErr::NonStructFieldAccess,
             Some(expr.loc.clone()),
@@ -1199,25 +2977,31 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
         let obj_type = self.resolve_expr(&expr.object)?;
         let value_type = self.resolve_expr(&expr.value)?;
 
+        // Like variables assignments, the field type is returned: a.x = b.y = 5
         if let VarType::Struct(struct_name) = &obj_type {
-            let struct_type = self.get_type_def(&struct_name, &expr.loc)?;
+            let struct_type = self.get_type_def(struct_name, &expr.loc)?;
 
             let member_type = struct_type.get_member_type(&expr.name)?;
 
-            if member_type != value_type && !StaticAnalyzer::is_castable(&value_type, &member_type) {
+            if member_type != value_type && !self.is_castable(&value_type, &member_type) {
                 return Err(RizonResult::new(
                     StaticAnalyzerErr::WrongTypeAssign(value_type.to_string(), member_type.to_string()),
                     Some(expr.value.get_loc()),
                 ));
             }
+
+            Ok(member_type)
+        } else if let VarType::Optional(_) = &obj_type {
+            Err(RizonResult::new(
+                StaticAnalyzerErr::NullableFieldAccess(obj_type.to_string()),
+                Some(expr.loc.clone()),
+            ))
         } else {
-            return Err(RizonResult::new(
+            Err(RizonResult::new(
                 StaticAnalyzerErr::NonStructFieldAccess,
                 Some(expr.loc.clone()),
-            ));
+            ))
         }
-
-        Ok(obj_type)
     }
 
     fn visit_self_expr(&mut self, expr: &SelfExpr) -> StaticAnalyzerExprRes {
@@ -1237,11 +3021,23 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
 
     fn visit_is_expr(&mut self, expr: &IsExpr) -> StaticAnalyzerExprRes {
         let left_type = self.resolve_expr(&expr.left)?;
-        self.check_type_exists(&expr.typ.value, &expr.loc)?;
 
-        let right_type: VarType = (&expr.typ).into();
+        // 'null' isn't a type that can be declared but nullable values can be checked against it
+        match &expr.typ {
+            VarTypeDecl::Identifier(t) if t.kind == TokenKind::Null => {}
+            VarTypeDecl::Identifier(t) => self.check_type_exists(&t.value, &expr.loc)?,
+            t => self.check_type_decl_exists(t)?,
+        }
+
+        let right_type = self.normalize_type((&expr.typ).into());
 
-        if left_type != right_type {
+        let is_nullable_check = match &left_type {
+            VarType::Optional(t) => **t == right_type || right_type == VarType::Null,
+            _ => false,
+        };
+
+        // Values of type 'any' are checked at runtime
+        if left_type != right_type && !is_nullable_check && left_type != VarType::Any {
             return Err(RizonResult::new(
                 StaticAnalyzerErr::WrongVarType(right_type.to_string()),
                 Some(expr.left.get_loc()),
@@ -1250,4 +3046,640 @@ impl VisitExpr<VarType, StaticAnalyzerErr> for StaticAnalyzer {
 
         Ok(VarType::Bool)
     }
+
+    fn visit_cast_expr(&mut self, expr: &CastExpr) -> StaticAnalyzerExprRes {
+        let from_type = self.resolve_expr(&expr.expr)?;
+        self.check_type_exists(&expr.typ.value, &expr.loc)?;
+
+        let to_type = self.normalize_type((&expr.typ).into());
+
+        match (&from_type, &to_type) {
+            (t1, t2) if t1 == t2 => Ok(to_type),
+            (VarType::Int, VarType::Float) | (VarType::Float, VarType::Int) => Ok(to_type),
+            // Checked at runtime
+            (VarType::Any, _) => Ok(to_type),
+            _ => Err(RizonResult::new(
+                StaticAnalyzerErr::InvalidCast(from_type.to_string(), to_type.to_string()),
+                Some(expr.loc.clone()),
+            )),
+        }
+    }
+
+    fn visit_if_expr(&mut self, expr: &IfExpr) -> StaticAnalyzerExprRes {
+        self.resolve_condition(&expr.condition)?;
+        let then_type = self.resolve_expr(&expr.then_expr)?;
+
+        let Some(else_expr) = &expr.else_expr else {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::NonExhaustiveIfExpr,
+                Some(expr.loc.clone()),
+            ));
+        };
+
+        let else_type = self.resolve_expr(else_expr)?;
+
+        match (&then_type, &else_type) {
+            (t1, t2) if t1 == t2 => Ok(then_type),
+            (VarType::Int, VarType::Float) | (VarType::Float, VarType::Int) => Ok(VarType::Float),
+            _ => Err(RizonResult::new(
+                StaticAnalyzerErr::MismatchedIfExprBranches(then_type.to_string(), else_type.to_string()),
+                Some(else_expr.get_loc()),
+            )),
+        }
+    }
+
+    fn visit_array_literal_expr(&mut self, expr: &ArrayLiteralExpr) -> StaticAnalyzerExprRes {
+        let mut values_type = VarType::Any;
+
+        for (i, v) in expr.values.iter().enumerate() {
+            let value_type = self.resolve_expr(v)?;
+
+            if i == 0 {
+                values_type = value_type;
+            } else if value_type != values_type {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::MixedArrayTypes(values_type.to_string(), value_type.to_string()),
+                    Some(v.get_loc()),
+                ));
+            }
+        }
+
+        Ok(VarType::Array(Box::new(values_type)))
+    }
+
+    fn visit_map_literal_expr(&mut self, expr: &MapLiteralExpr) -> StaticAnalyzerExprRes {
+        let mut keys_type = VarType::Any;
+        let mut values_type = VarType::Any;
+
+        for (i, (k, v)) in expr.entries.iter().enumerate() {
+            let key_type = self.resolve_expr(k)?;
+            let value_type = self.resolve_expr(v)?;
+
+            if i == 0 {
+                keys_type = key_type;
+                values_type = value_type;
+            } else if key_type != keys_type || value_type != values_type {
+                let expected = VarType::Map(Box::new(keys_type), Box::new(values_type));
+                let found = VarType::Map(Box::new(key_type), Box::new(value_type));
+
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::MixedMapTypes(expected.to_string(), found.to_string()),
+                    Some(k.get_loc()),
+                ));
+            }
+        }
+
+        Ok(VarType::Map(Box::new(keys_type), Box::new(values_type)))
+    }
+
+    fn visit_struct_literal_expr(&mut self, expr: &StructLiteralExpr) -> StaticAnalyzerExprRes {
+        let strukt_type = self.resolve_expr(&expr.strukt)?;
+
+        // An instance has the type of its structure but isn't named like it
+        let struct_name = match (&strukt_type, &*expr.strukt) {
+            (VarType::Struct(s), Expr::Identifier(i)) if *s == i.name => s.clone(),
+            _ => {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::NonStructLiteral(expr.strukt.to_string()),
+                    Some(expr.strukt.get_loc()),
+                ))
+            }
+        };
+
+        let mut initialized: HashSet<&EcoString> = HashSet::new();
+
+        for (name, value) in &expr.fields {
+            let value_type = self.resolve_expr(value)?;
+            let type_def = self.get_type_def(&struct_name, &expr.loc)?;
+
+            let Some(field_type) = type_def.fields.get(&name.value) else {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::InexistantField(struct_name.to_string(), name.value.to_string()),
+                    Some(name.loc.clone()),
+                ));
+            };
+
+            if !initialized.insert(&name.value) {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::FieldInitTwice(name.value.to_string()),
+                    Some(name.loc.clone()),
+                ));
+            }
+
+            if field_type != &VarType::Any
+                && field_type != &value_type
+                && !self.is_castable(&value_type, field_type)
+            {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::WrongTypeAssign(value_type.to_string(), field_type.to_string()),
+                    Some(value.get_loc()),
+                ));
+            }
+        }
+
+        let type_def = self.get_type_def(&struct_name, &expr.loc)?;
+
+        if let Some(field) = type_def.required_fields.iter().find(|f| !initialized.contains(f)) {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::UninitializedField(struct_name.to_string(), field.to_string()),
+                Some(expr.loc.clone()),
+            ));
+        }
+
+        Ok(strukt_type)
+    }
+
+    fn visit_index_expr(&mut self, expr: &IndexExpr) -> StaticAnalyzerExprRes {
+        self.index_type(&expr.object, &expr.index, &expr.loc)
+    }
+
+    fn visit_index_set_expr(&mut self, expr: &IndexSetExpr) -> StaticAnalyzerExprRes {
+        let value_type = self.index_type(&expr.object, &expr.index, &expr.loc)?;
+        let assigned_type = self.resolve_expr(&expr.value)?;
+
+        if value_type != VarType::Any
+            && assigned_type != value_type
+            && !self.is_castable(&assigned_type, &value_type)
+        {
+            return Err(RizonResult::new(
+                StaticAnalyzerErr::WrongTypeAssign(assigned_type.to_string(), value_type.to_string()),
+                Some(expr.value.get_loc()),
+            ));
+        }
+
+        self.record_widening(&assigned_type, &value_type, expr.value.get_loc());
+
+        Ok(value_type)
+    }
+
+    fn visit_range_expr(&mut self, expr: &RangeExpr) -> StaticAnalyzerExprRes {
+        for bound in [&expr.start, &expr.end] {
+            let bound_type = self.resolve_expr(bound)?;
+
+            if bound_type != VarType::Int {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::NonIntRangeBound(bound_type.to_string()),
+                    Some(bound.get_loc()),
+                ));
+            }
+        }
+
+        if let Some(step) = &expr.step {
+            let step_type = self.resolve_expr(step)?;
+
+            if step_type != VarType::Int {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::NonIntRangeStep(step_type.to_string()),
+                    Some(step.get_loc()),
+                ));
+            }
+
+            // Computed steps are checked at runtime
+            if StaticAnalyzer::int_literal_value(step) == Some(0) {
+                return Err(RizonResult::new(
+                    StaticAnalyzerErr::ZeroRangeStep,
+                    Some(step.get_loc()),
+                ));
+            }
+        }
+
+        Ok(VarType::Range)
+    }
+}
+
+// Splits a format string around its '{}' placeholders, '{{' and '}}' being
+// the escapes of the braces themselves. There is always one more segment
+// than there are placeholders
+pub fn format_segments(fmt: &str) -> Vec<String> {
+    let mut segments = vec![String::new()];
+    let mut chars = fmt.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('}')) => {
+                chars.next();
+                segments.push(String::new());
+            }
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                segments.last_mut().unwrap().push(c);
+            }
+            _ => segments.last_mut().unwrap().push(c),
+        }
+    }
+
+    segments
+}
+
+// "1 placeholder", "2 placeholders"
+pub fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("{} {}", count, noun),
+        _ => format!("{} {}s", count, noun),
+    }
+}
+
+pub fn plural_given(count: usize) -> String {
+    match count {
+        1 => format!("{} was", plural(count, "argument")),
+        _ => format!("{} were", plural(count, "argument")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rizon_frontend::{ast::stmt::Stmt, lexer::Lexer, parser::Parser};
+    use rizon_tools::results::Loc;
+
+    use super::{
+        format_segments, FnType, RizonResResolv, StaticAnalyzer, StaticAnalyzerErr,
+        StaticAnalyzerWarning, VarType, WarningLevel,
+    };
+
+    fn resolve(
+        analyzer: &mut StaticAnalyzer,
+        code: &str,
+    ) -> Result<HashMap<Loc, usize>, Vec<RizonResResolv>> {
+        let tokens = Lexer::new().tokenize(code).unwrap();
+        let nodes = Parser::default().parse(tokens).unwrap();
+
+        analyzer.resolve(&nodes)
+    }
+
+    fn warnings_kinds(analyzer: &mut StaticAnalyzer) -> Vec<StaticAnalyzerWarning> {
+        analyzer
+            .take_warnings()
+            .into_iter()
+            .map(|w| match w.err {
+                StaticAnalyzerErr::Warning(w) => w.kind,
+                e => panic!("expected a warning, found: {}", e),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn format_segments_unescape_braces() {
+        assert_eq!(format_segments("a {} b"), vec!["a ", " b"]);
+        assert_eq!(format_segments("{{}} {}"), vec!["{} ", ""]);
+        assert_eq!(format_segments("{{{}}}"), vec!["{", "}"]);
+        assert_eq!(format_segments("{ }"), vec!["{ }"]);
+    }
+
+    const WARNINGS_CODE: &str = "
+var a = 1 < 2.
+
+fn foo() -> int {
+    return 1
+    print a
+}
+";
+
+    #[test]
+    fn warnings_default_level() {
+        let mut analyzer = StaticAnalyzer::default();
+
+        assert!(resolve(&mut analyzer, WARNINGS_CODE).is_ok());
+        assert_eq!(
+            warnings_kinds(&mut analyzer),
+            vec![
+                StaticAnalyzerWarning::CompIntFloat,
+                StaticAnalyzerWarning::UnreachAfterReturn
+            ]
+        );
+    }
+
+    #[test]
+    fn struct_layouts_in_declaration_order() {
+        let mut analyzer = StaticAnalyzer::default();
+        let code = "
+struct Point {
+    y: float
+    x: int
+    tags: [str]
+
+    fn norm() -> float { return 0. }
+    fn moved(dx: int, dy: float) -> Point { return Point() }
+}
+
+struct Empty {}
+";
+
+        assert!(resolve(&mut analyzer, code).is_ok());
+
+        let layouts = analyzer.struct_layouts();
+        let names: Vec<&str> = layouts.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["Empty", "Point"]);
+
+        let point = analyzer.struct_layout("Point").unwrap();
+        assert_eq!(
+            point.fields,
+            [
+                ("y".into(), VarType::Float),
+                ("x".into(), VarType::Int),
+                ("tags".into(), VarType::Array(Box::new(VarType::Str))),
+            ]
+        );
+        assert_eq!(
+            point.methods,
+            [
+                ("norm".into(), FnType::new(vec![], VarType::Float)),
+                (
+                    "moved".into(),
+                    FnType::new(vec![VarType::Int, VarType::Float], VarType::Struct("Point".into()))
+                ),
+                // Implicit constructor
+                ("init".into(), FnType::default()),
+            ]
+        );
+
+        assert!(analyzer.struct_layout("int").is_none());
+        assert!(analyzer.struct_layout("Unknown").is_none());
+    }
+
+    #[test]
+    fn division_by_literal_zero_warning() {
+        let mut analyzer = StaticAnalyzer::default();
+        let code = "
+var a = 1
+var b = a / 0 + a % (-0)
+var c = a / 2 + a % a + 1.5 / 0 + a / 0.";
+
+        assert!(resolve(&mut analyzer, code).is_ok());
+
+        let warnings = analyzer.take_warnings();
+        let locs: Vec<&str> = warnings
+            .iter()
+            .map(|w| &code[w.loc.as_ref().unwrap().start..=w.loc.as_ref().unwrap().end])
+            .collect();
+
+        assert_eq!(locs, ["0", "(-0)"]);
+    }
+
+    #[test]
+    fn unreachable_after_break_or_continue() {
+        let mut analyzer = StaticAnalyzer::default();
+        let code = "
+while true {
+    break
+    print 1
+    print 2
+}
+for i in 0..3 {
+    if i == 1 { continue }
+    continue
+    print i
+}";
+
+        assert!(resolve(&mut analyzer, code).is_ok());
+
+        let warnings = analyzer.take_warnings();
+        let locs: Vec<&str> = warnings
+            .iter()
+            .map(|w| code[w.loc.as_ref().unwrap().start..=w.loc.as_ref().unwrap().end].trim_end())
+            .collect();
+
+        assert_eq!(locs, ["print 1", "print i"]);
+        assert!(warnings.iter().all(|w| matches!(
+            &w.err,
+            StaticAnalyzerErr::Warning(w) if w.kind == StaticAnalyzerWarning::UnreachAfterBreak
+        )));
+    }
+
+    #[test]
+    fn allow_warning() {
+        let mut analyzer = StaticAnalyzer::default();
+        analyzer.set_warning_level(StaticAnalyzerWarning::CompIntFloat, WarningLevel::Allow);
+
+        assert!(resolve(&mut analyzer, WARNINGS_CODE).is_ok());
+        assert_eq!(
+            warnings_kinds(&mut analyzer),
+            vec![StaticAnalyzerWarning::UnreachAfterReturn]
+        );
+    }
+
+    #[test]
+    fn deny_warning() {
+        let mut analyzer = StaticAnalyzer::default();
+        analyzer.set_warning_level(StaticAnalyzerWarning::UnreachAfterReturn, WarningLevel::Deny);
+
+        let errors = resolve(&mut analyzer, WARNINGS_CODE).err().unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0].err,
+            StaticAnalyzerErr::DeniedWarning(w) if w.kind == StaticAnalyzerWarning::UnreachAfterReturn
+        ));
+        assert_eq!(
+            warnings_kinds(&mut analyzer),
+            vec![StaticAnalyzerWarning::CompIntFloat]
+        );
+    }
+
+    #[test]
+    fn implicit_any_warning() {
+        let code = "
+var a
+var b: int
+var c = 1
+for i in 0..2 {}";
+
+        // Opt-in
+        let mut analyzer = StaticAnalyzer::default();
+        resolve(&mut analyzer, code).unwrap();
+        assert!(warnings_kinds(&mut analyzer).is_empty());
+
+        let mut analyzer = StaticAnalyzer::default();
+        analyzer.set_warning_level(StaticAnalyzerWarning::ImplicitAny, WarningLevel::Warn);
+        resolve(&mut analyzer, code).unwrap();
+
+        let warnings = analyzer.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].loc, Some(Loc::new(5, 5)));
+    }
+
+    #[test]
+    fn list_warnings() {
+        let analyzer = StaticAnalyzer::default();
+
+        for w in StaticAnalyzerWarning::all() {
+            assert_eq!(analyzer.warning_level(*w), w.default_level());
+        }
+
+        assert_eq!(analyzer.warning_level(StaticAnalyzerWarning::ImplicitAny), WarningLevel::Allow);
+        assert_eq!(analyzer.warning_level(StaticAnalyzerWarning::CompIntFloat), WarningLevel::Warn);
+    }
+
+    #[test]
+    fn reset_between_programs() {
+        let mut analyzer = StaticAnalyzer::default();
+        analyzer.set_warning_level(StaticAnalyzerWarning::CompIntFloat, WarningLevel::Allow);
+
+        let first = "
+fn foo(a: int) -> int {
+    return a
+}
+var b = 1 < 2.";
+        let first_locals = resolve(&mut analyzer, first).unwrap();
+        assert_eq!(first_locals.len(), 1);
+        assert!(analyzer.take_warnings().is_empty());
+
+        analyzer.reset();
+
+        // Same names, different types
+        let second = "
+var foo = \"foo\"
+var b: str = foo";
+        let second_locals = resolve(&mut analyzer, second).unwrap();
+        assert!(second_locals.is_empty());
+
+        analyzer.reset();
+
+        let errors = resolve(&mut analyzer, "print foo").err().unwrap();
+        assert!(matches!(&errors[0].err, StaticAnalyzerErr::UndeclaredVar(v) if v == "foo"));
+
+        // Natives are restored from the snapshot
+        analyzer.reset();
+        assert!(resolve(&mut analyzer, "var s: str = to_upper(\"a\")\nprint len(s) + to_int(sqrt(4.))").is_ok());
+    }
+
+    #[test]
+    fn implicit_numeric_widening() {
+        let mut analyzer = StaticAnalyzer::default();
+        assert!(resolve(&mut analyzer, "var x: float = 5").is_ok());
+
+        analyzer.reset();
+        analyzer.set_implicit_numeric_widening(false);
+
+        let errors = resolve(&mut analyzer, "var x: float = 5").err().unwrap();
+        assert!(matches!(&errors[0].err, StaticAnalyzerErr::WrongTypeAssign(..)));
+
+        analyzer.reset();
+        assert!(resolve(&mut analyzer, "var x: float = 5 as float").is_ok());
+
+        analyzer.reset();
+        let code = "
+fn half(v: float) -> float {
+    return v / 2.
+}
+print half(5)";
+        assert!(resolve(&mut analyzer, code).is_err());
+
+        // Returns in branches aren't unified to the declared type either
+        analyzer.reset();
+        let code = "
+fn pick(c: bool) -> float {
+    if c { return 1 } else { return 2.0 }
+}";
+        let errors = resolve(&mut analyzer, code).err().unwrap();
+        assert!(matches!(&errors[0].err, StaticAnalyzerErr::WrongReturnType(..)));
+    }
+
+    #[test]
+    fn recorded_expression_types() {
+        let mut analyzer = StaticAnalyzer::default();
+        analyzer.set_record_types(true);
+
+        resolve(&mut analyzer, "var x = 1 + 2.0").unwrap();
+
+        let types = analyzer.take_expr_types().unwrap();
+        assert_eq!(types.get(&Loc::new(8, 14)), Some(&VarType::Float));
+        assert_eq!(types.get(&Loc::new(8, 8)), Some(&VarType::Int));
+
+        // Still recording, the next program starts empty
+        assert_eq!(analyzer.expr_types().map(|t| t.len()), Some(0));
+
+        analyzer.set_record_types(false);
+        resolve(&mut analyzer, "var y = 1 + 2.0").unwrap();
+        assert!(analyzer.take_expr_types().is_none());
+    }
+
+    #[test]
+    fn if_directive_blocks() {
+        let code = "
+#if DEBUG {
+    var a: int = \"wrong\"
+} #endif";
+
+        let mut lexer = Lexer::new();
+        let nodes = Parser::default().parse(lexer.tokenize(code).unwrap()).unwrap();
+        assert!(StaticAnalyzer::default().resolve(&nodes).is_ok());
+
+        let mut lexer = Lexer::new();
+        lexer.set_features(["DEBUG".into()].into());
+        let nodes = Parser::default().parse(lexer.tokenize(code).unwrap()).unwrap();
+        let errors = StaticAnalyzer::default().resolve(&nodes).err().unwrap();
+
+        assert!(matches!(errors[0].err, StaticAnalyzerErr::WrongTypeAssign(..)));
+    }
+
+    #[test]
+    fn const_eval_folds_integer_expressions() {
+        let eval = |code: &str| {
+            let nodes = Parser::default().parse(Lexer::new().tokenize(code).unwrap()).unwrap();
+
+            match &nodes[0] {
+                Stmt::Expr(s) => StaticAnalyzer::const_eval(&s.expr).map_err(|e| e.err),
+                s => panic!("expected an expression statement, found: {:?}", s),
+            }
+        };
+
+        assert!(matches!(eval("2 + 3"), Ok(5)));
+        assert!(matches!(eval("(2 + 3) * -4 / 2"), Ok(-10)));
+        assert!(matches!(eval("2 ** 3 ** 2"), Ok(512)));
+        assert!(matches!(eval("2 ** -1"), Err(StaticAnalyzerErr::NonConstVariantValue)));
+        assert!(matches!(eval("1 / 0"), Err(StaticAnalyzerErr::ConstDivisionByZero)));
+        assert!(matches!(eval("9223372036854775807 + 1"), Err(StaticAnalyzerErr::ConstOverflow)));
+        assert!(matches!(eval("1.5 + 2"), Err(StaticAnalyzerErr::NonConstVariantValue)));
+        assert!(matches!(eval("a + 1"), Err(StaticAnalyzerErr::NonConstVariantValue)));
+    }
+
+    #[test]
+    fn multi_line_spans_are_underlined_on_their_first_line() {
+        let code = "var a: str = [\n    1,\n]";
+        let errors = resolve(&mut StaticAnalyzer::default(), code).unwrap_err();
+
+        let snippet = errors[0].snippet(code).unwrap();
+        let lines: Vec<&str> = snippet.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("var a: str = ["));
+        assert_eq!(lines[1].matches('^').count(), 1);
+    }
+
+    #[test]
+    fn return_without_value_in_non_void_function() {
+        let errors = resolve(&mut StaticAnalyzer::default(), "fn f() -> int {\n    return\n}").unwrap_err();
+        assert!(matches!(&errors[0].err, StaticAnalyzerErr::MissingReturnValue(t) if t == "int"));
+
+        assert!(resolve(&mut StaticAnalyzer::default(), "fn f() {\n    return\n}").is_ok());
+        assert!(resolve(&mut StaticAnalyzer::default(), "fn f() -> void {\n    return\n}").is_ok());
+    }
+
+    #[test]
+    fn each_return_checked_once_against_declared_type() {
+        let mut analyzer = StaticAnalyzer::default();
+        let code = "
+fn pick(first: bool) -> int {
+    if first {
+        return 1
+    }
+
+    return 2.5
+}";
+        let errors = resolve(&mut analyzer, code).unwrap_err();
+        assert!(matches!(
+            &errors[0].err,
+            StaticAnalyzerErr::WrongReturnType(expected, found) if expected == "int" && found == "float"
+        ));
+
+        // Top level return values are only resolved once
+        let code = "
+fn f() -> int {
+    return 1 / 0
+}";
+        assert!(resolve(&mut analyzer, code).is_ok());
+        assert_eq!(warnings_kinds(&mut analyzer), vec![StaticAnalyzerWarning::DivisionByZero]);
+    }
 }
@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+use rizon_frontend::ast::{
+    expr::Expr,
+    stmt::{BlockStmt, FnDeclStmt, MatchPattern, Stmt, VarDeclStmt},
+};
+use rizon_tools::results::Loc;
+use serde::Serialize;
+
+use crate::static_analyzer::VarType;
+
+
+// Node of the resolved tree exported for external tools. Offsets are in
+// characters like the 'Loc' they come from, 'end' is the last character
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TypedNode {
+    pub kind: &'static str,
+    pub start: usize,
+    pub end: usize,
+    // Inferred type of expressions, declared one of declarations
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+    // Name, literal value or operator
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TypedNode>,
+}
+
+impl TypedNode {
+    fn new(kind: &'static str, loc: Loc) -> Self {
+        Self { kind, start: loc.start, end: loc.end, typ: None, value: None, children: vec![] }
+    }
+
+    fn typ(mut self, typ: Option<String>) -> Self {
+        self.typ = typ;
+        self
+    }
+
+    fn value(mut self, value: impl ToString) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+
+    fn children(mut self, children: Vec<TypedNode>) -> Self {
+        self.children = children;
+        self
+    }
+}
+
+// Types are the ones recorded by the static analyzer, see 'set_record_types'
+pub fn typed_ast(stmts: &[Stmt], types: &HashMap<Loc, VarType>) -> Vec<TypedNode> {
+    let builder = TypedAstBuilder { types };
+
+    stmts.iter().map(|s| builder.stmt(s)).collect()
+}
+
+pub fn to_json(stmts: &[Stmt], types: &HashMap<Loc, VarType>) -> String {
+    serde_json::to_string_pretty(&typed_ast(stmts, types)).expect("typed nodes are always serializable")
+}
+
+struct TypedAstBuilder<'a> {
+    types: &'a HashMap<Loc, VarType>,
+}
+
+impl TypedAstBuilder<'_> {
+    fn stmt(&self, stmt: &Stmt) -> TypedNode {
+        let node = |kind| TypedNode::new(kind, stmt.get_loc());
+
+        match stmt {
+            Stmt::Expr(s) => node("expr_stmt").children(vec![self.expr(&s.expr)]),
+            Stmt::Print(s) => node("print").children(self.exprs(&s.exprs)),
+            Stmt::VarDecl(s) => self.var_decl(s),
+            Stmt::Block(s) => node("block").children(self.block(s)),
+            Stmt::If(s) => {
+                let mut children = vec![self.expr(&s.condition)];
+                children.extend(s.then_branch.iter().map(|b| self.block_node(b)));
+                children.extend(s.else_branch.iter().map(|b| self.block_node(b)));
+
+                node("if").children(children)
+            }
+            Stmt::While(s) => {
+                node("while").children(vec![self.expr(&s.condition), self.stmt(&s.body)])
+            }
+            Stmt::DoWhile(s) => {
+                node("do_while").children(vec![self.stmt(&s.body), self.expr(&s.condition)])
+            }
+            Stmt::For(s) => node("for").children(vec![
+                self.var_decl(&s.placeholder),
+                self.expr(&s.iterable),
+                self.stmt(&s.body),
+            ]),
+            Stmt::Break(_) => node("break"),
+            Stmt::Continue(_) => node("continue"),
+            Stmt::FnDecl(s) => self.fn_decl(s),
+            Stmt::Return(s) => node("return").children(s.value.iter().map(|v| self.expr(v)).collect()),
+            Stmt::Struct(s) => {
+                let mut children: Vec<TypedNode> = s.fields.iter().map(|f| self.var_decl(f)).collect();
+                children.extend(s.methods.iter().map(|m| self.fn_decl(m)));
+
+                node("struct").value(&s.name.value).children(children)
+            }
+            Stmt::EnumDecl(s) => {
+                let variants = s
+                    .variants
+                    .iter()
+                    .map(|v| {
+                        TypedNode::new("variant", v.name.loc.clone())
+                            .value(&v.name.value)
+                            .children(v.value.iter().map(|e| self.expr(e)).collect())
+                    })
+                    .collect();
+
+                node("enum").value(&s.name.value).children(variants)
+            }
+            Stmt::Match(s) => {
+                let mut children = vec![self.expr(&s.expr)];
+
+                for arm in &s.arms {
+                    let pattern = match &arm.pattern {
+                        MatchPattern::Wildcard(loc) => TypedNode::new("wildcard", loc.clone()),
+                        MatchPattern::Value(e) => self.expr(e),
+                    };
+                    let body = self.stmt(&arm.body);
+
+                    let loc = Loc::new(pattern.start, body.end);
+                    children.push(TypedNode::new("arm", loc).children(vec![pattern, body]));
+                }
+
+                node("match").children(children)
+            }
+            Stmt::TypeAlias(s) => node("type_alias")
+                .value(&s.name.value)
+                .typ(Some(s.typ.to_string())),
+        }
+    }
+
+    fn var_decl(&self, decl: &VarDeclStmt) -> TypedNode {
+        TypedNode::new("var_decl", decl.loc.clone())
+            .value(&decl.name.value)
+            .typ(decl.typ.as_ref().map(|t| t.to_string()))
+            .children(decl.value.iter().map(|v| self.expr(v)).collect())
+    }
+
+    fn fn_decl(&self, decl: &FnDeclStmt) -> TypedNode {
+        let params: Vec<TypedNode> = decl
+            .params
+            .iter()
+            .map(|p| TypedNode::new("param", p.name.loc.clone()).value(&p.name.value).typ(Some(p.typ.to_string())))
+            .collect();
+
+        let mut children = params;
+        children.extend(self.block(&decl.body));
+
+        TypedNode::new("fn_decl", decl.loc.clone())
+            .value(&decl.name.value)
+            .typ(decl.return_type.as_ref().map(|t| t.to_string()))
+            .children(children)
+    }
+
+    fn block(&self, block: &BlockStmt) -> Vec<TypedNode> {
+        block.stmts.iter().map(|s| self.stmt(s)).collect()
+    }
+
+    // Blocks have no location, they span their statements like in 'Stmt::get_loc'
+    fn block_node(&self, block: &BlockStmt) -> TypedNode {
+        let children = self.block(block);
+
+        let loc = match (children.first(), children.last()) {
+            (Some(first), Some(last)) => Loc::new(first.start, last.end),
+            _ => Loc::new(0, 0),
+        };
+
+        TypedNode::new("block", loc).children(children)
+    }
+
+    fn exprs(&self, exprs: &[Expr]) -> Vec<TypedNode> {
+        exprs.iter().map(|e| self.expr(e)).collect()
+    }
+
+    fn expr(&self, expr: &Expr) -> TypedNode {
+        let loc = expr.get_loc();
+        let typ = self.types.get(&loc).map(|t| t.to_string());
+        let node = |kind| TypedNode::new(kind, loc.clone()).typ(typ.clone());
+
+        match expr {
+            Expr::Binary(e) => node("binary")
+                .value(&e.operator.value)
+                .children(vec![self.expr(&e.left), self.expr(&e.right)]),
+            Expr::Grouping(e) => node("grouping").children(vec![self.expr(&e.expr)]),
+            Expr::IntLiteral(e) => node("int").value(e.value),
+            Expr::FloatLiteral(e) => node("float").value(format!("{:?}", e.value)),
+            Expr::StrLiteral(e) => node("str").value(&e.value),
+            Expr::CharLiteral(e) => node("char").value(e.value),
+            Expr::Identifier(e) => node("identifier").value(&e.name),
+            Expr::Unary(e) => node("unary")
+                .value(&e.operator.value)
+                .children(vec![self.expr(&e.right)]),
+            Expr::Assign(e) => node("assign").value(&e.name).children(vec![self.expr(&e.value)]),
+            Expr::CompoundAssign(e) => node("compound_assign")
+                .value(&e.operator.value)
+                .children(vec![self.expr(&e.target), self.expr(&e.value)]),
+            Expr::Logical(e) => node("logical")
+                .value(&e.operator.value)
+                .children(vec![self.expr(&e.left), self.expr(&e.right)]),
+            Expr::ChainedCompare(e) => {
+                let operators: Vec<&str> = e.operators.iter().map(|o| o.value.as_str()).collect();
+
+                node("chained_compare").value(operators.join(" ")).children(self.exprs(&e.operands))
+            }
+            Expr::Call(e) => {
+                let mut children = vec![self.expr(&e.callee)];
+                children.extend(self.exprs(&e.args));
+
+                node("call").children(children)
+            }
+            Expr::Get(e) => node("get").value(&e.name.value).children(vec![self.expr(&e.object)]),
+            Expr::Set(e) => node("set")
+                .value(&e.name.value)
+                .children(vec![self.expr(&e.object), self.expr(&e.value)]),
+            Expr::Selff(_) => node("self"),
+            Expr::Is(e) => node("is").value(&e.typ).children(vec![self.expr(&e.left)]),
+            Expr::Cast(e) => node("cast").value(&e.typ.value).children(vec![self.expr(&e.expr)]),
+            Expr::If(e) => {
+                let mut children = vec![self.expr(&e.condition), self.expr(&e.then_expr)];
+                children.extend(e.else_expr.iter().map(|els| self.expr(els)));
+
+                node("if_expr").children(children)
+            }
+            Expr::ArrayLiteral(e) => node("array").children(self.exprs(&e.values)),
+            Expr::MapLiteral(e) => {
+                let entries = e.entries.iter().flat_map(|(k, v)| [self.expr(k), self.expr(v)]).collect();
+
+                node("map").children(entries)
+            }
+            Expr::StructLiteral(e) => {
+                let mut children = vec![self.expr(&e.strukt)];
+                children.extend(e.fields.iter().map(|(name, v)| {
+                    let value = self.expr(v);
+                    let loc = Loc::new(name.loc.start, value.end);
+
+                    TypedNode::new("field", loc).value(&name.value).children(vec![value])
+                }));
+
+                node("struct_literal").children(children)
+            }
+            Expr::Index(e) => node("index").children(vec![self.expr(&e.object), self.expr(&e.index)]),
+            Expr::IndexSet(e) => node("index_set").children(vec![
+                self.expr(&e.object),
+                self.expr(&e.index),
+                self.expr(&e.value),
+            ]),
+            Expr::Range(e) => {
+                let mut children = vec![self.expr(&e.start), self.expr(&e.end)];
+                children.extend(e.step.iter().map(|s| self.expr(s)));
+
+                let op = if e.inclusive { "..=" } else { ".." };
+                node("range").value(op).children(children)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rizon_frontend::{lexer::Lexer, parser::Parser};
+
+    use super::{to_json, typed_ast};
+    use crate::StaticAnalyzer;
+
+    #[test]
+    fn expressions_have_inferred_types() {
+        let code = "var x = 1 + 2.5\nprint [x]";
+        let nodes = Parser::default().parse(Lexer::new().tokenize(code).unwrap()).unwrap();
+
+        let mut analyzer = StaticAnalyzer::default();
+        analyzer.set_record_types(true);
+        analyzer.resolve(&nodes).unwrap();
+
+        let types = analyzer.expr_types().unwrap();
+        let tree = typed_ast(&nodes, types);
+
+        let decl = &tree[0];
+        assert_eq!((decl.kind, decl.value.as_deref(), decl.typ.as_deref()), ("var_decl", Some("x"), None));
+
+        let sum = &decl.children[0];
+        assert_eq!((sum.kind, sum.value.as_deref(), sum.typ.as_deref()), ("binary", Some("+"), Some("float")));
+        assert_eq!((sum.start, sum.end), (8, 14));
+        assert_eq!(sum.children[0].typ.as_deref(), Some("int"));
+
+        let array = &tree[1].children[0];
+        assert_eq!((array.kind, array.typ.as_deref()), ("array", Some("[float]")));
+
+        assert!(to_json(&nodes, types).contains("\"type\": \"[float]\""));
+    }
+
+    #[test]
+    fn types_are_recorded_on_demand() {
+        let nodes = Parser::default().parse(Lexer::new().tokenize("var x = 1").unwrap()).unwrap();
+
+        let mut analyzer = StaticAnalyzer::default();
+        analyzer.resolve(&nodes).unwrap();
+        assert!(analyzer.expr_types().is_none());
+    }
+}
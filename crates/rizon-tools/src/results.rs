@@ -1,25 +1,109 @@
 use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
 use colored::*;
 
 
-#[derive(Debug, PartialEq, Default, Clone, Eq, Hash)]
+// Span in the source code, 'end' is the offset of the last character.
+// Offsets are in characters, the lexer also records where the same
+// characters start in bytes, for editors and language servers. Those are
+// 32 bits to keep the errors small
+#[derive(Debug, Default, Clone)]
 pub struct Loc {
     pub start: usize,
-    pub end: usize
+    pub end: usize,
+    pub byte_start: u32,
+    pub byte_end: u32,
+}
+
+// Byte offsets follow from the characters ones in a given code
+impl PartialEq for Loc {
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start && self.end == other.end
+    }
+}
+
+impl Eq for Loc {}
+
+impl Hash for Loc {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.start.hash(state);
+        self.end.hash(state);
+    }
 }
 
 impl Loc {
+    // For code where each character is one byte
     pub fn new(start: usize, end: usize) -> Self {
-        Self { start, end }
+        Self { start, end, byte_start: start as u32, byte_end: end as u32 }
+    }
+
+    // From the start of the first location to the end of the last one
+    pub fn span(first: &Loc, last: &Loc) -> Self {
+        Self {
+            start: first.start,
+            end: last.end,
+            byte_start: first.byte_start,
+            byte_end: last.byte_end,
+        }
     }
 
     pub fn new_len_one_from_start(loc: Loc) -> Self {
-        Self { start: loc.start, end: loc.start }
+        Self { start: loc.start, end: loc.start, byte_start: loc.byte_start, byte_end: loc.byte_start }
     }
     
     pub fn get_len(&self) -> usize {
         self.end - self.start + 1
     }
+
+    // Exclusive range, to slice the code directly. Only the length of
+    // the last character is looked up
+    pub fn byte_range(&self, code: &str) -> Range<usize> {
+        let (start, end) = (self.byte_start as usize, self.byte_end as usize);
+
+        let end = code
+            .get(end..)
+            .and_then(|rest| rest.chars().next())
+            .map_or(code.len(), |c| end + c.len_utf8());
+
+        start.min(code.len())..end
+    }
+}
+
+// Offsets in characters where each line of the code starts, to find the
+// line of a location without going through the code again
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    starts: Vec<usize>,
+}
+
+// Without code, every location is on the first line
+impl Default for LineIndex {
+    fn default() -> Self {
+        Self { starts: vec![0] }
+    }
+}
+
+impl LineIndex {
+    pub fn new(code: &str) -> Self {
+        let lines = code.chars().enumerate().filter(|(_, c)| *c == '\n').map(|(i, _)| i + 1);
+
+        Self { starts: std::iter::once(0).chain(lines).collect() }
+    }
+
+    // Line and column of the offset, both starting at 1
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.starts.partition_point(|&s| s <= offset);
+
+        (line, offset - self.starts[line - 1] + 1)
+    }
+}
+
+// Function call an error went through: the called function and where
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraceFrame {
+    pub name: String,
+    pub loc: Loc,
 }
 
 pub trait RizonReport {
@@ -37,50 +121,97 @@ struct ReportContext<'a> {
 pub struct RizonResult<T: RizonReport> {
     pub err: T,
     pub loc: Option<Loc>,
+    // Calls the error went through, innermost first
+    pub trace: Box<[TraceFrame]>,
 }
 
+// Deep recursions only show their innermost calls
+const MAX_TRACE_FRAMES: usize = 10;
+
 impl<'a, T: RizonReport> RizonResult<T> {
     pub fn new(err: T, loc: Option<Loc>) -> RizonResult<T> {
-        RizonResult { err, loc }
+        RizonResult { err, loc, trace: Box::default() }
     }
 
-    pub fn report(&self, file_name: &String, code: &str) {
+    pub fn with_trace(mut self, trace: Vec<TraceFrame>) -> Self {
+        self.trace = trace.into();
+        self
+    }
+
+    // Location of the error in bytes, for diagnostics sent to editors
+    pub fn byte_range(&self, code: &str) -> Option<Range<usize>> {
+        self.loc.as_ref().map(|l| l.byte_range(code))
+    }
+
+    pub fn report(&self, file_name: &str, code: &str) {
+        println!("{}", self.format_report(file_name, code));
+    }
+
+    // Same output as 'report', for callers that print it themselves
+    pub fn format_report(&self, file_name: &str, code: &str) -> String {
         // Error msg
-        println!("{}", self.err.get_err_msg());
+        let mut lines = vec![self.err.get_err_msg()];
 
         // Additional infos on location
         if let Some(loc) = &self.loc {
             let cx = self.get_context(code, loc);
-            let deco = self.get_decorators(&cx, loc);
 
-            println!("  {} {} [line {}]", "-->".cyan(), file_name, cx.line_nb);
+            lines.push(format!("  {} {} [line {}]", "-->".cyan(), file_name, cx.line_nb));
+            lines.push(self.format_snippet(&cx, loc));
+        }
 
-            if let Some(line) = cx.ctx_line {
-                let mut add_space = "";
+        // Innermost call first
+        for frame in self.trace.iter().take(MAX_TRACE_FRAMES) {
+            let cx = self.get_context(code, &frame.loc);
+            lines.push(format!("  {} in '{}' called at [line {}]", "-->".cyan(), frame.name, cx.line_nb));
+        }
 
-                if (cx.line_nb).to_string().len() - (cx.line_nb-1).to_string().len() == 1 {
-                    add_space = " ";
-                }
+        if self.trace.len() > MAX_TRACE_FRAMES {
+            lines.push(format!("  {} ... {} more calls", "-->".cyan(), self.trace.len() - MAX_TRACE_FRAMES));
+        }
 
-                println!(" {} {}", format!("{}{} |", add_space, cx.line_nb-1).cyan(), line);
-            }
+        lines.join("\n")
+    }
+
+    // Line of the error, preceded by the previous one for context, with
+    // the span underlined. Without location, there is nothing to show
+    pub fn snippet(&self, code: &str) -> Option<String> {
+        self.loc.as_ref().map(|loc| self.format_snippet(&self.get_context(code, loc), loc))
+    }
+
+    fn format_snippet(&self, cx: &ReportContext, loc: &Loc) -> String {
+        let mut lines = vec![];
+        let deco = self.get_decorators(cx, loc);
 
-            println!(" {} {}", format!("{} |", cx.line_nb).cyan(), cx.line);
+        if let Some(line) = cx.ctx_line {
+            let mut add_space = "";
 
-            // Here, 4 is for space at the beginning and between line nb and '|' and space again
-            let margin = cx.line_nb.to_string().len() + 4;
-            println!("{}{}", " ".repeat(margin), deco.red());
+            if (cx.line_nb).to_string().len() - (cx.line_nb-1).to_string().len() == 1 {
+                add_space = " ";
+            }
+
+            lines.push(format!(" {} {}", format!("{}{} |", add_space, cx.line_nb-1).cyan(), line));
         }
+
+        lines.push(format!(" {} {}", format!("{} |", cx.line_nb).cyan(), cx.line));
+
+        // Here, 4 is for space at the beginning and between line nb and '|' and space again
+        let margin = cx.line_nb.to_string().len() + 4;
+        lines.push(format!("{}{}", " ".repeat(margin), deco.red()));
+
+        lines.join("\n")
     }
 
-    fn get_context(&'a self, code: &'a str, loc: &Loc) -> ReportContext {
+    // Locations are offsets in characters, not in bytes
+    fn get_context(&'a self, code: &'a str, loc: &Loc) -> ReportContext<'a> {
         let mut offset: usize = 0;
         let mut lines: VecDeque<&'a str> = VecDeque::new();
 
         for (i, line) in code.split('\n').enumerate() {
             lines.push_back(line);
+            let line_len = line.chars().count();
 
-            if loc.start >= offset && loc.start < offset + line.len() + 1 {
+            if loc.start >= offset && loc.start < offset + line_len + 1 {
                 let ctx_line = if lines.len() == 2 { Some(lines.pop_front().unwrap()) } else { None };
 
                 return ReportContext {
@@ -95,7 +226,7 @@ impl<'a, T: RizonReport> RizonResult<T> {
                 }
 
                 // + 1 because we don't have '\n' anymore
-                offset += line.len() + 1;
+                offset += line_len + 1;
             }
         }
 
@@ -105,9 +236,13 @@ impl<'a, T: RizonReport> RizonResult<T> {
         )
     }
 
+    // Spans over several lines are underlined until the end of the first one
     fn get_decorators(&self, cx: &ReportContext, loc: &Loc) -> String {
-        let mut decorators = " ".repeat(loc.start - cx.offset);
-        let indicators = "^".repeat(loc.get_len());
+        let column = loc.start - cx.offset;
+        let len = loc.get_len().min(cx.line.chars().count().saturating_sub(column)).max(1);
+
+        let mut decorators = " ".repeat(column);
+        let indicators = "^".repeat(len);
 
         decorators.push_str(indicators.as_str());
         decorators
@@ -75,7 +75,7 @@ for dir in os.listdir():
                 if "-->" in line or " | " in line or "^" in line or line == "":
                     continue
                 elif "error" in line:
-                    rizon_err.append(line.split(": ")[1])
+                    rizon_err.append(line.split(": ", 1)[1])
                 else:
                     rizon_res.append(line.strip())
 